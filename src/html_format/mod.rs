@@ -0,0 +1,297 @@
+// Generates a self-contained HTML reference sheet for a layout, with a diagram per layer,
+// a searchable table of the characters it can type and its dead key tables.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::model::*;
+use crate::svg_format::SvgOptions;
+
+const SCRIPT: &str = "\
+document.getElementById('search').addEventListener('input', function () {
+    var query = this.value.toLowerCase();
+    document.querySelectorAll('#chars tbody tr').forEach(function (row) {
+        row.style.display = row.textContent.toLowerCase().indexOf(query) >= 0 ? '' : 'none';
+    });
+});";
+
+/// A character the layout can type, with the keystrokes to type it.
+struct CharEntry {
+    text: String,
+    is_dead_key: bool,
+    code_points: String,
+    keystrokes: String,
+}
+
+impl KeyboardDesc {
+    pub fn to_html(&self, options: &SvgOptions) -> String {
+        let title = self.title();
+        let style = style(options);
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        writeln!(html, "<title>{}</title>", escape(&title)).unwrap();
+        writeln!(html, "<style>\n{}\n</style>\n</head>\n<body>", style).unwrap();
+        writeln!(html, "<h1>{}</h1>", escape(&title)).unwrap();
+
+        self.write_metadata(&mut html);
+        self.write_layers(&mut html, options);
+        self.write_chars(&mut html);
+        self.write_dead_keys(&mut html);
+
+        writeln!(html, "<script>\n{}\n</script>\n</body>\n</html>", SCRIPT).unwrap();
+        html
+    }
+
+    fn write_metadata(&self, html: &mut String) {
+        let metadata = &self.metadata;
+        let yes_no = |value: bool| if value { "Yes" } else { "No" }.to_string();
+        let mut entries: Vec<(&str, Option<String>)> = vec![
+            ("Display name", metadata.display_name.clone()),
+            ("Company", metadata.company_name.clone()),
+            ("File version", metadata.file_version.clone()),
+            ("Product version", metadata.product_version.clone()),
+            ("Layout file", metadata.layout_file.clone()),
+            ("Layout text", metadata.layout_text.clone()),
+            ("KLID", metadata.klid.map(|klid| format!("{:08X}", klid))),
+            ("Locale", metadata.locale_name.clone()),
+            ("Locale identifier", metadata.locale_id.map(|locale_id| format!("{:04X}", locale_id))),
+            ("Layout identifier", metadata.layout_id.map(|layout_id| format!("{:04X}", layout_id))),
+            ("Architecture", metadata.machine.map(|machine| LayoutMetadata::machine_to_name(machine)
+                .map(str::to_string).unwrap_or_else(|| format!("{:04X}", machine)))),
+        ];
+        entries.extend([
+            ("Version", Some(self.version.to_string())),
+            ("AltGr", Some(yes_no(self.supports_altgr))),
+            ("Shift lock", Some(yes_no(self.supports_shift_lock))),
+            ("LRM/RLM", Some(yes_no(self.supports_directionality))),
+            ("Keyboard type", Some(format!("{}, subtype {}", self.type_value, self.subtype_value))),
+            ("Geometry", Some(self.geometry_kind().name().to_string())),
+        ]);
+
+        html.push_str("<h2>Properties</h2>\n<dl>\n");
+        for (name, value) in entries {
+            let Some(value) = value else { continue };
+            writeln!(html, "<dt>{}</dt><dd>{}</dd>", name, escape(&value)).unwrap();
+        }
+        html.push_str("</dl>\n");
+    }
+
+    fn write_layers(&self, html: &mut String, options: &SvgOptions) {
+        html.push_str("<h2>Layers</h2>\n");
+        for layer in &options.layers {
+            // Skip layers which no key types on, such as AltGr on layouts without it.
+            let is_used = self.virtual_keys.values().any(|key_effect| matches!(key_effect,
+                KeyEffect::Typing(key_typing) if key_typing.effect(&key_typing.kana_lock_modifiers(&layer.modifiers(), options.kana_lock), options.caps_lock).is_some()));
+            if !is_used { continue; }
+
+            let layer_options = SvgOptions { layers: vec![*layer], dead_key_diagrams: false, ..options.clone() };
+            let name = match layer.modifiers().describe(self.supports_altgr) {
+                modifiers if modifiers.is_empty() => "Base".to_string(),
+                modifiers => modifiers
+            };
+            let locks: Vec<&str> = [(options.caps_lock, "Caps Lock"), (options.kana_lock, "Kana Lock")].into_iter()
+                .filter_map(|(is_on, lock)| is_on.then_some(lock))
+                .collect();
+            let name = if locks.is_empty() { name } else { format!("{} with {}", name, locks.join(" and ")) };
+            writeln!(html, "<h3>{}</h3>", escape(&name)).unwrap();
+            html.push_str(&self.to_svg(&layer_options));
+        }
+    }
+
+    fn write_chars(&self, html: &mut String) {
+        html.push_str("<h2>Characters</h2>\n");
+        html.push_str("<input id=\"search\" type=\"search\" placeholder=\"Search characters, code points or keys\">\n");
+        html.push_str("<table id=\"chars\">\n<thead><tr><th>Character</th><th>Code points</th><th>Keystrokes</th></tr></thead>\n<tbody>\n");
+        for entry in self.char_entries() {
+            let class = if entry.is_dead_key { "char dead" } else { "char" };
+            writeln!(html, "<tr><td class=\"{}\">{}</td><td>{}</td><td>{}</td></tr>",
+                class, escape(&entry.text), entry.code_points, escape(&entry.keystrokes)).unwrap();
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    fn write_dead_keys(&self, html: &mut String) {
+        if self.dead_keys.is_empty() { return; }
+
+        html.push_str("<h2>Dead keys</h2>\n");
+        for (dead_char, dead_key) in self.sorted_dead_keys() {
+            let heading = match &dead_key.name {
+                Some(name) => format!("{} {}", char_text(*dead_char), name),
+                None => char_text(*dead_char),
+            };
+            writeln!(html, "<h3>{} <small>{}</small></h3>", escape(&heading), code_points(&[*dead_char])).unwrap();
+            html.push_str("<table>\n<thead><tr><th>Base</th><th>Result</th><th>Code point</th></tr></thead>\n<tbody>\n");
+
+            for (base_char, combo) in dead_key.sorted_combos() {
+                let (result, class) = match combo {
+                    DeadKeyCombo::Char(char) => (*char, "char"),
+                    DeadKeyCombo::DeadKey(char) => (*char, "char dead"),
+                };
+                writeln!(html, "<tr><td class=\"char\">{}</td><td class=\"{}\">{}</td><td>{}</td></tr>",
+                    escape(&char_text(*base_char)), class, escape(&char_text(result)), code_points(&[result])).unwrap();
+            }
+            html.push_str("</tbody>\n</table>\n");
+        }
+    }
+
+    /// Lists every character the layout types, directly or through dead keys,
+    /// with the simplest keystrokes found to type it.
+    fn char_entries(&self) -> Vec<CharEntry> {
+        let mut entries = Vec::new();
+        // The keystrokes typing each character, used as dead key combo bases.
+        let mut char_keystrokes: HashMap<WideChar, String> = HashMap::new();
+        // The keystroke sequences entering each dead key state.
+        let mut dead_key_keystrokes: HashMap<WideChar, String> = HashMap::new();
+
+        for (scan_code, physical_key) in &self.physical_keys {
+            let Some(KeyEffect::Typing(key_typing)) = self.virtual_keys.get(&physical_key.virtual_key) else { continue };
+            let caption = self.key_caption(*scan_code, physical_key, key_typing);
+
+            // Prefer keystrokes with fewer modifiers for typing dead key combo bases.
+            // Chars typed with caps lock on (SGCAPS) come last, as they need caps lock toggled first.
+            let mut by_modifiers: Vec<(&KeyModifiers, &TypingEffect, bool)> = key_typing.sorted_by_modifiers()
+                .map(|(modifiers, typing_effect)| (modifiers, typing_effect, false))
+                .collect();
+            by_modifiers.sort_by_key(|(modifiers, _, _)| modifiers.to_bits().count_ones());
+            by_modifiers.extend(key_typing.caps_lock_layer.iter().map(|(modifiers, typing_effect)| (modifiers, typing_effect, true)));
+            for (modifiers, typing_effect, caps_lock) in by_modifiers {
+                // The kana modifier of keys supporting kana lock comes from toggling it rather than holding a key.
+                let kana_lock = modifiers.kana && key_typing.kana_support;
+                let held_modifiers = KeyModifiers { kana: modifiers.kana && !kana_lock, ..*modifiers };
+                let keystroke = match held_modifiers.describe(self.supports_altgr) {
+                    modifiers if modifiers.is_empty() => caption.clone(),
+                    modifiers => format!("{}+{}", modifiers, caption)
+                };
+                let keystroke = if kana_lock { format!("Kana Lock, {}", keystroke) } else { keystroke };
+                let keystroke = if caps_lock { format!("Caps Lock, {}", keystroke) } else { keystroke };
+
+                let (chars, is_dead_key) = match typing_effect {
+                    TypingEffect::Char(char) => (vec![*char], false),
+                    TypingEffect::DeadKey(char) => (vec![*char], true),
+                    TypingEffect::Ligature(string) => (string.0.clone(), false),
+                };
+                if chars.iter().all(|char| char.to_char().is_some_and(|char| char.is_control())) { continue; }
+
+                if let [char] = chars[..] {
+                    char_keystrokes.entry(char).or_insert_with(|| keystroke.clone());
+                    if is_dead_key {
+                        dead_key_keystrokes.entry(char).or_insert_with(|| keystroke.clone());
+                    }
+                }
+                entries.push(CharEntry {
+                    text: WideString(chars.clone()).to_string_lossy(),
+                    is_dead_key,
+                    code_points: code_points(&chars),
+                    keystrokes: keystroke,
+                });
+            }
+        }
+
+        // Chained dead keys are reached through other dead keys, so resolve them breadth-first.
+        loop {
+            let mut found = false;
+            for (dead_char, dead_key) in self.sorted_dead_keys() {
+                let Some(prefix) = dead_key_keystrokes.get(dead_char).cloned() else { continue };
+                for (base_char, combo) in dead_key.sorted_combos() {
+                    let DeadKeyCombo::DeadKey(next_dead_char) = combo else { continue };
+                    let Some(base_keystroke) = char_keystrokes.get(base_char) else { continue };
+                    if dead_key_keystrokes.contains_key(next_dead_char) { continue; }
+                    dead_key_keystrokes.insert(*next_dead_char, format!("{}, {}", prefix, base_keystroke));
+                    found = true;
+                }
+            }
+            if !found { break; }
+        }
+
+        for (dead_char, dead_key) in self.sorted_dead_keys() {
+            let Some(prefix) = dead_key_keystrokes.get(dead_char) else { continue };
+            for (base_char, combo) in dead_key.sorted_combos() {
+                let DeadKeyCombo::Char(composed_char) = combo else { continue };
+                let Some(base_keystroke) = char_keystrokes.get(base_char) else { continue };
+                entries.push(CharEntry {
+                    text: char_text(*composed_char),
+                    is_dead_key: false,
+                    code_points: code_points(&[*composed_char]),
+                    keystrokes: format!("{}, {}", prefix, base_keystroke),
+                });
+            }
+        }
+
+        entries
+    }
+
+    /// Names a key the way users would find it: by the character engraved on it, or by its name.
+    fn key_caption(&self, scan_code: ScanCode, physical_key: &PhysicalKeyDesc, key_typing: &KeyTyping) -> String {
+        let base_char = match key_typing.by_modifiers.get(&KeyModifiers::from_bits(0)) {
+            Some(TypingEffect::Char(char) | TypingEffect::DeadKey(char)) => char.to_char(),
+            _ => None
+        };
+        match base_char {
+            Some(char) if !char.is_control() && !char.is_whitespace() => char.to_uppercase().collect(),
+            _ => physical_key.name.clone()
+                .or_else(|| scan_code.name().map(str::to_string))
+                .unwrap_or_else(|| format!("{:?}", physical_key.virtual_key))
+        }
+    }
+}
+
+fn char_text(char: WideChar) -> String {
+    WideString(vec![char]).to_string_lossy()
+}
+
+fn code_points(chars: &[WideChar]) -> String {
+    let code_points: Vec<String> = chars.iter().map(|char| match char {
+        WideChar::Scalar(char) => format!("U+{:04X}", *char as u32),
+        WideChar::Surrogate(unit) => format!("U+{:04X}", unit),
+    }).collect();
+    code_points.join(" ")
+}
+
+fn style(options: &SvgOptions) -> String {
+    let theme = &options.theme;
+    format!("\
+body {{ font-family: {font}; margin: 2em; color: {text}; background: {background}; }}
+h1, h2, h3 {{ font-weight: 600; }}
+svg {{ max-width: 100%; height: auto; }}
+table {{ border-collapse: collapse; margin-bottom: 1.5em; }}
+th, td {{ border: 1px solid {stroke}; padding: 0.25em 0.75em; text-align: left; }}
+td.char {{ font-size: 1.4em; }}
+.dead {{ color: {dead}; }}
+dt {{ font-weight: 600; float: left; clear: left; width: 12em; }}
+dd {{ margin-left: 12em; }}
+#search {{ font-size: 1em; padding: 0.25em; margin-bottom: 1em; width: 20em; }}",
+        font = css_font_family(&options.font_family),
+        text = theme.text,
+        background = theme.background,
+        stroke = theme.key_stroke,
+        dead = theme.dead_key_text)
+}
+
+/// Formats a comma-separated list of font families as CSS, quoting the family names
+/// so that no character in them can end the declaration or the style element.
+fn css_font_family(font_family: &str) -> String {
+    const GENERIC_FAMILIES: &[&str] = &["serif", "sans-serif", "monospace", "cursive", "fantasy", "system-ui", "emoji", "math"];
+    let families: Vec<String> = font_family.split(',')
+        .map(str::trim)
+        .filter(|family| !family.is_empty())
+        .map(|family| {
+            if GENERIC_FAMILIES.contains(&family) {
+                return family.to_string();
+            }
+            let mut quoted = String::from("\"");
+            for char in family.trim_matches(|char| char == '"' || char == '\'').chars() {
+                match char {
+                    '"' | '\\' | '<' | '>' | '&' | '\n' | '\r' => write!(quoted, "\\{:X} ", char as u32).unwrap(),
+                    _ => quoted.push(char)
+                }
+            }
+            quoted.push('"');
+            quoted
+        })
+        .collect();
+    families.join(", ")
+}
+
+fn escape(str: &str) -> String {
+    str.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
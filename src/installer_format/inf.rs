@@ -0,0 +1,68 @@
+use std::fmt::Write;
+use crate::model::LayoutMetadata;
+use super::LayoutRegistration;
+
+const SYSTEM32_DIRID: u32 = 11;
+const FLG_ADDREG_TYPE_SZ: u32 = 0x0000_0000;
+const FLG_ADDREG_TYPE_EXPAND_SZ: u32 = 0x0002_0000;
+
+pub fn write(registration: &LayoutRegistration, metadata: &LayoutMetadata) -> String {
+    let key = format!("\"{}\"", registration.key_path());
+    let file = &registration.layout_file;
+
+    let mut inf = String::new();
+    inf.push_str("[Version]\r\n");
+    inf.push_str("Signature = \"$Windows NT$\"\r\n");
+    inf.push_str("Provider = %Provider%\r\n");
+    inf.push_str("\r\n");
+
+    inf.push_str("[DestinationDirs]\r\n");
+    write!(inf, "DefaultDestDir = {}\r\n", SYSTEM32_DIRID).unwrap();
+    inf.push_str("\r\n");
+
+    inf.push_str("[SourceDisksNames]\r\n");
+    inf.push_str("1 = %DiskName%\r\n");
+    inf.push_str("\r\n");
+
+    inf.push_str("[SourceDisksFiles]\r\n");
+    write!(inf, "{} = 1\r\n", file).unwrap();
+    inf.push_str("\r\n");
+
+    inf.push_str("[DefaultInstall]\r\n");
+    inf.push_str("CopyFiles = LayoutFiles\r\n");
+    inf.push_str("AddReg = LayoutRegistry\r\n");
+    inf.push_str("\r\n");
+
+    inf.push_str("[DefaultUninstall]\r\n");
+    inf.push_str("DelFiles = LayoutFiles\r\n");
+    inf.push_str("DelReg = LayoutRegistry\r\n");
+    inf.push_str("\r\n");
+
+    inf.push_str("[LayoutFiles]\r\n");
+    write!(inf, "{}\r\n", file).unwrap();
+    inf.push_str("\r\n");
+
+    inf.push_str("[LayoutRegistry]\r\n");
+    write!(inf, "HKLM, {}, \"Layout File\", 0x{:08x}, \"{}\"\r\n", key, FLG_ADDREG_TYPE_SZ, escape(file)).unwrap();
+    write!(inf, "HKLM, {}, \"Layout Text\", 0x{:08x}, %LayoutText%\r\n", key, FLG_ADDREG_TYPE_SZ).unwrap();
+    match &registration.layout_display_name {
+        Some(layout_display_name) => write!(inf, "HKLM, {}, \"Layout Display Name\", 0x{:08x}, \"{}\"\r\n", key,
+            FLG_ADDREG_TYPE_EXPAND_SZ, escape(layout_display_name)),
+        None => write!(inf, "HKLM, {}, \"Layout Display Name\", 0x{:08x}, %LayoutText%\r\n", key, FLG_ADDREG_TYPE_SZ)
+    }.unwrap();
+    if let Some(layout_id) = registration.layout_id {
+        write!(inf, "HKLM, {}, \"Layout Id\", 0x{:08x}, \"{:04x}\"\r\n", key, FLG_ADDREG_TYPE_SZ, layout_id).unwrap();
+    }
+    inf.push_str("\r\n");
+
+    inf.push_str("[Strings]\r\n");
+    write!(inf, "Provider = \"{}\"\r\n", escape(metadata.company_name.as_deref().unwrap_or("kbdc"))).unwrap();
+    write!(inf, "DiskName = \"{}\"\r\n", escape(&format!("{} Installation Disk", registration.layout_text))).unwrap();
+    write!(inf, "LayoutText = \"{}\"\r\n", escape(&registration.layout_text)).unwrap();
+    inf
+}
+
+/// Escapes a value for use in a quoted INF string, where % starts a string substitution.
+fn escape(str: &str) -> String {
+    str.replace('%', "%%").replace('"', "\"\"")
+}
@@ -0,0 +1,129 @@
+// Generates the artefacts needed to register a layout DLL with Windows,
+// which looks up installed layouts under the Keyboard Layouts registry key by KLID.
+
+mod reg;
+mod inf;
+
+use crate::model::{KeyboardDesc, KnownLayout, LayoutMetadata};
+
+pub use reg::read_installed_layouts;
+
+pub const KEYBOARD_LAYOUTS_KEY: &str = r"SYSTEM\CurrentControlSet\Control\Keyboard Layouts";
+
+/// The range custom Layout Ids are allocated in. Layout ids of layouts shipped with Windows are below 0x0100.
+const CUSTOM_LAYOUT_IDS: std::ops::RangeInclusive<u16> = 0x0C00..=0x0FFF;
+
+/// A layout already registered on the target system, which a new layout must not clobber.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InstalledLayout {
+    pub klid: u32,
+    pub layout_file: Option<String>,
+    pub layout_id: Option<u16>,
+}
+
+#[derive(Default)]
+pub struct InstallerOptions {
+    /// The layouts registered under the Keyboard Layouts key of the target system,
+    /// whose KLIDs and Layout Ids are not reused. Layouts shipped with Windows are always avoided.
+    pub installed_layouts: Vec<InstalledLayout>,
+}
+
+impl InstallerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a layout other than the given layout file is registered under a KLID.
+    fn other_layout_uses_klid(&self, klid: u32, layout_file: &str) -> bool {
+        let is_other_file = |file: &str| !file.eq_ignore_ascii_case(layout_file);
+        KnownLayout::find_by_klid(klid).is_some_and(|known_layout| is_other_file(known_layout.layout_file))
+            || self.installed_layouts.iter().any(|installed| installed.klid == klid
+                && installed.layout_file.as_deref().is_none_or(is_other_file))
+    }
+}
+
+/// The registry values of an installed layout.
+struct LayoutRegistration {
+    klid: u32,
+    layout_file: String,
+    layout_text: String,
+    /// The reference to the display name string resource of the DLL, if it has one.
+    layout_display_name: Option<String>,
+    layout_id: Option<u16>,
+}
+
+impl LayoutRegistration {
+    fn from_model(keyboard_desc: &KeyboardDesc, options: &InstallerOptions) -> Result<Self, String> {
+        let metadata = &keyboard_desc.metadata;
+        let layout_file = metadata.layout_file.clone()
+            .ok_or("The layout file name is required to install a layout.")?;
+        let layout_text = metadata.layout_text.clone()
+            .or_else(|| metadata.display_name.clone())
+            .unwrap_or_else(|| layout_file.clone());
+
+        let klid = resolve_klid(metadata, &layout_file, options)?;
+
+        // Layouts other than the primary one for a locale need a Layout Id,
+        // which Windows uses to tell apart layouts sharing a locale.
+        let layout_id = if klid & 0xFFFF_0000 == 0 || metadata.layout_id.is_some() { metadata.layout_id }
+            else { Some(allocate_layout_id(klid, &options.installed_layouts).ok_or("No Layout Id is available for the layout.")?) };
+
+        Ok(Self {
+            klid,
+            layout_display_name: metadata.display_name_resource.as_ref()
+                .map(|_| format!(r"@%SystemRoot%\system32\{},-{}", layout_file, LayoutMetadata::DISPLAY_NAME_RESOURCE_ID)),
+            layout_file,
+            layout_text,
+            layout_id,
+        })
+    }
+
+    fn key_path(&self) -> String {
+        format!(r"{}\{:08x}", KEYBOARD_LAYOUTS_KEY, self.klid)
+    }
+}
+
+/// Uses the layout's own KLID unless it belongs to a different layout, shipped with Windows
+/// or installed, in which case a custom KLID is chosen for its locale.
+fn resolve_klid(metadata: &LayoutMetadata, layout_file: &str, options: &InstallerOptions) -> Result<u32, String> {
+    if let Some(klid) = metadata.klid && !options.other_layout_uses_klid(klid, layout_file) {
+        return Ok(klid)
+    }
+
+    let locale_id = metadata.locale_id
+        .or(metadata.klid.map(|klid| klid as u16))
+        .ok_or("The locale is required to choose a KLID for the layout.")?;
+    choose_custom_klid(locale_id, |klid| options.other_layout_uses_klid(klid, layout_file))
+        .ok_or_else(|| format!("No KLID is available for locale {:04x}.", locale_id))
+}
+
+/// Chooses the first KLID of the form aNNNxxxx, where xxxx is the locale identifier,
+/// which is not already in use, following the convention of the Microsoft Keyboard Layout Creator.
+pub fn choose_custom_klid(locale_id: u16, is_used: impl Fn(u32) -> bool) -> Option<u32> {
+    (0..0x1000u32)
+        .map(|variant| 0xA000_0000 | (variant << 16) | locale_id as u32)
+        .find(|&klid| !is_used(klid))
+}
+
+/// Allocates a Layout Id for a custom KLID: the one it is already installed with, if any,
+/// or else the first id of the custom range no installed layout uses.
+fn allocate_layout_id(klid: u32, installed_layouts: &[InstalledLayout]) -> Option<u16> {
+    let reinstalled = installed_layouts.iter().find(|installed| installed.klid == klid);
+    if let Some(layout_id) = reinstalled.and_then(|installed| installed.layout_id) {
+        return Some(layout_id)
+    }
+    CUSTOM_LAYOUT_IDS.into_iter()
+        .find(|layout_id| !installed_layouts.iter().any(|installed| installed.layout_id == Some(*layout_id)))
+}
+
+impl KeyboardDesc {
+    /// Generates a .reg file registering the layout DLL, which must be copied to system32.
+    pub fn to_reg(&self, options: &InstallerOptions) -> Result<String, String> {
+        Ok(reg::write(&LayoutRegistration::from_model(self, options)?))
+    }
+
+    /// Generates an .inf file copying the layout DLL to system32 and registering it.
+    pub fn to_inf(&self, options: &InstallerOptions) -> Result<String, String> {
+        Ok(inf::write(&LayoutRegistration::from_model(self, options)?, &self.metadata))
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt::Write;
+use super::{InstalledLayout, LayoutRegistration, KEYBOARD_LAYOUTS_KEY};
+
+pub fn write(registration: &LayoutRegistration) -> String {
+    let mut reg = String::new();
+    reg.push_str("Windows Registry Editor Version 5.00\r\n\r\n");
+    write!(reg, "[HKEY_LOCAL_MACHINE\\{}]\r\n", registration.key_path()).unwrap();
+    write!(reg, "\"Layout File\"=\"{}\"\r\n", escape(&registration.layout_file)).unwrap();
+    write!(reg, "\"Layout Text\"=\"{}\"\r\n", escape(&registration.layout_text)).unwrap();
+    match &registration.layout_display_name {
+        Some(layout_display_name) => write!(reg, "\"Layout Display Name\"={}\r\n", expand_sz(layout_display_name)),
+        None => write!(reg, "\"Layout Display Name\"=\"{}\"\r\n", escape(&registration.layout_text))
+    }.unwrap();
+    if let Some(layout_id) = registration.layout_id {
+        write!(reg, "\"Layout Id\"=\"{:04x}\"\r\n", layout_id).unwrap();
+    }
+    reg
+}
+
+/// Reads the layouts registered in a regedit export of the Keyboard Layouts key,
+/// ignoring other keys and values that are not plain strings.
+pub fn read_installed_layouts(reg: &str) -> Vec<InstalledLayout> {
+    let mut result: Vec<InstalledLayout> = Vec::new();
+    let mut in_layout_key = false;
+    for line in reg.lines() {
+        let line = line.trim();
+        if let Some(key) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            let klid = key.rsplit_once('\\')
+                .filter(|(parent, _)| parent.to_ascii_lowercase().ends_with(&KEYBOARD_LAYOUTS_KEY.to_ascii_lowercase()))
+                .and_then(|(_, name)| u32::from_str_radix(name, 16).ok());
+            in_layout_key = klid.is_some();
+            if let Some(klid) = klid {
+                result.push(InstalledLayout { klid, layout_file: None, layout_id: None });
+            }
+            continue;
+        }
+
+        if !in_layout_key { continue }
+        let Some(installed) = result.last_mut() else { continue };
+        let Some((name, value)) = line.split_once('=') else { continue };
+        let Some(value) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) else { continue };
+        let value = unescape(value);
+        match name {
+            "\"Layout File\"" => installed.layout_file = Some(value),
+            "\"Layout Id\"" => installed.layout_id = u16::from_str_radix(&value, 16).ok(),
+            _ => {}
+        }
+    }
+    result
+}
+
+fn escape(str: &str) -> String {
+    str.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn unescape(str: &str) -> String {
+    let mut result = String::new();
+    let mut chars = str.chars();
+    while let Some(char) = chars.next() {
+        result.push(if char == '\\' { chars.next().unwrap_or(char) } else { char });
+    }
+    result
+}
+
+/// Formats a REG_EXPAND_SZ value, which .reg files store as null-terminated UTF-16LE bytes.
+fn expand_sz(str: &str) -> String {
+    let bytes: Vec<String> = str.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|unit| unit.to_le_bytes())
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    // Wrap lines like regedit does, to keep them readable.
+    let mut value = "hex(2):".to_string();
+    for (index, chunk) in bytes.chunks(24).enumerate() {
+        if index > 0 { value.push_str(",\\\r\n  "); }
+        value.push_str(&chunk.join(","));
+    }
+    value
+}
@@ -1,20 +1,42 @@
 
 use std::collections::BTreeMap;
+use indexmap::IndexMap;
 use crate::model as model;
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(non_snake_case)]
 pub struct Document {
+    #[serde(default)]
     physicalKeyNames: BTreeMap<ScanCodeKey, String>,
+    #[serde(default)]
     physicalToVirtualKeys: BTreeMap<ScanCodeKey, VirtualKeyValue>,
-    modifierKeys: BTreeMap<VirtualKeyKey, ModifierKey>,
-    typingKeys: BTreeMap<VirtualKeyKey, KeyTypingDesc>,
-    deadKeys: BTreeMap<char, DeadKeyDesc>,
+    /// The scan code table flags of the keys having any, such as KBDEXT.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    physicalKeyFlags: BTreeMap<ScanCodeKey, VirtualKeyFlagsValue>,
+    #[serde(default)]
+    modifierKeys: IndexMap<VirtualKeyKey, ModifierKey>,
+    #[serde(default)]
+    typingKeys: IndexMap<VirtualKeyKey, KeyTypingDesc>,
+    #[serde(default)]
+    deadKeys: IndexMap<WideCharValue, DeadKeyDesc>,
     version: u16,
-    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "is_false")]
     supportsAltGr: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    supportsShiftLock: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    supportsDirectionality: bool,
     r#type: u32,
     subtype: u32,
+    #[serde(default)]
+    metadata: LayoutMetadataDesc,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geometry: Option<GeometryValue>,
+    /// The column of each combination of modifiers, null for invalid ones.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    shiftStates: IndexMap<KeyModifiersKey, Option<u8>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    charTables: Option<CharTablesDesc>,
 }
 
 impl Document {
@@ -34,8 +56,13 @@ impl Document {
                 VirtualKeyValue(physical_key.virtual_key));
         }
 
-        let mut modifier_keys = BTreeMap::new();
-        let mut typing_keys = BTreeMap::new();
+        let physical_key_flags = keyboard_desc.physical_keys.iter()
+            .filter(|(_, physical_key)| physical_key.virtual_key_flags.to_bits() != 0)
+            .map(|(scan_code, physical_key)| (ScanCodeKey(*scan_code), VirtualKeyFlagsValue(physical_key.virtual_key_flags)))
+            .collect();
+
+        let mut modifier_keys = IndexMap::new();
+        let mut typing_keys = IndexMap::new();
         for (virtual_key, key_effect) in &keyboard_desc.virtual_keys {
             match key_effect {
                 model::KeyEffect::Modifier(key_modifiers) => {
@@ -54,25 +81,140 @@ impl Document {
             }
         }
 
-        let mut dead_keys = BTreeMap::new();
+        let mut dead_keys = IndexMap::new();
         for (char, dead_key) in &keyboard_desc.dead_keys {
             dead_keys.insert(
-                char::from_u32(*char as u32).unwrap(),
-                DeadKeyDesc::from_model(&dead_key)
+                WideCharValue(*char),
+                DeadKeyDesc::from_model(dead_key)
             );
         }
 
         Document {
             physicalKeyNames: physical_key_names,
             physicalToVirtualKeys: physical_to_virtual_keys,
+            physicalKeyFlags: physical_key_flags,
             modifierKeys: modifier_keys,
             typingKeys: typing_keys,
             deadKeys: dead_keys,
             version: keyboard_desc.version,
             supportsAltGr: keyboard_desc.supports_altgr,
+            supportsShiftLock: keyboard_desc.supports_shift_lock,
+            supportsDirectionality: keyboard_desc.supports_directionality,
             r#type: keyboard_desc.type_value,
-            subtype: keyboard_desc.subtype_value
+            subtype: keyboard_desc.subtype_value,
+            metadata: LayoutMetadataDesc::from_model(&keyboard_desc.metadata),
+            geometry: keyboard_desc.geometry.map(GeometryValue),
+            shiftStates: keyboard_desc.shift_states.columns().iter().enumerate()
+                .map(|(mod_bits, column)| (KeyModifiersKey(model::KeyModifiers::from_bits(mod_bits as u8)), *column))
+                .collect(),
+            charTables: keyboard_desc.char_tables.as_ref().map(CharTablesDesc::from_model),
+        }
+    }
+
+    /// Converts the document to a layout, failing on metadata values which are not valid hex numbers.
+    pub fn into_model(self) -> Result<model::KeyboardDesc, String> {
+        let mut keyboard_desc = model::KeyboardDesc::new();
+
+        for (scan_code, virtual_key) in self.physicalToVirtualKeys {
+            keyboard_desc.physical_keys.insert(scan_code.0, model::PhysicalKeyDesc {
+                virtual_key: virtual_key.0,
+                virtual_key_flags: model::VirtualKeyFlags::from_bits(0),
+                name: None
+            });
+        }
+
+        for (scan_code, flags) in self.physicalKeyFlags {
+            if let Some(physical_key) = keyboard_desc.physical_keys.get_mut(&scan_code.0) {
+                physical_key.virtual_key_flags = flags.0;
+            }
+        }
+
+        for (scan_code, name) in self.physicalKeyNames {
+            if let Some(physical_key) = keyboard_desc.physical_keys.get_mut(&scan_code.0) {
+                physical_key.name = Some(name);
+            }
+        }
+
+        for (virtual_key, modifier_key) in self.modifierKeys {
+            keyboard_desc.virtual_keys.insert(
+                virtual_key.0,
+                model::KeyEffect::Modifier(modifier_key.to_model()));
+        }
+
+        for (virtual_key, key_typing) in self.typingKeys {
+            keyboard_desc.virtual_keys.insert(
+                virtual_key.0,
+                model::KeyEffect::Typing(key_typing.into_model()));
         }
+
+        for (char, dead_key) in self.deadKeys {
+            keyboard_desc.dead_keys.insert(char.0, dead_key.into_model());
+        }
+
+        keyboard_desc.version = self.version;
+        keyboard_desc.supports_altgr = self.supportsAltGr;
+        keyboard_desc.supports_shift_lock = self.supportsShiftLock;
+        keyboard_desc.supports_directionality = self.supportsDirectionality;
+        keyboard_desc.type_value = self.r#type;
+        keyboard_desc.subtype_value = self.subtype;
+        keyboard_desc.metadata = self.metadata.into_model()?;
+        keyboard_desc.geometry = self.geometry.map(|geometry| geometry.0);
+        for (modifiers, column) in self.shiftStates {
+            keyboard_desc.shift_states.set_column(&modifiers.0, column);
+        }
+        keyboard_desc.char_tables = self.charTables.map(CharTablesDesc::into_model);
+        Ok(keyboard_desc)
+    }
+}
+
+/// The VK_TO_WCHARS tables, which typing keys reference by index.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CharTablesDesc {
+    groups: Vec<CharTableGroupDesc>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(non_snake_case)]
+struct CharTableGroupDesc {
+    modifications: u8,
+    entrySize: u8,
+}
+
+impl CharTablesDesc {
+    fn from_model(value: &model::CharTableLayout) -> Self {
+        Self {
+            groups: value.groups.iter().map(|group| CharTableGroupDesc {
+                modifications: group.modifications,
+                entrySize: group.entry_size,
+            }).collect(),
+        }
+    }
+
+    fn into_model(self) -> model::CharTableLayout {
+        model::CharTableLayout {
+            groups: self.groups.into_iter().map(|group| model::CharTableGroup {
+                modifications: group.modifications,
+                entry_size: group.entrySize,
+            }).collect(),
+        }
+    }
+}
+
+struct GeometryValue(model::GeometryKind);
+
+impl serde::Serialize for GeometryValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        serializer.serialize_str(self.0.name())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for GeometryValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let str = String::deserialize(deserializer)?;
+        model::GeometryKind::from_name(&str).map(GeometryValue)
+            .ok_or_else(|| serde::de::Error::custom(format!("Unknown geometry: {}", str)))
     }
 }
 
@@ -91,18 +233,72 @@ impl serde::Serialize for ScanCodeKey {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+impl<'de> serde::Deserialize<'de> for ScanCodeKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let str = String::deserialize(deserializer)?;
+        let parse_code = |digits: &str| u8::from_str_radix(digits, 16).ok().filter(|code| code & 0x80 == 0);
+        let scan_code = match str.len() {
+            2 => parse_code(&str).map(model::ScanCode::Unescaped),
+            4 if str.starts_with("E0") => parse_code(&str[2..]).map(model::ScanCode::Extended0),
+            4 if str.starts_with("E1") => parse_code(&str[2..]).map(model::ScanCode::Extended1),
+            _ => None
+        };
+        scan_code.or_else(|| model::ScanCode::from_name(&str)).map(ScanCodeKey)
+            .ok_or_else(|| serde::de::Error::custom(format!("Invalid scan code: {}", str)))
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct VirtualKeyKey(crate::model::VirtualKey);
 
 impl serde::Serialize for VirtualKeyKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: serde::Serializer {
-        if let Some(enum_name) = self.0.to_vk_enum(true) {
-            serializer.serialize_str(&enum_name)
+        match self.0.to_vk_enum(true) {
+            Some(enum_name) => serializer.serialize_str(&enum_name),
+            // Codes left unassigned by Windows can still be used by custom layouts.
+            None => serializer.serialize_str(&format!("0x{:02X}", self.0.code))
         }
-        else {
-            panic!("VirtualKey {:?} does not have a name", self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for VirtualKeyKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let str = String::deserialize(deserializer)?;
+        crate::model::VirtualKey::from_vk_enum(&str, true)
+            .or_else(|| str.strip_prefix("0x")
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .map(|code| crate::model::VirtualKey { code }))
+            .map(VirtualKeyKey)
+            .ok_or_else(|| serde::de::Error::custom(format!("Unknown virtual key: {}", str)))
+    }
+}
+
+/// Scan code table flags, serialized as a list of their kbd.h names.
+struct VirtualKeyFlagsValue(model::VirtualKeyFlags);
+
+impl serde::Serialize for VirtualKeyFlagsValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        let bits = self.0.to_bits();
+        serializer.collect_seq(model::VirtualKeyFlags::NAMES.iter().enumerate()
+            .filter(|(bit, _)| (bits & (1 << bit)) != 0)
+            .map(|(_, name)| name))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for VirtualKeyFlagsValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let mut bits = 0u8;
+        for name in Vec::<String>::deserialize(deserializer)? {
+            let bit = model::VirtualKeyFlags::NAMES.iter().position(|flag_name| *flag_name == name)
+                .ok_or_else(|| serde::de::Error::custom(format!("Unknown virtual key flag: {}", name)))?;
+            bits |= 1 << bit;
         }
+        Ok(VirtualKeyFlagsValue(model::VirtualKeyFlags::from_bits(bits)))
     }
 }
 
@@ -123,6 +319,96 @@ impl serde::Serialize for VirtualKeyValue {
     }
 }
 
+impl<'de> serde::Deserialize<'de> for VirtualKeyValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Value { Name(String), Code(u8) }
+
+        match Option::<Value>::deserialize(deserializer)? {
+            None => Ok(VirtualKeyValue(crate::model::VirtualKey::NONE)),
+            Some(Value::Code(code)) => Ok(VirtualKeyValue(crate::model::VirtualKey { code })),
+            Some(Value::Name(name)) => crate::model::VirtualKey::from_vk_enum(&name, true)
+                .map(VirtualKeyValue)
+                .ok_or_else(|| serde::de::Error::custom(format!("Unknown virtual key: {}", name)))
+        }
+    }
+}
+
+/// A character, serialized as a single-character string,
+/// or as a \uXXXX escape sequence for lone surrogates.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct WideCharValue(model::WideChar);
+
+impl WideCharValue {
+    fn to_json_string(&self) -> String {
+        match self.0 {
+            model::WideChar::Scalar(char) => char.to_string(),
+            model::WideChar::Surrogate(unit) => format!("\\u{:04X}", unit)
+        }
+    }
+
+    fn from_json_string(str: &str) -> Option<Self> {
+        let mut chars = str.chars();
+        match (chars.next(), chars.next()) {
+            (Some(char), None) => Some(WideCharValue(model::WideChar::Scalar(char))),
+            _ => {
+                let unit = u16::from_str_radix(str.strip_prefix("\\u")?, 16).ok()?;
+                match model::WideChar::from_u16(unit) {
+                    surrogate @ model::WideChar::Surrogate(_) if str.len() == 6 => Some(WideCharValue(surrogate)),
+                    _ => None
+                }
+            }
+        }
+    }
+}
+
+impl serde::Serialize for WideCharValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        serializer.serialize_str(&self.to_json_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for WideCharValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let str = String::deserialize(deserializer)?;
+        Self::from_json_string(&str)
+            .ok_or_else(|| serde::de::Error::custom(format!("Invalid character: {}", str)))
+    }
+}
+
+/// A string, serialized as such if it is valid Unicode,
+/// or as an array of characters if it contains lone surrogates.
+struct WideStringValue(model::WideString);
+
+impl serde::Serialize for WideStringValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        match self.0.try_to_string() {
+            Some(str) => serializer.serialize_str(&str),
+            None => serializer.collect_seq(self.0.0.iter().map(|char| WideCharValue(*char)))
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for WideStringValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Value { String(String), Chars(Vec<WideCharValue>) }
+
+        Ok(WideStringValue(match Value::deserialize(deserializer)? {
+            Value::String(str) => model::WideString::from(str.as_str()),
+            Value::Chars(chars) => model::WideString(chars.into_iter().map(|char| char.0).collect())
+        }))
+    }
+}
+
+#[derive(serde::Deserialize)]
 #[allow(non_camel_case_types)]
 enum ModifierKey {
     shift,
@@ -144,6 +430,19 @@ impl ModifierKey {
         else if value.loya { Some(ModifierKey::loya) }
         else { None }
     }
+
+    fn to_model(&self) -> model::KeyModifiers {
+        let mut value = model::KeyModifiers::from_bits(0);
+        match self {
+            ModifierKey::shift => value.shift = true,
+            ModifierKey::control => value.control = true,
+            ModifierKey::alt => value.alt = true,
+            ModifierKey::kana => value.kana = true,
+            ModifierKey::roya => value.roya = true,
+            ModifierKey::loya => value.loya = true
+        }
+        value
+    }
 }
 
 impl serde::Serialize for ModifierKey {
@@ -165,26 +464,50 @@ struct KeyModifiersKey(model::KeyModifiers);
 impl KeyModifiersKey {
     fn to_mask_string(&self) -> String {
         let mut mask = String::new();
-        if self.0.shift { mask.push_str("s"); }
-        if self.0.control { mask.push_str("c"); }
-        if self.0.alt { mask.push_str("a"); }
-        if self.0.kana { mask.push_str("k"); }
-        if self.0.roya { mask.push_str("r"); }
-        if self.0.loya { mask.push_str("l"); }
-        if self.0.unknown0x40 { mask.push_str("u"); }
-        if self.0.grpseltap { mask.push_str("g"); }
+        if self.0.shift { mask.push('s'); }
+        if self.0.control { mask.push('c'); }
+        if self.0.alt { mask.push('a'); }
+        if self.0.kana { mask.push('k'); }
+        if self.0.roya { mask.push('r'); }
+        if self.0.loya { mask.push('l'); }
+        if self.0.unknown0x40 { mask.push('u'); }
+        if self.0.grpseltap { mask.push('g'); }
         mask
     }
+
+    fn from_mask_string(mask: &str) -> Option<Self> {
+        let mut value = model::KeyModifiers::from_bits(0);
+        for char in mask.chars() {
+            match char {
+                's' => value.shift = true,
+                'c' => value.control = true,
+                'a' => value.alt = true,
+                'k' => value.kana = true,
+                'r' => value.roya = true,
+                'l' => value.loya = true,
+                'u' => value.unknown0x40 = true,
+                'g' => value.grpseltap = true,
+                _ => return None
+            }
+        }
+        Some(Self(value))
+    }
 }
 
 impl PartialEq for KeyModifiersKey {
     fn eq(&self, other: &Self) -> bool {
-        self.cmp(&other) == std::cmp::Ordering::Equal
+        self.cmp(other) == std::cmp::Ordering::Equal
     }
 }
 
 impl Eq for KeyModifiersKey {}
 
+impl std::hash::Hash for KeyModifiersKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state)
+    }
+}
+
 impl PartialOrd for KeyModifiersKey {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -204,93 +527,265 @@ impl serde::Serialize for KeyModifiersKey {
     }
 }
 
+impl<'de> serde::Deserialize<'de> for KeyModifiersKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let str = String::deserialize(deserializer)?;
+        Self::from_mask_string(&str)
+            .ok_or_else(|| serde::de::Error::custom(format!("Invalid modifiers mask: {}", str)))
+    }
+}
+
 fn is_false(b: &bool) -> bool { !b }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(non_snake_case)]
 struct KeyTypingDesc {
-    pub byModifiers: BTreeMap<KeyModifiersKey, TypingEffect>,
-    #[serde(skip_serializing_if = "is_false")]
+    pub byModifiers: IndexMap<KeyModifiersKey, TypingEffect>,
+    #[serde(default, skip_serializing_if = "is_false")]
     pub capsLockAsShift: bool,
-    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub capsLockAsUppercase: bool,
-    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub capsLockLayer: IndexMap<KeyModifiersKey, TypingEffect>,
+    #[serde(default, skip_serializing_if = "is_false")]
     pub capsLockAltGrAsShift: bool,
-    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub kanaSupport: bool,
-    #[serde(skip_serializing_if = "is_false")]
-    pub grpseltapSupport: bool
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub grpseltapSupport: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<usize>
 }
 
 impl KeyTypingDesc {
     fn from_model(value: &model::KeyTyping) -> Self {
-        let mut by_modifiers = BTreeMap::new();
-        for (key_modifiers, effect) in &value.by_modifiers {
-            match effect {
-                model::TypingEffect::Char(char) => {
-                    by_modifiers.insert(
-                        KeyModifiersKey(*key_modifiers),
-                        TypingEffect::Char(char::from_u32(*char as u32).unwrap())
-                    );
-                },
-                model::TypingEffect::DeadKey(char) => {
-                    by_modifiers.insert(
-                        KeyModifiersKey(*key_modifiers),
-                        TypingEffect::DeadKey {
-                            deadKey: char::from_u32(*char as u32).unwrap()
-                        }
-                    );
-                },
-                model::TypingEffect::Ligature(chars) => {
-                    by_modifiers.insert(
-                        KeyModifiersKey(*key_modifiers),
-                        TypingEffect::Ligature {
-                            ligature: String::from_utf16_lossy(chars)
-                        }
-                    );
-                }
-            }
-        }
-
         Self {
-            byModifiers: by_modifiers,
+            byModifiers: effects_from_model(&value.by_modifiers),
             capsLockAsShift: value.caps_lock_as_shift,
             capsLockAsUppercase: value.caps_lock_as_uppercase,
+            capsLockLayer: effects_from_model(&value.caps_lock_layer),
             capsLockAltGrAsShift: value.caps_lock_altgr_as_shift,
             kanaSupport: value.kana_support,
-            grpseltapSupport: value.grpseltap_support
+            grpseltapSupport: value.grpseltap_support,
+            group: value.group
+        }
+    }
+
+    fn into_model(self) -> model::KeyTyping {
+        model::KeyTyping {
+            by_modifiers: effects_to_model(self.byModifiers),
+            caps_lock_as_shift: self.capsLockAsShift,
+            caps_lock_as_uppercase: self.capsLockAsUppercase,
+            caps_lock_layer: effects_to_model(self.capsLockLayer),
+            caps_lock_altgr_as_shift: self.capsLockAltGrAsShift,
+            kana_support: self.kanaSupport,
+            grpseltap_support: self.grpseltapSupport,
+            group: self.group
         }
     }
 }
 
-#[derive(serde::Serialize)]
+fn effects_from_model(value: &IndexMap<model::KeyModifiers, model::TypingEffect>) -> IndexMap<KeyModifiersKey, TypingEffect> {
+    let mut by_modifiers = IndexMap::new();
+    for (key_modifiers, effect) in value {
+        match effect {
+            model::TypingEffect::Char(char) => {
+                by_modifiers.insert(
+                    KeyModifiersKey(*key_modifiers),
+                    TypingEffect::Char(WideCharValue(*char))
+                );
+            },
+            model::TypingEffect::DeadKey(char) => {
+                by_modifiers.insert(
+                    KeyModifiersKey(*key_modifiers),
+                    TypingEffect::DeadKey {
+                        deadKey: WideCharValue(*char)
+                    }
+                );
+            },
+            model::TypingEffect::Ligature(chars) => {
+                by_modifiers.insert(
+                    KeyModifiersKey(*key_modifiers),
+                    TypingEffect::Ligature {
+                        ligature: WideStringValue(chars.clone())
+                    }
+                );
+            }
+        }
+    }
+    by_modifiers
+}
+
+fn effects_to_model(value: IndexMap<KeyModifiersKey, TypingEffect>) -> IndexMap<model::KeyModifiers, model::TypingEffect> {
+    let mut by_modifiers = IndexMap::new();
+    for (key_modifiers, effect) in value {
+        by_modifiers.insert(key_modifiers.0, match effect {
+            TypingEffect::Char(char) => model::TypingEffect::Char(char.0),
+            TypingEffect::DeadKey { deadKey } => model::TypingEffect::DeadKey(deadKey.0),
+            TypingEffect::Ligature { ligature } => model::TypingEffect::Ligature(ligature.0)
+        });
+    }
+    by_modifiers
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
 enum TypingEffect {
-    Char(char),
-    DeadKey { deadKey: char },
-    Ligature { ligature: String }
+    Char(WideCharValue),
+    DeadKey { deadKey: WideCharValue },
+    Ligature { ligature: WideStringValue }
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(non_snake_case)]
 struct DeadKeyDesc {
     name: Option<String>,
-    combos: BTreeMap<char, char>
+    combos: IndexMap<WideCharValue, DeadKeyCombo>,
+    /// The uFlags bits of combos other than DKF_DEAD, by base char.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    comboFlags: IndexMap<WideCharValue, u16>
 }
 
 impl DeadKeyDesc {
     fn from_model(value: &model::DeadKeyDesc) -> Self {
-        let mut combos = BTreeMap::new();
+        let mut combos = IndexMap::new();
         for (char, combo) in &value.combos {
             combos.insert(
-                char::from_u32(*char as u32).unwrap(),
-                char::from_u32(combo.composed_char as u32).unwrap()
+                WideCharValue(*char),
+                match combo {
+                    model::DeadKeyCombo::Char(char) => DeadKeyCombo::Char(WideCharValue(*char)),
+                    model::DeadKeyCombo::DeadKey(char) => DeadKeyCombo::DeadKey { deadKey: WideCharValue(*char) }
+                }
             );
         }
 
         Self {
             name: value.name.clone(),
-            combos: combos
+            combos,
+            comboFlags: value.combo_flags.iter().map(|(char, flags)| (WideCharValue(*char), *flags)).collect()
+        }
+    }
+
+    fn into_model(self) -> model::DeadKeyDesc {
+        let mut combos = IndexMap::new();
+        for (char, combo) in self.combos {
+            combos.insert(char.0, match combo {
+                DeadKeyCombo::Char(char) => model::DeadKeyCombo::Char(char.0),
+                DeadKeyCombo::DeadKey { deadKey } => model::DeadKeyCombo::DeadKey(deadKey.0)
+            });
+        }
+
+        model::DeadKeyDesc {
+            name: self.name,
+            combos,
+            combo_flags: self.comboFlags.into_iter().map(|(char, flags)| (char.0, flags)).collect()
         }
     }
-}
\ No newline at end of file
+}
+
+/// Combos resulting in another dead key reference it by its
+/// character in the top-level deadKeys map.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+#[allow(non_snake_case)]
+enum DeadKeyCombo {
+    Char(WideCharValue),
+    DeadKey { deadKey: WideCharValue }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(default)]
+#[allow(non_snake_case)]
+struct LayoutMetadataDesc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    displayName: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    companyName: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fileVersion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    productVersion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    codepage: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    machine: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    klid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    localeId: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    localeName: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layoutText: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layoutFile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layoutId: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    displayNameResource: Option<String>,
+}
+
+impl LayoutMetadataDesc {
+    fn from_model(value: &model::LayoutMetadata) -> Self {
+        Self {
+            displayName: value.display_name.clone(),
+            companyName: value.company_name.clone(),
+            fileVersion: value.file_version.clone(),
+            productVersion: value.product_version.clone(),
+            language: value.language.map(|language| format!("{:04X}", language)),
+            codepage: value.codepage,
+            timestamp: value.timestamp,
+            machine: value.machine.map(|machine| match model::LayoutMetadata::machine_to_name(machine) {
+                Some(name) => name.to_string(),
+                None => format!("{:04X}", machine)
+            }),
+            klid: value.klid.map(|klid| format!("{:08X}", klid)),
+            localeId: value.locale_id.map(|locale_id| format!("{:04X}", locale_id)),
+            localeName: value.locale_name.clone(),
+            layoutText: value.layout_text.clone(),
+            layoutFile: value.layout_file.clone(),
+            layoutId: value.layout_id.map(|layout_id| format!("{:04X}", layout_id)),
+            displayNameResource: value.display_name_resource.clone(),
+        }
+    }
+
+    fn into_model(self) -> Result<model::LayoutMetadata, String> {
+        let mut value = model::LayoutMetadata::new();
+        value.display_name = self.displayName;
+        value.company_name = self.companyName;
+        value.file_version = self.fileVersion;
+        value.product_version = self.productVersion;
+        value.language = parse_hex("language", self.language)?;
+        value.codepage = self.codepage;
+        value.timestamp = self.timestamp;
+        value.machine = match self.machine {
+            Some(machine) => match model::LayoutMetadata::machine_from_name(&machine) {
+                Some(machine) => Some(machine),
+                None => parse_hex("machine", Some(machine))?
+            },
+            None => None
+        };
+        value.klid = parse_hex("klid", self.klid)?;
+        value.locale_id = parse_hex("localeId", self.localeId)?;
+        value.locale_name = self.localeName;
+        value.layout_text = self.layoutText;
+        value.layout_file = self.layoutFile;
+        value.layout_id = parse_hex("layoutId", self.layoutId)?;
+        value.display_name_resource = self.displayNameResource;
+        Ok(value)
+    }
+}
+
+/// Parses an optional hex number of a metadata field, reporting malformed ones as the command line does.
+fn parse_hex<T: TryFrom<u32>>(name: &str, value: Option<String>) -> Result<Option<T>, String> {
+    let Some(value) = value else { return Ok(None) };
+    u32::from_str_radix(&value, 16).ok()
+        .and_then(|number| T::try_from(number).ok())
+        .map(Some)
+        .ok_or_else(|| format!("Invalid value for {}: {}", name, value))
+}
@@ -1,9 +1,13 @@
 mod json_model;
 
-use serde_json::to_string_pretty;
+use serde_json::{from_str, to_string_pretty};
 
 impl crate::model::KeyboardDesc {
     pub fn to_json(&self) -> String {
-        to_string_pretty(&json_model::Document::from_model(&self)).unwrap()
+        to_string_pretty(&json_model::Document::from_model(self)).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        from_str::<json_model::Document>(json)?.into_model().map_err(serde::de::Error::custom)
     }
 }
\ No newline at end of file
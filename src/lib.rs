@@ -0,0 +1,9 @@
+pub mod model;
+pub mod read_dll;
+pub mod json_format;
+pub mod pe;
+pub mod installer_format;
+pub mod simulator;
+pub mod svg_format;
+pub mod html_format;
+pub mod text_format;
@@ -1,13 +1,242 @@
-mod model;
-mod read_dll;
-mod json_format;
+use kbdc::{installer_format, model, read_dll, simulator, svg_format};
+use kbdc::model::KeyboardDesc;
+
+const USAGE: &str = "\
+Usage: kbdc [options] <input>
+
+The input is either a keyboard layout DLL or a JSON layout description.
+
+Options:
+  -o, --output <path>     Write the output to a file instead of stdout.
+  -f, --format <format>   The output format: json (default), reg, inf, svg,
+                          html, md or txt.
+                          Defaults to the output file extension if recognized.
+  --klid <hex>            Sets the keyboard layout identifier, such as 00000409.
+  --locale-id <hex>       Sets the Windows locale identifier, such as 0409.
+  --locale <name>         Sets the BCP-47 locale name, such as en-US.
+  --layout-text <text>    Sets the layout display name in the registry.
+  --layout-file <name>    Sets the layout DLL file name in the registry.
+  --layout-id <hex>       Sets the layout variant identifier in the registry.
+  --installed-layouts <path>
+                          A regedit export of the Keyboard Layouts key of the target system,
+                          whose KLIDs and layout identifiers .reg and .inf files avoid.
+  --layers <layers>       The layers to draw in SVG and HTML diagrams, comma-separated
+                          among base, shift, altgr, shift-altgr, left-thumb and right-thumb.
+  --font <family>         The font family of SVG diagrams and HTML sheets.
+  --theme <theme>         The colour theme of SVG diagrams and HTML sheets: light or dark.
+  --dead-key-diagrams     Adds an SVG diagram per dead key showing its combinations.
+  --caps-lock             Shows what keys type with Caps Lock on in SVG diagrams and HTML sheets.
+  --kana-lock             Shows what keys type with Kana Lock on in SVG diagrams and HTML sheets.
+  --max-table-rows <n>    The maximum number of rows of any table read from a layout DLL.
+  --max-string-length <n> The maximum length of key names read from a layout DLL.
+  --trace <path>          Types a keystroke trace with the layout instead of writing it,
+                          reporting the text differing from what the trace expects.";
+
+struct Options {
+    input_path: String,
+    output_path: Option<String>,
+    format: Option<String>,
+    metadata_overrides: Vec<(String, String)>,
+    svg_options: svg_format::SvgOptions,
+    installer_options: installer_format::InstallerOptions,
+    read_limits: read_dll::ReadLimits,
+    trace_path: Option<String>,
+}
 
 fn main() {
-    let descriptor = read_dll::read_keyboard(r"C:\Windows\System32\KBDUS.DLL".to_owned());
-    println!("Found {} scan codes, {} virtual keys, {} dead keys",
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let mut descriptor = if options.input_path.to_ascii_lowercase().ends_with(".json") {
+        let json = std::fs::read_to_string(&options.input_path).expect("Failed to read input file");
+        KeyboardDesc::from_json(&json).expect("Failed to parse JSON layout")
+    }
+    else {
+        match read_dll::read_keyboard(options.input_path.clone(), &options.read_limits) {
+            Ok(descriptor) => descriptor,
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    for (name, value) in &options.metadata_overrides {
+        if let Err(message) = apply_metadata_override(&mut descriptor.metadata, name, value) {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    }
+    descriptor.metadata.complete_locale();
+
+    for issue in descriptor.validate() {
+        eprintln!("warning: {}", issue);
+    }
+
+    eprintln!("Found {} scan codes, {} virtual keys, {} dead keys",
         descriptor.physical_keys.len(),
         descriptor.virtual_keys.len(),
         descriptor.dead_keys.len());
 
-    println!("{}", descriptor.to_json());
+    if let Some(trace_path) = &options.trace_path {
+        run_trace(&descriptor, trace_path);
+        return;
+    }
+
+    let format = options.format.clone()
+        .or_else(|| options.output_path.as_deref().and_then(format_from_extension))
+        .unwrap_or("json".to_string());
+    let output = match format.as_str() {
+        "json" => Ok(descriptor.to_json()),
+        "reg" => descriptor.to_reg(&options.installer_options),
+        "inf" => descriptor.to_inf(&options.installer_options),
+        "svg" => Ok(descriptor.to_svg(&options.svg_options)),
+        "html" => Ok(descriptor.to_html(&options.svg_options)),
+        "md" => Ok(descriptor.to_markdown()),
+        "txt" => Ok(descriptor.to_text()),
+        _ => Err(format!("Unknown output format: {}", format))
+    };
+
+    let output = match output {
+        Ok(output) => output,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    match options.output_path {
+        Some(output_path) => std::fs::write(output_path, output).expect("Failed to write output file"),
+        None => println!("{}", output)
+    }
+}
+
+fn run_trace(descriptor: &KeyboardDesc, trace_path: &str) {
+    let text = std::fs::read_to_string(trace_path).expect("Failed to read trace file");
+    let trace = match simulator::Trace::parse(&text) {
+        Ok(trace) => trace,
+        Err(error) => {
+            eprintln!("{}: {}", trace_path, error);
+            std::process::exit(1);
+        }
+    };
+
+    let mismatches = trace.run(descriptor);
+    for mismatch in &mismatches {
+        eprintln!("{}: {}", trace_path, mismatch);
+    }
+    if !mismatches.is_empty() {
+        std::process::exit(1);
+    }
+    eprintln!("Trace passed");
+}
+
+/// Reads a .reg file, which regedit exports as UTF-16LE with a byte order mark.
+fn read_reg_file(path: &str) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|error| format!("Failed to read {}: {}", path, error))?;
+    match bytes.strip_prefix(&[0xFF, 0xFE]) {
+        Some(utf16) => {
+            let units: Vec<u16> = utf16.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+            Ok(String::from_utf16_lossy(&units))
+        },
+        None => String::from_utf8(bytes).map_err(|_| format!("Invalid text in {}", path))
+    }
+}
+
+fn format_from_extension(path: &str) -> Option<String> {
+    let extension = std::path::Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "json" | "reg" | "inf" | "svg" | "html" | "md" | "txt" => Some(extension),
+        _ => None
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut input_path = None;
+    let mut output_path = None;
+    let mut format = None;
+    let mut metadata_overrides = Vec::new();
+    let mut svg_options = svg_format::SvgOptions::new();
+    let mut installer_options = installer_format::InstallerOptions::new();
+    let mut read_limits = read_dll::ReadLimits::new();
+    let mut trace_path = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output_path = Some(args.next().ok_or("Missing value for --output")?);
+            },
+            "-f" | "--format" => {
+                format = Some(args.next().ok_or("Missing value for --format")?);
+            },
+            "--klid" | "--locale-id" | "--locale" | "--layout-text" | "--layout-file" | "--layout-id" => {
+                let value = args.next().ok_or_else(|| format!("Missing value for {}", arg))?;
+                metadata_overrides.push((arg, value));
+            },
+            "--installed-layouts" => {
+                let path = args.next().ok_or("Missing value for --installed-layouts")?;
+                installer_options.installed_layouts = installer_format::read_installed_layouts(&read_reg_file(&path)?);
+            },
+            "--layers" => {
+                let value = args.next().ok_or("Missing value for --layers")?;
+                svg_options.layers = value.split(',')
+                    .map(|name| svg_format::SvgLayer::from_name(name.trim()).ok_or_else(|| format!("Unknown layer: {}", name)))
+                    .collect::<Result<_, _>>()?;
+            },
+            "--font" => {
+                svg_options.font_family = args.next().ok_or("Missing value for --font")?;
+            },
+            "--theme" => {
+                let value = args.next().ok_or("Missing value for --theme")?;
+                svg_options.theme = svg_format::SvgTheme::from_name(&value).ok_or_else(|| format!("Unknown theme: {}", value))?;
+            },
+            "--dead-key-diagrams" => svg_options.dead_key_diagrams = true,
+            "--caps-lock" => svg_options.caps_lock = true,
+            "--kana-lock" => svg_options.kana_lock = true,
+            "--max-table-rows" => {
+                let value = args.next().ok_or("Missing value for --max-table-rows")?;
+                read_limits.max_table_rows = value.parse().map_err(|_| format!("Invalid value for --max-table-rows: {}", value))?;
+            },
+            "--max-string-length" => {
+                let value = args.next().ok_or("Missing value for --max-string-length")?;
+                read_limits.max_string_length = value.parse().map_err(|_| format!("Invalid value for --max-string-length: {}", value))?;
+            },
+            "--trace" => {
+                trace_path = Some(args.next().ok_or("Missing value for --trace")?);
+            },
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+            _ if input_path.is_some() => return Err(format!("Unexpected argument: {}", arg)),
+            _ => input_path = Some(arg)
+        }
+    }
+
+    Ok(Options {
+        input_path: input_path.ok_or("Missing input path")?,
+        output_path,
+        format,
+        metadata_overrides,
+        svg_options,
+        installer_options,
+        read_limits,
+        trace_path,
+    })
+}
+
+fn apply_metadata_override(metadata: &mut model::LayoutMetadata, name: &str, value: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid value for {}: {}", name, value);
+    match name {
+        "--klid" => metadata.klid = Some(u32::from_str_radix(value, 16).map_err(|_| invalid())?),
+        "--locale-id" => metadata.locale_id = Some(u16::from_str_radix(value, 16).map_err(|_| invalid())?),
+        "--locale" => metadata.locale_name = Some(value.to_string()),
+        "--layout-text" => metadata.layout_text = Some(value.to_string()),
+        "--layout-file" => metadata.layout_file = Some(value.to_string()),
+        "--layout-id" => metadata.layout_id = Some(u16::from_str_radix(value, 16).map_err(|_| invalid())?),
+        _ => return Err(format!("Unknown option: {}", name))
+    }
+    Ok(())
 }
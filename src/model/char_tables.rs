@@ -0,0 +1,85 @@
+// Describes how the pVkToWcharTable tables of a layout DLL are laid out,
+// which writers need to reproduce the original binary rather than an equivalent one.
+// The columns of the tables are given by the layout's ShiftStateTable.
+
+use crate::model::keyboard_layer::*;
+use crate::model::shift_states::*;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct CharTableLayout {
+    /// The VK_TO_WCHARS tables, in pVkToWcharTable order.
+    pub groups: Vec<CharTableGroup>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CharTableGroup {
+    /// The number of character columns of the table rows.
+    pub modifications: u8, // nModifications
+    /// The size of the table rows, in bytes.
+    pub entry_size: u8, // cbSize
+}
+
+impl CharTableLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CharTableGroup {
+    pub fn new(modifications: u8) -> Self {
+        // VK_TO_WCHARSn rows are a virtual key and attributes byte followed by n characters.
+        Self { modifications, entry_size: 2 + 2 * modifications }
+    }
+}
+
+impl KeyboardDesc {
+    /// Checks whether every typing key fits the stored table layout, which may not be
+    /// the case if keys or modifiers were edited since the layout was read.
+    pub fn fits_char_table_layout(&self) -> bool {
+        let Some(char_tables) = &self.char_tables else { return false };
+        self.virtual_keys.values().all(|key_effect| {
+            let KeyEffect::Typing(key_typing) = key_effect else { return true };
+            let Some(group) = key_typing.group.and_then(|group| char_tables.groups.get(group)) else { return false };
+            key_typing.all_effects().all(|(modifiers, _)| self.shift_states.column(modifiers)
+                .is_some_and(|column| column < group.modifications))
+        })
+    }
+
+    /// Computes the most compact table layout for the shift state table,
+    /// with keys grouped by the number of columns they need.
+    pub fn optimal_char_table_layout(&self) -> CharTableLayout {
+        let mut group_sizes: Vec<u8> = self.virtual_keys.values()
+            .filter_map(|key_effect| match key_effect {
+                KeyEffect::Typing(key_typing) => Some(columns_needed(&self.shift_states, key_typing)),
+                KeyEffect::Modifier(_) => None
+            })
+            .collect();
+        group_sizes.sort();
+        group_sizes.dedup();
+        CharTableLayout { groups: group_sizes.into_iter().map(CharTableGroup::new).collect() }
+    }
+
+    /// Replaces the table layout by the optimal one and assigns keys to its groups.
+    /// The shift state table is kept, preserving custom states, unless it lacks some used by keys.
+    pub fn regroup_char_tables(&mut self) {
+        if !self.fits_shift_state_table() {
+            self.shift_states = self.optimal_shift_state_table();
+        }
+
+        let char_tables = self.optimal_char_table_layout();
+        for key_effect in self.virtual_keys.values_mut() {
+            let KeyEffect::Typing(key_typing) = key_effect else { continue };
+            let columns_needed = columns_needed(&self.shift_states, key_typing);
+            key_typing.group = char_tables.groups.iter().position(|group| group.modifications == columns_needed);
+        }
+        self.char_tables = Some(char_tables);
+    }
+}
+
+fn columns_needed(shift_states: &ShiftStateTable, key_typing: &KeyTyping) -> u8 {
+    key_typing.all_effects()
+        .filter_map(|(modifiers, _)| shift_states.column(modifiers))
+        .map(|column| column + 1)
+        .max()
+        .unwrap_or(1)
+}
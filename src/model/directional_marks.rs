@@ -0,0 +1,20 @@
+// Models the directional marks of right-to-left layouts with KLLF_LRM_RLM, where Shift+Backspace
+// types a Left-to-Right Mark with the left Shift key and a Right-to-Left Mark with the right one.
+
+use crate::model::keyboard_layer::*;
+use crate::model::virtual_keys::*;
+use crate::model::wide_chars::*;
+
+pub const LEFT_TO_RIGHT_MARK: WideChar = WideChar::Scalar('\u{200E}');
+pub const RIGHT_TO_LEFT_MARK: WideChar = WideChar::Scalar('\u{200F}');
+
+impl KeyboardDesc {
+    /// Gets the keystrokes typing directional marks, as the shift key pressed with Backspace and the mark typed.
+    /// These are not in the layout tables, so are empty unless the layout supports directionality.
+    pub fn directional_marks(&self) -> Vec<(VirtualKey, WideChar)> {
+        if !self.supports_directionality {
+            return Vec::new()
+        }
+        vec![(VirtualKey::LEFT_SHIFT, LEFT_TO_RIGHT_MARK), (VirtualKey::RIGHT_SHIFT, RIGHT_TO_LEFT_MARK)]
+    }
+}
@@ -0,0 +1,304 @@
+// Describes where keys physically sit on common keyboard form factors.
+// Positions and sizes are in key units (1u = 19.05mm), from the top-left
+// corner of the Escape key, following the conventional PC 101-key arrangement.
+
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use crate::model::*;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum GeometryKind {
+    /// The US-style board, with a wide Backslash above a wide Enter.
+    Ansi104,
+    /// The European-style board, with a tall Enter and an extra key right of Left Shift.
+    Iso105,
+    /// The Japanese board, with Muhenkan/Henkan/Kana keys around a shorter space bar.
+    Jis109,
+    /// The Brazilian board, an ISO board with an extra key left of Right Shift and a split numpad plus.
+    Abnt2,
+    /// The Korean board, an ANSI board with Hanja and Hangul keys around the space bar.
+    Korean106,
+}
+
+impl GeometryKind {
+    pub const ALL: [GeometryKind; 5] = [Self::Ansi104, Self::Iso105, Self::Jis109, Self::Abnt2, Self::Korean106];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ansi104 => "ansi104",
+            Self::Iso105 => "iso105",
+            Self::Jis109 => "jis109",
+            Self::Abnt2 => "abnt2",
+            Self::Korean106 => "korean106",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum KeyShape {
+    Rectangle,
+    /// The inverted L-shaped Enter key of ISO and JIS boards. The key's rectangle bounds the top part,
+    /// while the bottom part is narrower by a quarter unit on the left.
+    IsoEnter,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct KeyGeometry {
+    pub scan_code: ScanCode,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub shape: KeyShape,
+}
+
+pub struct KeyboardGeometry {
+    pub kind: GeometryKind,
+    pub keys: Vec<KeyGeometry>,
+}
+
+impl KeyboardGeometry {
+    /// The width of the ISO Enter bottom part, relative to its top part.
+    pub const ISO_ENTER_INSET: f32 = 0.25;
+
+    pub fn new(kind: GeometryKind) -> Self {
+        let mut builder = GeometryBuilder { keys: Vec::new(), x: 0.0, y: 0.0 };
+        builder.function_row();
+        match kind {
+            GeometryKind::Ansi104 => builder.ansi_block().standard_bottom_row(),
+            GeometryKind::Iso105 => builder.iso_block(false).standard_bottom_row(),
+            GeometryKind::Jis109 => builder.jis_block().jis_bottom_row(),
+            GeometryKind::Abnt2 => builder.iso_block(true).standard_bottom_row(),
+            GeometryKind::Korean106 => builder.ansi_block().korean_bottom_row(),
+        };
+        builder.navigation_cluster();
+        builder.numpad(kind == GeometryKind::Abnt2);
+        Self { kind, keys: builder.keys }
+    }
+
+    pub fn key(&self, scan_code: ScanCode) -> Option<&KeyGeometry> {
+        self.keys.iter().find(|key| key.scan_code == scan_code)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.keys.iter().map(|key| key.x + key.width).fold(0.0, f32::max)
+    }
+
+    pub fn height(&self) -> f32 {
+        self.keys.iter().map(|key| key.y + key.height).fold(0.0, f32::max)
+    }
+}
+
+/// Locales whose keyboards are predominantly sold in the ANSI form factor,
+/// even though their layouts assign characters to VK_OEM_102. Only consulted
+/// for layouts without a C12 key to tell whether that key is a copy of it.
+const ANSI_LOCALE_IDS: &[u16] = &[
+    0x0409, // en-US
+    0x0404, // zh-TW
+    0x0804, // zh-CN
+    0x0C04, // zh-HK
+    0x041E, // th-TH
+    0x3409, // en-PH
+];
+
+impl KeyboardDesc {
+    /// The geometry the layout targets, either declared or inferred.
+    pub fn geometry_kind(&self) -> GeometryKind {
+        self.geometry.unwrap_or_else(|| self.infer_geometry_kind())
+    }
+
+    /// Guesses the geometry a layout was designed for from the keyboard type
+    /// and the keys only present on some form factors.
+    pub fn infer_geometry_kind(&self) -> GeometryKind {
+        // Most layouts map the form factor-specific scan codes to virtual keys,
+        // so only consider those which actually type something.
+        let types = |vk: u16| matches!(
+            self.virtual_keys.get(&VirtualKey { code: vk as u8 }),
+            Some(KeyEffect::Typing(_)));
+
+        if self.type_value == KeyboardDesc::TYPE_JAPAN { GeometryKind::Jis109 }
+        else if self.type_value == KeyboardDesc::TYPE_KOREA { GeometryKind::Korean106 }
+        else if types(VK_ABNT_C1) { GeometryKind::Abnt2 }
+        else if self.types_on_iso_only_key() { GeometryKind::Iso105 }
+        else { GeometryKind::Ansi104 }
+    }
+
+    /// Whether the key right of Left Shift (B00, VK_OEM_102 on scan code 56) types characters of its own.
+    /// ANSI layouts often make it a copy of the C12 key (Backslash) for ISO boards, which says nothing
+    /// about the board they target.
+    fn types_on_iso_only_key(&self) -> bool {
+        let key_typing = |scan_code: ScanCode| {
+            let physical_key = self.physical_keys.get(&scan_code)?;
+            match self.virtual_keys.get(&physical_key.virtual_key)? {
+                KeyEffect::Typing(key_typing) => Some(key_typing),
+                KeyEffect::Modifier(_) => None
+            }
+        };
+
+        let Some(b00) = key_typing(ScanCode::INTL_BACKSLASH) else { return false };
+        match key_typing(ScanCode::BACKSLASH) {
+            Some(c12) => !b00.sorted_by_modifiers().eq(c12.sorted_by_modifiers()),
+            None => !self.metadata.locale_id.is_some_and(|id| ANSI_LOCALE_IDS.contains(&id))
+        }
+    }
+}
+
+struct GeometryBuilder {
+    keys: Vec<KeyGeometry>,
+    x: f32,
+    y: f32,
+}
+
+impl GeometryBuilder {
+    // Rows of the alphanumeric section, below the function row.
+    const ROW_E: f32 = 1.5;
+    const ROW_D: f32 = 2.5;
+    const ROW_C: f32 = 3.5;
+    const ROW_B: f32 = 4.5;
+    const ROW_A: f32 = 5.5;
+
+    fn row(&mut self, x: f32, y: f32) -> &mut Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    fn gap(&mut self, width: f32) -> &mut Self {
+        self.x += width;
+        self
+    }
+
+    fn sized_key(&mut self, scan_code: ScanCode, width: f32, height: f32, shape: KeyShape) -> &mut Self {
+        self.keys.push(KeyGeometry { scan_code, x: self.x, y: self.y, width, height, shape });
+        self.x += width;
+        self
+    }
+
+    fn wide_key(&mut self, scan_code: ScanCode, width: f32) -> &mut Self {
+        self.sized_key(scan_code, width, 1.0, KeyShape::Rectangle)
+    }
+
+    fn keys(&mut self, scan_codes: &[ScanCode]) -> &mut Self {
+        for scan_code in scan_codes {
+            self.wide_key(*scan_code, 1.0);
+        }
+        self
+    }
+
+    fn function_row(&mut self) -> &mut Self {
+        self.row(0.0, 0.0)
+            .keys(&[ScanCode::ESCAPE]).gap(1.0)
+            .keys(&[ScanCode::F1, ScanCode::F2, ScanCode::F3, ScanCode::F4]).gap(0.5)
+            .keys(&[ScanCode::F5, ScanCode::F6, ScanCode::F7, ScanCode::F8]).gap(0.5)
+            .keys(&[ScanCode::F9, ScanCode::F10, ScanCode::F11, ScanCode::F12]).gap(0.25)
+            .keys(&[ScanCode::PRINT_SCREEN, ScanCode::SCROLL_LOCK, ScanCode::PAUSE])
+    }
+
+    fn digits(&mut self) -> &mut Self {
+        self.keys(&[
+            ScanCode::BACKQUOTE, ScanCode::_1, ScanCode::_2, ScanCode::_3, ScanCode::_4, ScanCode::_5,
+            ScanCode::_6, ScanCode::_7, ScanCode::_8, ScanCode::_9, ScanCode::_0, ScanCode::MINUS, ScanCode::EQUAL])
+    }
+
+    fn top_letters(&mut self) -> &mut Self {
+        self.keys(&[
+            ScanCode::Q, ScanCode::W, ScanCode::E, ScanCode::R, ScanCode::T, ScanCode::Y,
+            ScanCode::U, ScanCode::I, ScanCode::O, ScanCode::P, ScanCode::BRACKET_LEFT, ScanCode::BRACKET_RIGHT])
+    }
+
+    fn home_letters(&mut self) -> &mut Self {
+        self.keys(&[
+            ScanCode::A, ScanCode::S, ScanCode::D, ScanCode::F, ScanCode::G, ScanCode::H,
+            ScanCode::J, ScanCode::K, ScanCode::L, ScanCode::SEMICOLON, ScanCode::QUOTE])
+    }
+
+    fn bottom_letters(&mut self) -> &mut Self {
+        self.keys(&[
+            ScanCode::Z, ScanCode::X, ScanCode::C, ScanCode::V, ScanCode::B,
+            ScanCode::N, ScanCode::M, ScanCode::COMMA, ScanCode::PERIOD, ScanCode::SLASH])
+    }
+
+    fn ansi_block(&mut self) -> &mut Self {
+        self.row(0.0, Self::ROW_E).digits().wide_key(ScanCode::BACKSPACE, 2.0);
+        self.row(0.0, Self::ROW_D).wide_key(ScanCode::TAB, 1.5).top_letters().wide_key(ScanCode::BACKSLASH, 1.5);
+        self.row(0.0, Self::ROW_C).wide_key(ScanCode::CAPS_LOCK, 1.75).home_letters().wide_key(ScanCode::ENTER, 2.25);
+        self.row(0.0, Self::ROW_B).wide_key(ScanCode::LEFT_SHIFT, 2.25).bottom_letters().wide_key(ScanCode::RIGHT_SHIFT, 2.75)
+    }
+
+    fn iso_block(&mut self, abnt: bool) -> &mut Self {
+        self.row(0.0, Self::ROW_E).digits().wide_key(ScanCode::BACKSPACE, 2.0);
+        self.row(0.0, Self::ROW_D).wide_key(ScanCode::TAB, 1.5).top_letters()
+            .sized_key(ScanCode::ENTER, 1.5, 2.0, KeyShape::IsoEnter);
+        self.row(0.0, Self::ROW_C).wide_key(ScanCode::CAPS_LOCK, 1.75).home_letters().keys(&[ScanCode::BACKSLASH]);
+        self.row(0.0, Self::ROW_B).wide_key(ScanCode::LEFT_SHIFT, 1.25).keys(&[ScanCode::INTL_BACKSLASH]).bottom_letters();
+        if abnt {
+            self.keys(&[ScanCode::INTL_RO]).wide_key(ScanCode::RIGHT_SHIFT, 1.75)
+        }
+        else {
+            self.wide_key(ScanCode::RIGHT_SHIFT, 2.75)
+        }
+    }
+
+    fn jis_block(&mut self) -> &mut Self {
+        self.row(0.0, Self::ROW_E).digits().keys(&[ScanCode::INTL_YEN]).wide_key(ScanCode::BACKSPACE, 1.0);
+        self.row(0.0, Self::ROW_D).wide_key(ScanCode::TAB, 1.5).top_letters()
+            .sized_key(ScanCode::ENTER, 1.5, 2.0, KeyShape::IsoEnter);
+        self.row(0.0, Self::ROW_C).wide_key(ScanCode::CAPS_LOCK, 1.75).home_letters().keys(&[ScanCode::BACKSLASH]);
+        self.row(0.0, Self::ROW_B).wide_key(ScanCode::LEFT_SHIFT, 2.25).bottom_letters()
+            .keys(&[ScanCode::INTL_RO]).wide_key(ScanCode::RIGHT_SHIFT, 1.75)
+    }
+
+    fn standard_bottom_row(&mut self) -> &mut Self {
+        self.row(0.0, Self::ROW_A)
+            .wide_key(ScanCode::LEFT_CONTROL, 1.25).wide_key(ScanCode::LEFT_META, 1.25).wide_key(ScanCode::LEFT_ALT, 1.25)
+            .wide_key(ScanCode::SPACE, 6.25)
+            .wide_key(ScanCode::RIGHT_ALT, 1.25).wide_key(ScanCode::RIGHT_META, 1.25)
+            .wide_key(ScanCode::CONTEXT_MENU, 1.25).wide_key(ScanCode::RIGHT_CONTROL, 1.25)
+    }
+
+    fn jis_bottom_row(&mut self) -> &mut Self {
+        self.row(0.0, Self::ROW_A)
+            .wide_key(ScanCode::LEFT_CONTROL, 1.25).wide_key(ScanCode::LEFT_META, 1.25).wide_key(ScanCode::LEFT_ALT, 1.25)
+            .wide_key(ScanCode::NON_CONVERT, 1.25).wide_key(ScanCode::SPACE, 2.5)
+            .wide_key(ScanCode::CONVERT, 1.25).wide_key(ScanCode::KANA_MODE, 1.25)
+            .wide_key(ScanCode::RIGHT_ALT, 1.25).wide_key(ScanCode::RIGHT_META, 1.25)
+            .wide_key(ScanCode::CONTEXT_MENU, 1.25).wide_key(ScanCode::RIGHT_CONTROL, 1.25)
+    }
+
+    fn korean_bottom_row(&mut self) -> &mut Self {
+        // LANG2 is Hanja and LANG1 is Han/Yeong
+        self.row(0.0, Self::ROW_A)
+            .wide_key(ScanCode::LEFT_CONTROL, 1.25).wide_key(ScanCode::LEFT_META, 1.25).wide_key(ScanCode::LEFT_ALT, 1.25)
+            .wide_key(ScanCode::LANG2, 1.25).wide_key(ScanCode::SPACE, 3.75).wide_key(ScanCode::LANG1, 1.25)
+            .wide_key(ScanCode::RIGHT_ALT, 1.25).wide_key(ScanCode::RIGHT_META, 1.25)
+            .wide_key(ScanCode::CONTEXT_MENU, 1.25).wide_key(ScanCode::RIGHT_CONTROL, 1.25)
+    }
+
+    fn navigation_cluster(&mut self) -> &mut Self {
+        self.row(15.25, Self::ROW_E).keys(&[ScanCode::INSERT, ScanCode::HOME, ScanCode::PAGE_UP]);
+        self.row(15.25, Self::ROW_D).keys(&[ScanCode::DELETE, ScanCode::END, ScanCode::PAGE_DOWN]);
+        self.row(16.25, Self::ROW_B).keys(&[ScanCode::UP]);
+        self.row(15.25, Self::ROW_A).keys(&[ScanCode::LEFT, ScanCode::DOWN, ScanCode::RIGHT])
+    }
+
+    fn numpad(&mut self, abnt: bool) -> &mut Self {
+        const X: f32 = 18.5;
+        self.row(X, Self::ROW_E).keys(&[ScanCode::NUM_LOCK, ScanCode::NUMPAD_DIVIDE, ScanCode::NUMPAD_MULTIPLY, ScanCode::NUMPAD_SUBTRACT]);
+        self.row(X, Self::ROW_D).keys(&[ScanCode::NUMPAD_7, ScanCode::NUMPAD_8, ScanCode::NUMPAD_9]);
+        if abnt {
+            // The ABNT2 numpad splits the plus key to fit a thousands separator key
+            self.keys(&[ScanCode::NUMPAD_ADD]);
+            self.row(X + 3.0, Self::ROW_C).keys(&[ScanCode::NUMPAD_COMMA]);
+        }
+        else {
+            self.sized_key(ScanCode::NUMPAD_ADD, 1.0, 2.0, KeyShape::Rectangle);
+        }
+        self.row(X, Self::ROW_C).keys(&[ScanCode::NUMPAD_4, ScanCode::NUMPAD_5, ScanCode::NUMPAD_6]);
+        self.row(X, Self::ROW_B).keys(&[ScanCode::NUMPAD_1, ScanCode::NUMPAD_2, ScanCode::NUMPAD_3])
+            .sized_key(ScanCode::NUMPAD_ENTER, 1.0, 2.0, KeyShape::Rectangle);
+        self.row(X, Self::ROW_A).wide_key(ScanCode::NUMPAD_0, 2.0).keys(&[ScanCode::NUMPAD_DECIMAL])
+    }
+}
@@ -0,0 +1,30 @@
+// Models the kana lock of Japanese layouts: the Kana key toggles a lock, like Caps Lock,
+// and while it is on the keys marked KANALOK type on their kana (KBDKANA) columns.
+
+use crate::model::keyboard_layer::*;
+use crate::model::virtual_keys::*;
+
+impl KeyboardDesc {
+    /// Gets the modifier keys setting the kana modifier, which toggle kana lock rather than being held.
+    pub fn kana_toggle_keys(&self) -> Vec<VirtualKey> {
+        self.sorted_virtual_keys()
+            .filter(|(_, key_effect)| matches!(key_effect, KeyEffect::Modifier(key_modifiers) if key_modifiers.kana))
+            .map(|(virtual_key, _)| *virtual_key)
+            .collect()
+    }
+
+    /// Whether any key types something with the kana modifier.
+    pub fn uses_kana(&self) -> bool {
+        self.virtual_keys.values().any(|key_effect| match key_effect {
+            KeyEffect::Typing(key_typing) => key_typing.all_effects().any(|(modifiers, _)| modifiers.kana),
+            KeyEffect::Modifier(_) => false
+        })
+    }
+}
+
+impl KeyTyping {
+    /// Gets the modifiers the key types with, adding the kana modifier if the key supports kana lock and it is on.
+    pub fn kana_lock_modifiers(&self, modifiers: &KeyModifiers, kana_lock: bool) -> KeyModifiers {
+        KeyModifiers { kana: modifiers.kana || (kana_lock && self.kana_support), ..*modifiers }
+    }
+}
@@ -0,0 +1,178 @@
+// Emulates MapVirtualKeyEx and GetKeyNameText over a layout's tables, returning what
+// Windows would for the same layout, including the quirks applications rely on.
+
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use crate::model::keyboard_layer::*;
+use crate::model::scan_codes::*;
+use crate::model::special_keys::NUMPAD_KEYS;
+use crate::model::virtual_keys::*;
+use crate::model::wide_chars::*;
+
+/// The translations of MapVirtualKeyEx, by their MAPVK_* value.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MapVirtualKeyMode {
+    /// Virtual key to scan code, without its E0/E1 prefix.
+    VkToVsc, // MAPVK_VK_TO_VSC
+    /// Scan code to virtual key, not distinguishing left and right modifiers.
+    VscToVk, // MAPVK_VSC_TO_VK
+    /// Virtual key to its unshifted character, with the top bit set for dead keys.
+    VkToChar, // MAPVK_VK_TO_CHAR
+    /// Scan code to virtual key, distinguishing left and right modifiers.
+    VscToVkEx, // MAPVK_VSC_TO_VK_EX
+    /// Virtual key to scan code, with its E0/E1 prefix in the high byte.
+    VkToVscEx, // MAPVK_VK_TO_VSC_EX
+}
+
+impl MapVirtualKeyMode {
+    pub const ALL: [MapVirtualKeyMode; 5] = [Self::VkToVsc, Self::VscToVk, Self::VkToChar, Self::VscToVkEx, Self::VkToVscEx];
+
+    pub fn from_value(value: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.value() == value)
+    }
+
+    pub fn value(&self) -> u32 {
+        match self {
+            Self::VkToVsc => MAPVK_VK_TO_VSC,
+            Self::VscToVk => MAPVK_VSC_TO_VK,
+            Self::VkToChar => MAPVK_VK_TO_CHAR,
+            Self::VscToVkEx => MAPVK_VSC_TO_VK_EX,
+            Self::VkToVscEx => MAPVK_VK_TO_VSC_EX,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::VkToVsc => "MAPVK_VK_TO_VSC",
+            Self::VscToVk => "MAPVK_VSC_TO_VK",
+            Self::VkToChar => "MAPVK_VK_TO_CHAR",
+            Self::VscToVkEx => "MAPVK_VSC_TO_VK_EX",
+            Self::VkToVscEx => "MAPVK_VK_TO_VSC_EX",
+        }
+    }
+}
+
+/// The bits of GetKeyNameText's parameter, laid out as in WM_KEYDOWN's.
+const KEY_NAME_SCAN_CODE_SHIFT: u32 = 16;
+const KEY_NAME_EXTENDED_BIT: u32 = 1 << 24;
+const KEY_NAME_DONT_CARE_BIT: u32 = 1 << 25;
+
+const DEAD_KEY_BIT: u32 = 0x8000_0000;
+
+impl KeyboardDesc {
+    /// Emulates MapVirtualKeyEx, returning 0 where Windows finds no translation.
+    /// Scan codes take their E0/E1 prefix in the high byte, as in 0xE01D for the right Ctrl key.
+    pub fn map_virtual_key(&self, code: u32, mode: MapVirtualKeyMode) -> u32 {
+        match mode {
+            MapVirtualKeyMode::VkToVsc => self.scan_code_of(code).map_or(0, |scan_code| scan_code_value(scan_code) & 0xFF),
+            MapVirtualKeyMode::VkToVscEx => self.scan_code_of(code).map_or(0, scan_code_value),
+            MapVirtualKeyMode::VscToVk => self.virtual_key_of(code).map_or(0, |virtual_key| virtual_key.generic_modifier().code as u32),
+            MapVirtualKeyMode::VscToVkEx => self.virtual_key_of(code).map_or(0, |virtual_key| virtual_key.code as u32),
+            MapVirtualKeyMode::VkToChar => self.unshifted_char(code).map_or(0, |(unit, is_dead_key)| {
+                if is_dead_key { unit as u32 | DEAD_KEY_BIT } else { unit as u32 }
+            }),
+        }
+    }
+
+    /// Emulates GetKeyNameText, taking the scan code in bits 16-23 of the parameter, bit 24 for E0 scan codes
+    /// and bit 25 to name left and right modifier keys alike. Returns an empty string for keys without a name.
+    pub fn key_name_text(&self, lparam: u32) -> String {
+        let mut code = ((lparam >> KEY_NAME_SCAN_CODE_SHIFT) & 0xFF) as u8;
+        let mut is_extended = (lparam & KEY_NAME_EXTENDED_BIT) != 0;
+
+        // Windows checks these scan codes rather than their virtual keys.
+        if (lparam & KEY_NAME_DONT_CARE_BIT) != 0 {
+            if code == 0x36 && !is_extended {
+                code = 0x2A;
+            }
+            else if code == 0x1D || code == 0x38 {
+                is_extended = false;
+            }
+        }
+
+        let scan_code = if is_extended { ScanCode::Extended0(code) } else { ScanCode::Unescaped(code) };
+        if let Some(name) = self.physical_keys.get(&scan_code).and_then(|physical_key| physical_key.name.as_ref()) {
+            return name.clone()
+        }
+
+        // Keys without a name are named after the character they type, or the name of their dead key.
+        let virtual_key = self.physical_keys.get(&scan_code).map(|physical_key| physical_key.virtual_key);
+        match virtual_key.and_then(|virtual_key| self.unshifted_char(virtual_key.code as u32)) {
+            Some((unit, true)) => self.dead_keys.get(&WideChar::from_u16(unit))
+                .and_then(|dead_key| dead_key.name.clone())
+                .unwrap_or_else(|| String::from_utf16_lossy(&[unit])),
+            Some((unit, false)) => String::from_utf16_lossy(&[unit]),
+            None => String::new()
+        }
+    }
+
+    /// Finds the first scan code in table order which maps to a virtual key, with generic modifiers
+    /// mapping to their left key and keypad digits to the keypad keys (KBDNUMPAD).
+    fn scan_code_of(&self, code: u32) -> Option<ScanCode> {
+        let code = u8::try_from(code).ok()?;
+        let virtual_key = match (VirtualKey { code }) {
+            VirtualKey::SHIFT => VirtualKey::LEFT_SHIFT,
+            VirtualKey::CONTROL => VirtualKey::LEFT_CONTROL,
+            VirtualKey::ALT => VirtualKey::LEFT_ALT,
+            virtual_key => virtual_key
+        };
+        if virtual_key == VirtualKey::NONE {
+            return None
+        }
+
+        let found = self.physical_keys.iter().find(|(_, physical_key)| physical_key.virtual_key == virtual_key);
+        if let Some((scan_code, _)) = found {
+            return Some(*scan_code)
+        }
+
+        let (navigation_key, _) = NUMPAD_KEYS.iter().find(|(_, numpad_key)| *numpad_key == code as VIRTUAL_KEY)?;
+        self.physical_keys.iter()
+            .find(|(_, physical_key)| physical_key.virtual_key_flags.numpad && physical_key.virtual_key.code as VIRTUAL_KEY == *navigation_key)
+            .map(|(scan_code, _)| *scan_code)
+    }
+
+    fn virtual_key_of(&self, code: u32) -> Option<VirtualKey> {
+        let scan_code = match code >> 8 {
+            0x00 => ScanCode::Unescaped(code as u8),
+            0xE0 => ScanCode::Extended0(code as u8),
+            0xE1 => ScanCode::Extended1(code as u8),
+            _ => return None
+        };
+        let virtual_key = self.physical_keys.get(&scan_code)?.virtual_key;
+        (virtual_key != VirtualKey::NONE).then_some(virtual_key)
+    }
+
+    /// Gets the UTF-16 unit a virtual key types without modifiers, uppercased as Windows does,
+    /// and whether it is a dead key. Ligatures and characters beyond the BMP have no such unit.
+    fn unshifted_char(&self, code: u32) -> Option<(u16, bool)> {
+        let code = u8::try_from(code).ok()?;
+        let Some(KeyEffect::Typing(key_typing)) = self.virtual_keys.get(&VirtualKey { code }) else { return None };
+        let (char, is_dead_key) = match key_typing.by_modifiers.get(&KeyModifiers::from_bits(0))? {
+            TypingEffect::Char(char) => (*char, false),
+            TypingEffect::DeadKey(char) => (*char, true),
+            TypingEffect::Ligature(_) => return None
+        };
+
+        let char = match char {
+            WideChar::Scalar(scalar) => {
+                let mut uppercase = scalar.to_uppercase();
+                match (uppercase.next(), uppercase.next()) {
+                    (Some(upper), None) => WideChar::Scalar(upper),
+                    _ => char
+                }
+            },
+            WideChar::Surrogate(_) => char
+        };
+        match char.to_utf16()[..] {
+            [unit] => Some((unit, is_dead_key)),
+            _ => None
+        }
+    }
+}
+
+fn scan_code_value(scan_code: ScanCode) -> u32 {
+    match scan_code {
+        ScanCode::Unescaped(code) => code as u32,
+        ScanCode::Extended0(code) => 0xE000 | code as u32,
+        ScanCode::Extended1(code) => 0xE100 | code as u32,
+    }
+}
@@ -1,20 +1,29 @@
 // Represents the data returned by the KbdLayerDescriptor
 // entry point of a keyboard layout DLL.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
+use indexmap::IndexMap;
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
 use crate::model::scan_codes::*;
 use crate::model::virtual_keys::*;
+use crate::model::layout_metadata::*;
+use crate::model::wide_chars::*;
+use crate::model::geometry::*;
+use crate::model::char_tables::*;
+use crate::model::shift_states::*;
 
+#[derive(Default)]
 pub struct KeyboardDesc {
     // pusVSCtoVK, bMaxVSCtoVK, pVSCtoVK_E0, pVSCtoVK_E1
     pub physical_keys: BTreeMap<ScanCode, PhysicalKeyDesc>,
 
     // pCharModifiers, pVkToWcharTable, pKeyNames, pKeyNamesExt
-    pub virtual_keys: HashMap<VirtualKey, KeyEffect>,
+    // Ordered as in the tables, which matters for reproducing them.
+    pub virtual_keys: IndexMap<VirtualKey, KeyEffect>,
 
     // pDeadKey, pKeyNamesDead
-    pub dead_keys: HashMap<u16, DeadKeyDesc>,
+    // Ordered by first appearance in the dead key table.
+    pub dead_keys: IndexMap<WideChar, DeadKeyDesc>,
 
     // fLocaleFlags
     pub version: u16,
@@ -33,27 +42,39 @@ pub struct KeyboardDesc {
 
     pub type_value: u32,
     pub subtype_value: u32,
+
+    // pCharModifiers ModNumber and wMaxModBits
+    pub shift_states: ShiftStateTable,
+
+    // pVkToWcharTable groups, if known
+    pub char_tables: Option<CharTableLayout>,
+
+    // VERSIONINFO resource and PE headers
+    pub metadata: LayoutMetadata,
+
+    /// The physical keyboard the layout was designed for, if declared.
+    pub geometry: Option<GeometryKind>,
 }
 
 impl KeyboardDesc {
     pub fn new() -> Self {
-        Self {
-            physical_keys: BTreeMap::new(),
-            virtual_keys: HashMap::new(),
-            dead_keys: HashMap::new(),
-            version: 0,
-            supports_altgr: false,
-            supports_shift_lock: false,
-            supports_directionality: false,
-            type_value: 0,
-            subtype_value: 0,
-        }
+        Self::default()
     }
 
     pub const TYPE_GENERIC101: u32 = 4;
     pub const TYPE_JAPAN: u32 = 7;
     pub const TYPE_KOREA: u32 = 8;
     pub const TYPE_UNKNOWN: u32 = 0x51;
+
+    /// Iterates virtual keys by virtual key code rather than in table order.
+    pub fn sorted_virtual_keys(&self) -> impl Iterator<Item = (&VirtualKey, &KeyEffect)> {
+        sorted_by_key(&self.virtual_keys, |virtual_key| *virtual_key)
+    }
+
+    /// Iterates dead keys by character rather than in table order.
+    pub fn sorted_dead_keys(&self) -> impl Iterator<Item = (&WideChar, &DeadKeyDesc)> {
+        sorted_by_key(&self.dead_keys, |char| *char)
+    }
 }
 
 pub struct PhysicalKeyDesc {
@@ -75,7 +96,9 @@ pub struct KeyModifiers {
     pub alt: bool, // KBDALT
     /// The kana modifier.
     pub kana: bool, // KBDKANA
+    /// The right thumb shift (Oyayubi) modifier of NICOLA layouts.
     pub roya: bool, // KBDROYA
+    /// The left thumb shift (Oyayubi) modifier of NICOLA layouts.
     pub loya: bool, // KBDLOYA
     pub unknown0x40: bool,
     pub grpseltap: bool, // KBDGRPSELTAP
@@ -108,6 +131,23 @@ impl KeyModifiers {
         if self.grpseltap { flags |= KBDGRPSELTAP; }
         return flags as u8;
     }
+
+    /// Describes the modifier keys to hold, such as "Shift+AltGr", or an empty string if none.
+    /// Control and Alt read as AltGr for layouts which support it.
+    pub fn describe(&self, supports_altgr: bool) -> String {
+        let altgr = supports_altgr && self.control && self.alt;
+        let mut names = Vec::new();
+        if self.shift { names.push("Shift"); }
+        if altgr { names.push("AltGr"); }
+        if self.control && !altgr { names.push("Ctrl"); }
+        if self.alt && !altgr { names.push("Alt"); }
+        if self.kana { names.push("Kana"); }
+        if self.loya { names.push("Left Thumb"); }
+        if self.roya { names.push("Right Thumb"); }
+        if self.unknown0x40 { names.push("0x40"); }
+        if self.grpseltap { names.push("GrpSelTap"); }
+        names.join("+")
+    }
 }
 
 pub enum KeyEffect {
@@ -118,39 +158,93 @@ pub enum KeyEffect {
 }
 
 pub struct KeyTyping {
-    /// Maps modifiers to the typing effect.
-    pub by_modifiers: HashMap<KeyModifiers, TypingEffect>,
+    /// Maps modifiers to the typing effect, in modification number order.
+    pub by_modifiers: IndexMap<KeyModifiers, TypingEffect>,
 
     /// Interpret caps lock as a shift modifier.
     pub caps_lock_as_shift: bool, // CAPLOK
-    /// Caps lock upper cases the unshifted char.
+    /// Caps lock types the chars of the caps lock layer instead, when no modifiers other than shift are pressed.
     pub caps_lock_as_uppercase: bool, // SGCAPS
+    /// Maps modifiers to the typing effect with caps lock on, from the row following the key's with SGCAPS.
+    pub caps_lock_layer: IndexMap<KeyModifiers, TypingEffect>,
     /// Interpret caps lock as a shift modifier when altgr is pressed.
     pub caps_lock_altgr_as_shift: bool, // CAPLOKALTGR
     /// Kana lock is supported for this key.
     pub kana_support: bool, // KANALOK
     /// grpseltap is supported for this key.
     pub grpseltap_support: bool, // GRPSELTAP
+    /// The index of the VK_TO_WCHARS table holding this key in the char table layout.
+    pub group: Option<usize>,
+}
+
+impl KeyTyping {
+    /// Iterates typing effects by modifier bits rather than in modification number order.
+    pub fn sorted_by_modifiers(&self) -> impl Iterator<Item = (&KeyModifiers, &TypingEffect)> {
+        sorted_by_key(&self.by_modifiers, |modifiers| modifiers.to_bits())
+    }
+
+    /// Iterates typing effects with and without caps lock, such as to find every combination of modifiers in use.
+    pub fn all_effects(&self) -> impl Iterator<Item = (&KeyModifiers, &TypingEffect)> {
+        self.by_modifiers.iter().chain(&self.caps_lock_layer)
+    }
+
+    /// Gets what the key types with the given modifiers, applying its caps lock behaviour if caps lock is on.
+    pub fn effect(&self, modifiers: &KeyModifiers, caps_lock: bool) -> Option<&TypingEffect> {
+        if !caps_lock {
+            return self.by_modifiers.get(modifiers)
+        }
+
+        let altgr = modifiers.control && modifiers.alt;
+        let only_shift = KeyModifiers { shift: false, ..*modifiers }.to_bits() == 0;
+        if self.caps_lock_as_uppercase && only_shift {
+            return self.caps_lock_layer.get(modifiers)
+        }
+
+        let mut modifiers = *modifiers;
+        if (self.caps_lock_as_shift && only_shift) || (self.caps_lock_altgr_as_shift && altgr) {
+            modifiers.shift = !modifiers.shift;
+        }
+        self.by_modifiers.get(&modifiers)
+    }
 }
 
+#[derive(PartialEq, Eq, Clone)]
 pub enum TypingEffect {
     /// A character gets typed.
-    Char(u16),
+    Char(WideChar),
     /// A dead key gets triggered.
-    DeadKey(u16),
+    DeadKey(WideChar),
     /// A ligature gets entered.
-    Ligature(Box<[u16]>)
+    Ligature(WideString)
 }
 
 pub struct DeadKeyDesc {
     /// The human-readable display name of this dead key.
     pub name: Option<String>,
-    /// Maps base chars to a dead key combination.
-    pub combos: HashMap<u16, DeadKeyCombo>
+    /// Maps base chars to a dead key combination, in table order.
+    pub combos: IndexMap<WideChar, DeadKeyCombo>,
+    /// The uFlags bits of combos other than DKF_DEAD, by base char, for combos setting any.
+    /// Windows ignores them, but they are kept so that layouts read back unchanged.
+    pub combo_flags: IndexMap<WideChar, u16>
+}
+
+impl DeadKeyDesc {
+    /// Iterates combos by base character rather than in table order.
+    pub fn sorted_combos(&self) -> impl Iterator<Item = (&WideChar, &DeadKeyCombo)> {
+        sorted_by_key(&self.combos, |char| *char)
+    }
+}
+
+pub enum DeadKeyCombo {
+    /// The dead key + character typed produce a composed character.
+    Char(WideChar),
+    /// The dead key + character typed trigger another dead key,
+    /// whose combos apply to the next character typed. (DKF_DEAD)
+    DeadKey(WideChar)
 }
 
-pub struct DeadKeyCombo {
-    // The character resulting from the dead key + character typed.
-    pub composed_char: u16,
-    pub flags: u16
+fn sorted_by_key<K, V, O: Ord>(map: &IndexMap<K, V>, key: impl Fn(&K) -> O) -> impl Iterator<Item = (&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = map.iter().collect();
+    entries.sort_by_key(|(entry_key, _)| key(entry_key));
+    entries.into_iter()
 }
@@ -0,0 +1,160 @@
+// Identifiers of the keyboard layouts shipped with Windows, as registered under
+// HKLM\SYSTEM\CurrentControlSet\Control\Keyboard Layouts.
+// Where several layouts share a DLL, the first entry is the one reported for it.
+
+pub struct KnownLayout {
+    pub layout_file: &'static str,
+    pub klid: u32,
+    pub locale_name: &'static str,
+    pub layout_text: &'static str,
+}
+
+impl KnownLayout {
+    pub fn locale_id(&self) -> u16 {
+        self.klid as u16
+    }
+
+    pub fn find_by_file(layout_file: &str) -> Option<&'static KnownLayout> {
+        KNOWN_LAYOUTS.iter().find(|layout| layout.layout_file.eq_ignore_ascii_case(layout_file))
+    }
+
+    pub fn find_by_klid(klid: u32) -> Option<&'static KnownLayout> {
+        KNOWN_LAYOUTS.iter().find(|layout| layout.klid == klid)
+    }
+
+    pub fn find_by_locale_id(locale_id: u16) -> Option<&'static KnownLayout> {
+        KNOWN_LAYOUTS.iter().find(|layout| layout.locale_id() == locale_id)
+    }
+
+    pub fn find_by_locale_name(locale_name: &str) -> Option<&'static KnownLayout> {
+        KNOWN_LAYOUTS.iter().find(|layout| layout.locale_name.eq_ignore_ascii_case(locale_name))
+    }
+}
+
+const fn known(layout_file: &'static str, klid: u32, locale_name: &'static str, layout_text: &'static str) -> KnownLayout {
+    KnownLayout { layout_file, klid, locale_name, layout_text }
+}
+
+pub static KNOWN_LAYOUTS: &[KnownLayout] = &[
+    known("KBDA1.DLL", 0x00000401, "ar-SA", "Arabic (101)"),
+    known("KBDBULG.DLL", 0x00000402, "bg-BG", "Bulgarian (Typewriter)"),
+    known("KBDCZ.DLL", 0x00000405, "cs-CZ", "Czech"),
+    known("KBDDA.DLL", 0x00000406, "da-DK", "Danish"),
+    known("KBDGR.DLL", 0x00000407, "de-DE", "German"),
+    known("KBDHE.DLL", 0x00000408, "el-GR", "Greek"),
+    known("KBDUS.DLL", 0x00000409, "en-US", "US"),
+    known("KBDSP.DLL", 0x0000040A, "es-ES", "Spanish"),
+    known("KBDFI.DLL", 0x0000040B, "fi-FI", "Finnish"),
+    known("KBDFR.DLL", 0x0000040C, "fr-FR", "French"),
+    known("KBDHEB.DLL", 0x0000040D, "he-IL", "Hebrew"),
+    known("KBDHU.DLL", 0x0000040E, "hu-HU", "Hungarian"),
+    known("KBDIC.DLL", 0x0000040F, "is-IS", "Icelandic"),
+    known("KBDIT.DLL", 0x00000410, "it-IT", "Italian"),
+    known("KBDJPN.DLL", 0x00000411, "ja-JP", "Japanese"),
+    known("KBDKOR.DLL", 0x00000412, "ko-KR", "Korean"),
+    known("KBDNE.DLL", 0x00000413, "nl-NL", "Dutch"),
+    known("KBDNO.DLL", 0x00000414, "nb-NO", "Norwegian"),
+    known("KBDPL.DLL", 0x00000415, "pl-PL", "Polish (214)"),
+    known("KBDBR.DLL", 0x00000416, "pt-BR", "Portuguese (Brazil ABNT)"),
+    known("KBDRO.DLL", 0x00000418, "ro-RO", "Romanian (Legacy)"),
+    known("KBDRU.DLL", 0x00000419, "ru-RU", "Russian"),
+    known("KBDCR.DLL", 0x0000041A, "hr-HR", "Croatian"),
+    known("KBDSL.DLL", 0x0000041B, "sk-SK", "Slovak"),
+    known("KBDAL.DLL", 0x0000041C, "sq-AL", "Albanian"),
+    known("KBDSW.DLL", 0x0000041D, "sv-SE", "Swedish"),
+    known("KBDTH0.DLL", 0x0000041E, "th-TH", "Thai Kedmanee"),
+    known("KBDTUQ.DLL", 0x0000041F, "tr-TR", "Turkish Q"),
+    known("KBDURDU.DLL", 0x00000420, "ur-PK", "Urdu"),
+    known("KBDUR.DLL", 0x00000422, "uk-UA", "Ukrainian"),
+    known("KBDBLR.DLL", 0x00000423, "be-BY", "Belarusian"),
+    known("KBDEST.DLL", 0x00000425, "et-EE", "Estonian"),
+    known("KBDLV.DLL", 0x00000426, "lv-LV", "Latvian"),
+    known("KBDLT.DLL", 0x00000427, "lt-LT", "Lithuanian IBM"),
+    known("KBDFA.DLL", 0x00000429, "fa-IR", "Persian"),
+    known("KBDVNTC.DLL", 0x0000042A, "vi-VN", "Vietnamese"),
+    known("KBDARME.DLL", 0x0000042B, "hy-AM", "Armenian Eastern (Legacy)"),
+    known("KBDAZEL.DLL", 0x0000042C, "az-Latn-AZ", "Azerbaijani Latin"),
+    known("KBDMAC.DLL", 0x0000042F, "mk-MK", "Macedonian"),
+    known("KBDGEO.DLL", 0x00000437, "ka-GE", "Georgian"),
+    known("KBDFO.DLL", 0x00000438, "fo-FO", "Faeroese"),
+    known("KBDINDEV.DLL", 0x00000439, "hi-IN", "Devanagari - INSCRIPT"),
+    known("KBDMLT47.DLL", 0x0000043A, "mt-MT", "Maltese 47-Key"),
+    known("KBDNO1.DLL", 0x0000043B, "se-NO", "Norwegian with Sami"),
+    known("KBDKAZ.DLL", 0x0000043F, "kk-KZ", "Kazakh"),
+    known("KBDKYR.DLL", 0x00000440, "ky-KG", "Kyrgyz Cyrillic"),
+    known("KBDTURME.DLL", 0x00000442, "tk-TM", "Turkmen"),
+    known("KBDTAT.DLL", 0x00000444, "tt-RU", "Tatar (Legacy)"),
+    known("KBDINBEN.DLL", 0x00000445, "bn-IN", "Bangla (India - Legacy)"),
+    known("KBDINPUN.DLL", 0x00000446, "pa-IN", "Punjabi"),
+    known("KBDINGUJ.DLL", 0x00000447, "gu-IN", "Gujarati"),
+    known("KBDINORI.DLL", 0x00000448, "or-IN", "Odia"),
+    known("KBDINTAM.DLL", 0x00000449, "ta-IN", "Tamil"),
+    known("KBDINTEL.DLL", 0x0000044A, "te-IN", "Telugu"),
+    known("KBDINKAN.DLL", 0x0000044B, "kn-IN", "Kannada"),
+    known("KBDINMAL.DLL", 0x0000044C, "ml-IN", "Malayalam"),
+    known("KBDINASA.DLL", 0x0000044D, "as-IN", "Assamese - INSCRIPT"),
+    known("KBDINMAR.DLL", 0x0000044E, "mr-IN", "Marathi"),
+    known("KBDMON.DLL", 0x00000450, "mn-MN", "Mongolian Cyrillic"),
+    known("KBDTIPRC.DLL", 0x00000451, "bo-CN", "Tibetan (PRC)"),
+    known("KBDUKX.DLL", 0x00000452, "cy-GB", "United Kingdom Extended"),
+    known("KBDKHMR.DLL", 0x00000453, "km-KH", "Khmer"),
+    known("KBDLAO.DLL", 0x00000454, "lo-LA", "Lao"),
+    known("KBDSYR1.DLL", 0x0000045A, "syr-SY", "Syriac"),
+    known("KBDSN1.DLL", 0x0000045B, "si-LK", "Sinhala"),
+    known("KBDNEPR.DLL", 0x00000461, "ne-NP", "Nepali"),
+    known("KBDPASH.DLL", 0x00000463, "ps-AF", "Pashto (Afghanistan)"),
+    known("KBDDIV1.DLL", 0x00000465, "dv-MV", "Divehi Phonetic"),
+    known("KBDHAU.DLL", 0x00000468, "ha-Latn-NG", "Hausa"),
+    known("KBDYBA.DLL", 0x0000046A, "yo-NG", "Yoruba"),
+    known("KBDNSO.DLL", 0x0000046C, "nso-ZA", "Sesotho sa Leboa"),
+    known("KBDBASH.DLL", 0x0000046D, "ba-RU", "Bashkir"),
+    known("KBDGRLND.DLL", 0x0000046F, "kl-GL", "Greenlandic"),
+    known("KBDIBO.DLL", 0x00000470, "ig-NG", "Igbo"),
+    known("KBDGN.DLL", 0x00000474, "gn-PY", "Guarani"),
+    known("KBDHAW.DLL", 0x00000475, "haw-US", "Hawaiian"),
+    known("KBDUGHR.DLL", 0x00000480, "ug-CN", "Uyghur (Legacy)"),
+    known("KBDMAORI.DLL", 0x00000481, "mi-NZ", "Maori"),
+    known("KBDYAK.DLL", 0x00000485, "sah-RU", "Sakha"),
+    known("KBDWOL.DLL", 0x00000488, "wo-SN", "Wolof"),
+    known("KBDSG.DLL", 0x00000807, "de-CH", "Swiss German"),
+    known("KBDUK.DLL", 0x00000809, "en-GB", "United Kingdom"),
+    known("KBDLA.DLL", 0x0000080A, "es-MX", "Latin American"),
+    known("KBDBE.DLL", 0x0000080C, "fr-BE", "Belgian French"),
+    known("KBDPO.DLL", 0x00000816, "pt-PT", "Portuguese"),
+    known("KBDYCL.DLL", 0x0000081A, "sr-Latn-CS", "Serbian (Latin)"),
+    known("KBDAZE.DLL", 0x0000082C, "az-Cyrl-AZ", "Azerbaijani Cyrillic"),
+    known("KBDFI1.DLL", 0x0000083B, "se-SE", "Swedish with Sami"),
+    known("KBDUZB.DLL", 0x00000843, "uz-Cyrl-UZ", "Uzbek Cyrillic"),
+    known("KBDIULAT.DLL", 0x0000085D, "iu-Latn-CA", "Inuktitut - Latin"),
+    known("KBDFC.DLL", 0x00000C0C, "fr-CA", "Canadian French (Legacy)"),
+    known("KBDYCC.DLL", 0x00000C1A, "sr-Cyrl-CS", "Serbian (Cyrillic)"),
+    known("KBDCA.DLL", 0x00001009, "en-CA", "Canadian French"),
+    known("KBDSF.DLL", 0x0000100C, "fr-CH", "Swiss French"),
+    known("KBDBHC.DLL", 0x0000201A, "bs-Cyrl-BA", "Bosnian (Cyrillic)"),
+    known("KBDIR.DLL", 0x00001809, "ga-IE", "Irish"),
+    known("KBDDV.DLL", 0x00010409, "en-US", "United States-Dvorak"),
+    known("KBDES.DLL", 0x0001040A, "es-ES", "Spanish Variation"),
+    known("KBDHU1.DLL", 0x0001040E, "hu-HU", "Hungarian 101-key"),
+    known("KBDIT142.DLL", 0x00010410, "it-IT", "Italian (142)"),
+    known("KBDPL1.DLL", 0x00010415, "pl-PL", "Polish (Programmers)"),
+    known("KBDRU1.DLL", 0x00010419, "ru-RU", "Russian (Typewriter)"),
+    known("KBDSL1.DLL", 0x0001041B, "sk-SK", "Slovak (QWERTY)"),
+    known("KBDTH1.DLL", 0x0001041E, "th-TH", "Thai Pattachote"),
+    known("KBDTUF.DLL", 0x0001041F, "tr-TR", "Turkish F"),
+    known("KBDLV1.DLL", 0x00010426, "lv-LV", "Latvian (QWERTY)"),
+    known("KBDLT1.DLL", 0x00010427, "lt-LT", "Lithuanian"),
+    known("KBDARMW.DLL", 0x0001042B, "hy-AM", "Armenian Western (Legacy)"),
+    known("KBDINHIN.DLL", 0x00010439, "hi-IN", "Hindi Traditional"),
+    known("KBDSYR2.DLL", 0x0001045A, "syr-SY", "Syriac Phonetic"),
+    known("KBDBENE.DLL", 0x0001080C, "fr-BE", "Belgian (Comma)"),
+    known("KBDCAN.DLL", 0x00011009, "en-CA", "Canadian Multilingual Standard"),
+    known("KBDCZ1.DLL", 0x00010405, "cs-CZ", "Czech (QWERTY)"),
+    known("KBDHE220.DLL", 0x00010408, "el-GR", "Greek (220)"),
+    known("KBDA2.DLL", 0x00010401, "ar-SA", "Arabic (102)"),
+    known("KBDUSX.DLL", 0x00020409, "en-US", "United States-International"),
+    known("KBDCZ2.DLL", 0x00020405, "cs-CZ", "Czech Programmers"),
+    known("KBDHE319.DLL", 0x00020408, "el-GR", "Greek (319)"),
+    known("KBDA3.DLL", 0x00020401, "ar-SA", "Arabic (102) AZERTY"),
+    known("KBDDVLH.DLL", 0x00030409, "en-US", "United States-Dvorak for left hand"),
+    known("KBDDVRH.DLL", 0x00040409, "en-US", "United States-Dvorak for right hand"),
+];
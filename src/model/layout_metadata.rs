@@ -0,0 +1,108 @@
+// Describes the layout DLL itself rather than its keyboard tables:
+// what its VERSIONINFO resource and PE headers say about it,
+// and how it is identified when installed on a system.
+
+use crate::model::KnownLayout;
+
+#[derive(Default)]
+pub struct LayoutMetadata {
+    /// The display name of the layout (FileDescription).
+    pub display_name: Option<String>,
+    /// CompanyName
+    pub company_name: Option<String>,
+    /// FileVersion
+    pub file_version: Option<String>,
+    /// ProductVersion
+    pub product_version: Option<String>,
+    /// The language identifier of the version resource strings.
+    pub language: Option<u16>,
+    /// The codepage of the version resource strings.
+    pub codepage: Option<u16>,
+
+    /// The link time of the DLL, in seconds since the Unix epoch.
+    pub timestamp: Option<u32>,
+    /// The machine type the DLL was compiled for (IMAGE_FILE_MACHINE_*).
+    pub machine: Option<u16>,
+
+    /// The keyboard layout identifier, such as 00000409 for US.
+    pub klid: Option<u32>,
+    /// The Windows locale identifier (LCID), such as 0409 for en-US.
+    pub locale_id: Option<u16>,
+    /// The BCP-47 locale name, such as "en-US".
+    pub locale_name: Option<String>,
+    /// The "Layout Text" registry value, such as "US".
+    pub layout_text: Option<String>,
+    /// The "Layout File" registry value, such as "KBDUS.DLL".
+    pub layout_file: Option<String>,
+    /// The "Layout Id" registry value, which distinguishes layout variants sharing a locale.
+    pub layout_id: Option<u16>,
+    /// The string resource the "Layout Display Name" registry value refers to, which layout DLLs
+    /// built with the Microsoft Keyboard Layout Creator have. Installers use the Layout Text without it.
+    pub display_name_resource: Option<String>,
+}
+
+impl LayoutMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of the string resource holding the display name of a layout DLL.
+    pub const DISPLAY_NAME_RESOURCE_ID: u32 = 1000;
+
+    pub const MACHINE_I386: u16 = 0x014C;
+    pub const MACHINE_ARMNT: u16 = 0x01C4;
+    pub const MACHINE_AMD64: u16 = 0x8664;
+    pub const MACHINE_ARM64: u16 = 0xAA64;
+
+    pub fn machine_to_name(machine: u16) -> Option<&'static str> {
+        match machine {
+            Self::MACHINE_I386 => Some("x86"),
+            Self::MACHINE_ARMNT => Some("arm"),
+            Self::MACHINE_AMD64 => Some("x64"),
+            Self::MACHINE_ARM64 => Some("arm64"),
+            _ => None
+        }
+    }
+
+    pub fn machine_from_name(name: &str) -> Option<u16> {
+        match name {
+            "x86" => Some(Self::MACHINE_I386),
+            "arm" => Some(Self::MACHINE_ARMNT),
+            "x64" => Some(Self::MACHINE_AMD64),
+            "arm64" => Some(Self::MACHINE_ARM64),
+            _ => None
+        }
+    }
+
+    /// Fills in the identification fields which are not already set
+    /// from the built-in table of layouts shipped with Windows.
+    pub fn populate_from_known_layout(&mut self, layout_file: &str) {
+        if self.layout_file.is_none() {
+            self.layout_file = Some(layout_file.to_string());
+        }
+
+        let Some(known_layout) = KnownLayout::find_by_file(layout_file) else { return };
+        self.klid.get_or_insert(known_layout.klid);
+        self.locale_id.get_or_insert(known_layout.locale_id());
+        self.locale_name.get_or_insert_with(|| known_layout.locale_name.to_string());
+        self.layout_text.get_or_insert_with(|| known_layout.layout_text.to_string());
+    }
+
+    /// Fills in the locale fields which can be deduced from one another.
+    pub fn complete_locale(&mut self) {
+        if self.locale_id.is_none() {
+            self.locale_id = match (self.klid, &self.locale_name) {
+                (Some(klid), _) => Some(klid as u16),
+                (None, Some(locale_name)) => KnownLayout::find_by_locale_name(locale_name)
+                    .map(|known_layout| known_layout.locale_id()),
+                (None, None) => None
+            };
+        }
+
+        if self.locale_name.is_none() {
+            self.locale_name = self.locale_id
+                .and_then(KnownLayout::find_by_locale_id)
+                .map(|known_layout| known_layout.locale_name.to_string());
+        }
+    }
+}
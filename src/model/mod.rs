@@ -1,7 +1,28 @@
 mod scan_codes;
 mod virtual_keys;
 mod keyboard_layer;
+mod layout_metadata;
+mod known_layouts;
+mod wide_chars;
+mod validation;
+mod geometry;
+mod char_tables;
+mod shift_states;
+mod thumb_shift;
+mod kana_lock;
+mod directional_marks;
+mod special_keys;
+mod key_mapping;
 
 pub use keyboard_layer::*;
+pub use layout_metadata::*;
+pub use known_layouts::*;
+pub use wide_chars::*;
 pub use scan_codes::*;
 pub use virtual_keys::*;
+pub use geometry::*;
+pub use char_tables::*;
+pub use shift_states::*;
+pub use thumb_shift::*;
+pub use directional_marks::*;
+pub use key_mapping::*;
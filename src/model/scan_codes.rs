@@ -5,11 +5,39 @@ pub enum ScanCode {
     Extended1(u8), // E1-escaped, most significant bit unused
 }
 
-#[allow(dead_code)]
 impl ScanCode {
     // See https://learn.microsoft.com/en-us/windows/win32/inputdev/about-keyboard-input
+    // and the "Keyboard Scan Code Specification" for the USB HID usage mappings.
+
+    // Function row
+    pub const ESCAPE: Self = Self::Unescaped(0x01);
+    pub const F1: Self = Self::Unescaped(0x3B);
+    pub const F2: Self = Self::Unescaped(0x3C);
+    pub const F3: Self = Self::Unescaped(0x3D);
+    pub const F4: Self = Self::Unescaped(0x3E);
+    pub const F5: Self = Self::Unescaped(0x3F);
+    pub const F6: Self = Self::Unescaped(0x40);
+    pub const F7: Self = Self::Unescaped(0x41);
+    pub const F8: Self = Self::Unescaped(0x42);
+    pub const F9: Self = Self::Unescaped(0x43);
+    pub const F10: Self = Self::Unescaped(0x44);
+    pub const F11: Self = Self::Unescaped(0x57);
+    pub const F12: Self = Self::Unescaped(0x58);
+    pub const F13: Self = Self::Unescaped(0x64);
+    pub const F14: Self = Self::Unescaped(0x65);
+    pub const F15: Self = Self::Unescaped(0x66);
+    pub const F16: Self = Self::Unescaped(0x67);
+    pub const F17: Self = Self::Unescaped(0x68);
+    pub const F18: Self = Self::Unescaped(0x69);
+    pub const F19: Self = Self::Unescaped(0x6A);
+    pub const F20: Self = Self::Unescaped(0x6B);
+    pub const F21: Self = Self::Unescaped(0x6C);
+    pub const F22: Self = Self::Unescaped(0x6D);
+    pub const F23: Self = Self::Unescaped(0x6E);
+    pub const F24: Self = Self::Unescaped(0x76);
 
     // Numeric row
+    pub const BACKQUOTE: Self = Self::Unescaped(0x29);
     pub const _1: Self = Self::Unescaped(0x02);
     pub const _2: Self = Self::Unescaped(0x03);
     pub const _3: Self = Self::Unescaped(0x04);
@@ -20,8 +48,13 @@ impl ScanCode {
     pub const _8: Self = Self::Unescaped(0x09);
     pub const _9: Self = Self::Unescaped(0x0A);
     pub const _0: Self = Self::Unescaped(0x0B);
+    pub const MINUS: Self = Self::Unescaped(0x0C);
+    pub const EQUAL: Self = Self::Unescaped(0x0D);
+    pub const INTL_YEN: Self = Self::Unescaped(0x7D);
+    pub const BACKSPACE: Self = Self::Unescaped(0x0E);
 
     // Top alphabetic row
+    pub const TAB: Self = Self::Unescaped(0x0F);
     pub const Q: Self = Self::Unescaped(0x10);
     pub const W: Self = Self::Unescaped(0x11);
     pub const E: Self = Self::Unescaped(0x12);
@@ -32,8 +65,12 @@ impl ScanCode {
     pub const I: Self = Self::Unescaped(0x17);
     pub const O: Self = Self::Unescaped(0x18);
     pub const P: Self = Self::Unescaped(0x19);
+    pub const BRACKET_LEFT: Self = Self::Unescaped(0x1A);
+    pub const BRACKET_RIGHT: Self = Self::Unescaped(0x1B);
+    pub const ENTER: Self = Self::Unescaped(0x1C);
 
     // Middle alphabetic row (home row)
+    pub const CAPS_LOCK: Self = Self::Unescaped(0x3A);
     pub const A: Self = Self::Unescaped(0x1E);
     pub const S: Self = Self::Unescaped(0x1F);
     pub const D: Self = Self::Unescaped(0x20);
@@ -43,15 +80,336 @@ impl ScanCode {
     pub const J: Self = Self::Unescaped(0x24);
     pub const K: Self = Self::Unescaped(0x25);
     pub const L: Self = Self::Unescaped(0x26);
+    pub const SEMICOLON: Self = Self::Unescaped(0x27);
+    pub const QUOTE: Self = Self::Unescaped(0x28);
+    pub const BACKSLASH: Self = Self::Unescaped(0x2B);
 
     // Bottom alphabetic row
+    pub const LEFT_SHIFT: Self = Self::Unescaped(0x2A);
+    pub const INTL_BACKSLASH: Self = Self::Unescaped(0x56);
     pub const Z: Self = Self::Unescaped(0x2C);
-    pub const X: Self = Self::Unescaped(0x1D);
+    pub const X: Self = Self::Unescaped(0x2D);
     pub const C: Self = Self::Unescaped(0x2E);
     pub const V: Self = Self::Unescaped(0x2F);
     pub const B: Self = Self::Unescaped(0x30);
     pub const N: Self = Self::Unescaped(0x31);
     pub const M: Self = Self::Unescaped(0x32);
+    pub const COMMA: Self = Self::Unescaped(0x33);
+    pub const PERIOD: Self = Self::Unescaped(0x34);
+    pub const SLASH: Self = Self::Unescaped(0x35);
+    pub const INTL_RO: Self = Self::Unescaped(0x73);
+    pub const RIGHT_SHIFT: Self = Self::Unescaped(0x36);
+
+    // Space row
+    pub const LEFT_CONTROL: Self = Self::Unescaped(0x1D);
+    pub const LEFT_META: Self = Self::Extended0(0x5B);
+    pub const LEFT_ALT: Self = Self::Unescaped(0x38);
+    pub const NON_CONVERT: Self = Self::Unescaped(0x7B);
+    pub const SPACE: Self = Self::Unescaped(0x39);
+    pub const CONVERT: Self = Self::Unescaped(0x79);
+    pub const KANA_MODE: Self = Self::Unescaped(0x70);
+    pub const RIGHT_ALT: Self = Self::Extended0(0x38);
+    pub const RIGHT_META: Self = Self::Extended0(0x5C);
+    pub const CONTEXT_MENU: Self = Self::Extended0(0x5D);
+    pub const RIGHT_CONTROL: Self = Self::Extended0(0x1D);
 
+    // Input method keys
+    pub const LANG1: Self = Self::Unescaped(0x72);
+    pub const LANG2: Self = Self::Unescaped(0x71);
+    pub const LANG3: Self = Self::Unescaped(0x78);
+    pub const LANG4: Self = Self::Unescaped(0x77);
+
+    // Control pad
+    pub const PRINT_SCREEN: Self = Self::Extended0(0x37);
+    pub const SYSRQ: Self = Self::Unescaped(0x54);
+    pub const SCROLL_LOCK: Self = Self::Unescaped(0x46);
     pub const PAUSE: Self = Self::Extended1(0x1D);
-}
\ No newline at end of file
+    pub const CANCEL: Self = Self::Extended0(0x46);
+    pub const INSERT: Self = Self::Extended0(0x52);
+    pub const HOME: Self = Self::Extended0(0x47);
+    pub const PAGE_UP: Self = Self::Extended0(0x49);
+    pub const DELETE: Self = Self::Extended0(0x53);
+    pub const END: Self = Self::Extended0(0x4F);
+    pub const PAGE_DOWN: Self = Self::Extended0(0x51);
+
+    // Arrow pad
+    pub const UP: Self = Self::Extended0(0x48);
+    pub const LEFT: Self = Self::Extended0(0x4B);
+    pub const DOWN: Self = Self::Extended0(0x50);
+    pub const RIGHT: Self = Self::Extended0(0x4D);
+
+    // Numeric keypad
+    pub const NUM_LOCK: Self = Self::Unescaped(0x45);
+    pub const NUMPAD_DIVIDE: Self = Self::Extended0(0x35);
+    pub const NUMPAD_MULTIPLY: Self = Self::Unescaped(0x37);
+    pub const NUMPAD_SUBTRACT: Self = Self::Unescaped(0x4A);
+    pub const NUMPAD_7: Self = Self::Unescaped(0x47);
+    pub const NUMPAD_8: Self = Self::Unescaped(0x48);
+    pub const NUMPAD_9: Self = Self::Unescaped(0x49);
+    pub const NUMPAD_ADD: Self = Self::Unescaped(0x4E);
+    pub const NUMPAD_4: Self = Self::Unescaped(0x4B);
+    pub const NUMPAD_5: Self = Self::Unescaped(0x4C);
+    pub const NUMPAD_6: Self = Self::Unescaped(0x4D);
+    pub const NUMPAD_1: Self = Self::Unescaped(0x4F);
+    pub const NUMPAD_2: Self = Self::Unescaped(0x50);
+    pub const NUMPAD_3: Self = Self::Unescaped(0x51);
+    pub const NUMPAD_ENTER: Self = Self::Extended0(0x1C);
+    pub const NUMPAD_0: Self = Self::Unescaped(0x52);
+    pub const NUMPAD_DECIMAL: Self = Self::Unescaped(0x53);
+    pub const NUMPAD_EQUAL: Self = Self::Unescaped(0x59);
+    pub const NUMPAD_COMMA: Self = Self::Unescaped(0x7E);
+
+    // Media and browser keys
+    pub const MEDIA_PREVIOUS_TRACK: Self = Self::Extended0(0x10);
+    pub const MEDIA_NEXT_TRACK: Self = Self::Extended0(0x19);
+    pub const MEDIA_PLAY_PAUSE: Self = Self::Extended0(0x22);
+    pub const MEDIA_STOP: Self = Self::Extended0(0x24);
+    pub const MEDIA_SELECT: Self = Self::Extended0(0x6D);
+    pub const VOLUME_MUTE: Self = Self::Extended0(0x20);
+    pub const VOLUME_DOWN: Self = Self::Extended0(0x2E);
+    pub const VOLUME_UP: Self = Self::Extended0(0x30);
+    pub const BROWSER_BACK: Self = Self::Extended0(0x6A);
+    pub const BROWSER_FORWARD: Self = Self::Extended0(0x69);
+    pub const BROWSER_REFRESH: Self = Self::Extended0(0x67);
+    pub const BROWSER_STOP: Self = Self::Extended0(0x68);
+    pub const BROWSER_SEARCH: Self = Self::Extended0(0x65);
+    pub const BROWSER_FAVORITES: Self = Self::Extended0(0x66);
+    pub const BROWSER_HOME: Self = Self::Extended0(0x32);
+    pub const LAUNCH_MAIL: Self = Self::Extended0(0x6C);
+    pub const LAUNCH_APP1: Self = Self::Extended0(0x6B);
+    pub const LAUNCH_APP2: Self = Self::Extended0(0x21);
+
+    // System keys
+    pub const POWER: Self = Self::Extended0(0x5E);
+    pub const SLEEP: Self = Self::Extended0(0x5F);
+    pub const WAKE_UP: Self = Self::Extended0(0x63);
+}
+
+/// A USB HID usage, identifying a key as reported by USB keyboards.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct HidUsage {
+    pub page: u16,
+    pub id: u16,
+}
+
+impl HidUsage {
+    pub const GENERIC_DESKTOP: u16 = 0x01;
+    pub const KEYBOARD: u16 = 0x07;
+    pub const CONSUMER: u16 = 0x0C;
+}
+
+pub struct ScanCodeInfo {
+    pub scan_code: ScanCode,
+    /// The canonical name of the key, as defined for KeyboardEvent.code by the W3C UI Events specification.
+    pub name: &'static str,
+    /// The USB HID usage of the key.
+    pub hid_usage: HidUsage,
+    /// The ISO/IEC 9995-1 position of keys in the alphanumeric section, such as "C01" for the A key.
+    /// Positions are those of the ISO keyboard, where the key left of Enter is C12 rather than D13,
+    /// and Backspace is E14 and Right Shift B12 as the Japanese Yen and Ro keys take E13 and B11.
+    pub iso_position: Option<&'static str>,
+}
+
+impl ScanCode {
+    pub fn info(&self) -> Option<&'static ScanCodeInfo> {
+        SCAN_CODES.iter().find(|info| info.scan_code == *self)
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        self.info().map(|info| info.name)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SCAN_CODES.iter().find(|info| info.name == name).map(|info| info.scan_code)
+    }
+
+    pub fn hid_usage(&self) -> Option<HidUsage> {
+        self.info().map(|info| info.hid_usage)
+    }
+
+    pub fn from_hid_usage(hid_usage: HidUsage) -> Option<Self> {
+        // Keyboard Non-US # and ~ is the ISO counterpart of Keyboard \ and |, both map to the same scan code.
+        if hid_usage == (HidUsage { page: HidUsage::KEYBOARD, id: 0x32 }) {
+            return Some(Self::BACKSLASH);
+        }
+
+        SCAN_CODES.iter().find(|info| info.hid_usage == hid_usage).map(|info| info.scan_code)
+    }
+
+    pub fn iso_position(&self) -> Option<&'static str> {
+        self.info().and_then(|info| info.iso_position)
+    }
+
+    pub fn from_iso_position(iso_position: &str) -> Option<Self> {
+        SCAN_CODES.iter().find(|info| info.iso_position == Some(iso_position)).map(|info| info.scan_code)
+    }
+}
+
+impl std::fmt::Debug for ScanCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanCode::Unescaped(code) => write!(f, "{:02X}", code),
+            ScanCode::Extended0(code) => write!(f, "E0 {:02X}", code),
+            ScanCode::Extended1(code) => write!(f, "E1 {:02X}", code),
+        }
+    }
+}
+
+const fn info(scan_code: ScanCode, name: &'static str, hid_usage_page: u16, hid_usage_id: u16, iso_position: Option<&'static str>) -> ScanCodeInfo {
+    ScanCodeInfo { scan_code, name, hid_usage: HidUsage { page: hid_usage_page, id: hid_usage_id }, iso_position }
+}
+
+/// The keys of the Set 1 scan code table, ordered by scan code.
+static SCAN_CODES: &[ScanCodeInfo] = &[
+    info(ScanCode::ESCAPE, "Escape", HidUsage::KEYBOARD, 0x29, None),
+    info(ScanCode::_1, "Digit1", HidUsage::KEYBOARD, 0x1E, Some("E01")),
+    info(ScanCode::_2, "Digit2", HidUsage::KEYBOARD, 0x1F, Some("E02")),
+    info(ScanCode::_3, "Digit3", HidUsage::KEYBOARD, 0x20, Some("E03")),
+    info(ScanCode::_4, "Digit4", HidUsage::KEYBOARD, 0x21, Some("E04")),
+    info(ScanCode::_5, "Digit5", HidUsage::KEYBOARD, 0x22, Some("E05")),
+    info(ScanCode::_6, "Digit6", HidUsage::KEYBOARD, 0x23, Some("E06")),
+    info(ScanCode::_7, "Digit7", HidUsage::KEYBOARD, 0x24, Some("E07")),
+    info(ScanCode::_8, "Digit8", HidUsage::KEYBOARD, 0x25, Some("E08")),
+    info(ScanCode::_9, "Digit9", HidUsage::KEYBOARD, 0x26, Some("E09")),
+    info(ScanCode::_0, "Digit0", HidUsage::KEYBOARD, 0x27, Some("E10")),
+    info(ScanCode::MINUS, "Minus", HidUsage::KEYBOARD, 0x2D, Some("E11")),
+    info(ScanCode::EQUAL, "Equal", HidUsage::KEYBOARD, 0x2E, Some("E12")),
+    info(ScanCode::BACKSPACE, "Backspace", HidUsage::KEYBOARD, 0x2A, Some("E14")),
+    info(ScanCode::TAB, "Tab", HidUsage::KEYBOARD, 0x2B, Some("D00")),
+    info(ScanCode::Q, "KeyQ", HidUsage::KEYBOARD, 0x14, Some("D01")),
+    info(ScanCode::W, "KeyW", HidUsage::KEYBOARD, 0x1A, Some("D02")),
+    info(ScanCode::E, "KeyE", HidUsage::KEYBOARD, 0x08, Some("D03")),
+    info(ScanCode::R, "KeyR", HidUsage::KEYBOARD, 0x15, Some("D04")),
+    info(ScanCode::T, "KeyT", HidUsage::KEYBOARD, 0x17, Some("D05")),
+    info(ScanCode::Y, "KeyY", HidUsage::KEYBOARD, 0x1C, Some("D06")),
+    info(ScanCode::U, "KeyU", HidUsage::KEYBOARD, 0x18, Some("D07")),
+    info(ScanCode::I, "KeyI", HidUsage::KEYBOARD, 0x0C, Some("D08")),
+    info(ScanCode::O, "KeyO", HidUsage::KEYBOARD, 0x12, Some("D09")),
+    info(ScanCode::P, "KeyP", HidUsage::KEYBOARD, 0x13, Some("D10")),
+    info(ScanCode::BRACKET_LEFT, "BracketLeft", HidUsage::KEYBOARD, 0x2F, Some("D11")),
+    info(ScanCode::BRACKET_RIGHT, "BracketRight", HidUsage::KEYBOARD, 0x30, Some("D12")),
+    info(ScanCode::ENTER, "Enter", HidUsage::KEYBOARD, 0x28, Some("C13")),
+    info(ScanCode::LEFT_CONTROL, "ControlLeft", HidUsage::KEYBOARD, 0xE0, Some("A99")),
+    info(ScanCode::A, "KeyA", HidUsage::KEYBOARD, 0x04, Some("C01")),
+    info(ScanCode::S, "KeyS", HidUsage::KEYBOARD, 0x16, Some("C02")),
+    info(ScanCode::D, "KeyD", HidUsage::KEYBOARD, 0x07, Some("C03")),
+    info(ScanCode::F, "KeyF", HidUsage::KEYBOARD, 0x09, Some("C04")),
+    info(ScanCode::G, "KeyG", HidUsage::KEYBOARD, 0x0A, Some("C05")),
+    info(ScanCode::H, "KeyH", HidUsage::KEYBOARD, 0x0B, Some("C06")),
+    info(ScanCode::J, "KeyJ", HidUsage::KEYBOARD, 0x0D, Some("C07")),
+    info(ScanCode::K, "KeyK", HidUsage::KEYBOARD, 0x0E, Some("C08")),
+    info(ScanCode::L, "KeyL", HidUsage::KEYBOARD, 0x0F, Some("C09")),
+    info(ScanCode::SEMICOLON, "Semicolon", HidUsage::KEYBOARD, 0x33, Some("C10")),
+    info(ScanCode::QUOTE, "Quote", HidUsage::KEYBOARD, 0x34, Some("C11")),
+    info(ScanCode::BACKQUOTE, "Backquote", HidUsage::KEYBOARD, 0x35, Some("E00")),
+    info(ScanCode::LEFT_SHIFT, "ShiftLeft", HidUsage::KEYBOARD, 0xE1, Some("B99")),
+    info(ScanCode::BACKSLASH, "Backslash", HidUsage::KEYBOARD, 0x31, Some("C12")),
+    info(ScanCode::Z, "KeyZ", HidUsage::KEYBOARD, 0x1D, Some("B01")),
+    info(ScanCode::X, "KeyX", HidUsage::KEYBOARD, 0x1B, Some("B02")),
+    info(ScanCode::C, "KeyC", HidUsage::KEYBOARD, 0x06, Some("B03")),
+    info(ScanCode::V, "KeyV", HidUsage::KEYBOARD, 0x19, Some("B04")),
+    info(ScanCode::B, "KeyB", HidUsage::KEYBOARD, 0x05, Some("B05")),
+    info(ScanCode::N, "KeyN", HidUsage::KEYBOARD, 0x11, Some("B06")),
+    info(ScanCode::M, "KeyM", HidUsage::KEYBOARD, 0x10, Some("B07")),
+    info(ScanCode::COMMA, "Comma", HidUsage::KEYBOARD, 0x36, Some("B08")),
+    info(ScanCode::PERIOD, "Period", HidUsage::KEYBOARD, 0x37, Some("B09")),
+    info(ScanCode::SLASH, "Slash", HidUsage::KEYBOARD, 0x38, Some("B10")),
+    info(ScanCode::RIGHT_SHIFT, "ShiftRight", HidUsage::KEYBOARD, 0xE5, Some("B12")),
+    info(ScanCode::NUMPAD_MULTIPLY, "NumpadMultiply", HidUsage::KEYBOARD, 0x55, None),
+    info(ScanCode::LEFT_ALT, "AltLeft", HidUsage::KEYBOARD, 0xE2, Some("A01")),
+    info(ScanCode::SPACE, "Space", HidUsage::KEYBOARD, 0x2C, Some("A03")),
+    info(ScanCode::CAPS_LOCK, "CapsLock", HidUsage::KEYBOARD, 0x39, Some("C00")),
+    info(ScanCode::F1, "F1", HidUsage::KEYBOARD, 0x3A, None),
+    info(ScanCode::F2, "F2", HidUsage::KEYBOARD, 0x3B, None),
+    info(ScanCode::F3, "F3", HidUsage::KEYBOARD, 0x3C, None),
+    info(ScanCode::F4, "F4", HidUsage::KEYBOARD, 0x3D, None),
+    info(ScanCode::F5, "F5", HidUsage::KEYBOARD, 0x3E, None),
+    info(ScanCode::F6, "F6", HidUsage::KEYBOARD, 0x3F, None),
+    info(ScanCode::F7, "F7", HidUsage::KEYBOARD, 0x40, None),
+    info(ScanCode::F8, "F8", HidUsage::KEYBOARD, 0x41, None),
+    info(ScanCode::F9, "F9", HidUsage::KEYBOARD, 0x42, None),
+    info(ScanCode::F10, "F10", HidUsage::KEYBOARD, 0x43, None),
+    info(ScanCode::NUM_LOCK, "NumLock", HidUsage::KEYBOARD, 0x53, None),
+    info(ScanCode::SCROLL_LOCK, "ScrollLock", HidUsage::KEYBOARD, 0x47, None),
+    info(ScanCode::NUMPAD_7, "Numpad7", HidUsage::KEYBOARD, 0x5F, None),
+    info(ScanCode::NUMPAD_8, "Numpad8", HidUsage::KEYBOARD, 0x60, None),
+    info(ScanCode::NUMPAD_9, "Numpad9", HidUsage::KEYBOARD, 0x61, None),
+    info(ScanCode::NUMPAD_SUBTRACT, "NumpadSubtract", HidUsage::KEYBOARD, 0x56, None),
+    info(ScanCode::NUMPAD_4, "Numpad4", HidUsage::KEYBOARD, 0x5C, None),
+    info(ScanCode::NUMPAD_5, "Numpad5", HidUsage::KEYBOARD, 0x5D, None),
+    info(ScanCode::NUMPAD_6, "Numpad6", HidUsage::KEYBOARD, 0x5E, None),
+    info(ScanCode::NUMPAD_ADD, "NumpadAdd", HidUsage::KEYBOARD, 0x57, None),
+    info(ScanCode::NUMPAD_1, "Numpad1", HidUsage::KEYBOARD, 0x59, None),
+    info(ScanCode::NUMPAD_2, "Numpad2", HidUsage::KEYBOARD, 0x5A, None),
+    info(ScanCode::NUMPAD_3, "Numpad3", HidUsage::KEYBOARD, 0x5B, None),
+    info(ScanCode::NUMPAD_0, "Numpad0", HidUsage::KEYBOARD, 0x62, None),
+    info(ScanCode::NUMPAD_DECIMAL, "NumpadDecimal", HidUsage::KEYBOARD, 0x63, None),
+    info(ScanCode::SYSRQ, "SysRq", HidUsage::KEYBOARD, 0x9A, None),
+    info(ScanCode::INTL_BACKSLASH, "IntlBackslash", HidUsage::KEYBOARD, 0x64, Some("B00")),
+    info(ScanCode::F11, "F11", HidUsage::KEYBOARD, 0x44, None),
+    info(ScanCode::F12, "F12", HidUsage::KEYBOARD, 0x45, None),
+    info(ScanCode::NUMPAD_EQUAL, "NumpadEqual", HidUsage::KEYBOARD, 0x67, None),
+    info(ScanCode::F13, "F13", HidUsage::KEYBOARD, 0x68, None),
+    info(ScanCode::F14, "F14", HidUsage::KEYBOARD, 0x69, None),
+    info(ScanCode::F15, "F15", HidUsage::KEYBOARD, 0x6A, None),
+    info(ScanCode::F16, "F16", HidUsage::KEYBOARD, 0x6B, None),
+    info(ScanCode::F17, "F17", HidUsage::KEYBOARD, 0x6C, None),
+    info(ScanCode::F18, "F18", HidUsage::KEYBOARD, 0x6D, None),
+    info(ScanCode::F19, "F19", HidUsage::KEYBOARD, 0x6E, None),
+    info(ScanCode::F20, "F20", HidUsage::KEYBOARD, 0x6F, None),
+    info(ScanCode::F21, "F21", HidUsage::KEYBOARD, 0x70, None),
+    info(ScanCode::F22, "F22", HidUsage::KEYBOARD, 0x71, None),
+    info(ScanCode::F23, "F23", HidUsage::KEYBOARD, 0x72, None),
+    info(ScanCode::KANA_MODE, "KanaMode", HidUsage::KEYBOARD, 0x88, Some("A08")),
+    info(ScanCode::LANG2, "Lang2", HidUsage::KEYBOARD, 0x91, None),
+    info(ScanCode::LANG1, "Lang1", HidUsage::KEYBOARD, 0x90, None),
+    info(ScanCode::INTL_RO, "IntlRo", HidUsage::KEYBOARD, 0x87, Some("B11")),
+    info(ScanCode::F24, "F24", HidUsage::KEYBOARD, 0x73, None),
+    info(ScanCode::LANG4, "Lang4", HidUsage::KEYBOARD, 0x93, None),
+    info(ScanCode::LANG3, "Lang3", HidUsage::KEYBOARD, 0x92, None),
+    info(ScanCode::CONVERT, "Convert", HidUsage::KEYBOARD, 0x8A, Some("A07")),
+    info(ScanCode::NON_CONVERT, "NonConvert", HidUsage::KEYBOARD, 0x8B, Some("A02")),
+    info(ScanCode::INTL_YEN, "IntlYen", HidUsage::KEYBOARD, 0x89, Some("E13")),
+    info(ScanCode::NUMPAD_COMMA, "NumpadComma", HidUsage::KEYBOARD, 0x85, None),
+    info(ScanCode::MEDIA_PREVIOUS_TRACK, "MediaTrackPrevious", HidUsage::CONSUMER, 0xB6, None),
+    info(ScanCode::MEDIA_NEXT_TRACK, "MediaTrackNext", HidUsage::CONSUMER, 0xB5, None),
+    info(ScanCode::NUMPAD_ENTER, "NumpadEnter", HidUsage::KEYBOARD, 0x58, None),
+    info(ScanCode::RIGHT_CONTROL, "ControlRight", HidUsage::KEYBOARD, 0xE4, Some("A12")),
+    info(ScanCode::VOLUME_MUTE, "AudioVolumeMute", HidUsage::CONSUMER, 0xE2, None),
+    info(ScanCode::LAUNCH_APP2, "LaunchApp2", HidUsage::CONSUMER, 0x192, None),
+    info(ScanCode::MEDIA_PLAY_PAUSE, "MediaPlayPause", HidUsage::CONSUMER, 0xCD, None),
+    info(ScanCode::MEDIA_STOP, "MediaStop", HidUsage::CONSUMER, 0xB7, None),
+    info(ScanCode::VOLUME_DOWN, "AudioVolumeDown", HidUsage::CONSUMER, 0xEA, None),
+    info(ScanCode::VOLUME_UP, "AudioVolumeUp", HidUsage::CONSUMER, 0xE9, None),
+    info(ScanCode::BROWSER_HOME, "BrowserHome", HidUsage::CONSUMER, 0x223, None),
+    info(ScanCode::NUMPAD_DIVIDE, "NumpadDivide", HidUsage::KEYBOARD, 0x54, None),
+    info(ScanCode::PRINT_SCREEN, "PrintScreen", HidUsage::KEYBOARD, 0x46, None),
+    info(ScanCode::RIGHT_ALT, "AltRight", HidUsage::KEYBOARD, 0xE6, Some("A09")),
+    info(ScanCode::CANCEL, "Cancel", HidUsage::KEYBOARD, 0x9B, None),
+    info(ScanCode::HOME, "Home", HidUsage::KEYBOARD, 0x4A, None),
+    info(ScanCode::UP, "ArrowUp", HidUsage::KEYBOARD, 0x52, None),
+    info(ScanCode::PAGE_UP, "PageUp", HidUsage::KEYBOARD, 0x4B, None),
+    info(ScanCode::LEFT, "ArrowLeft", HidUsage::KEYBOARD, 0x50, None),
+    info(ScanCode::RIGHT, "ArrowRight", HidUsage::KEYBOARD, 0x4F, None),
+    info(ScanCode::END, "End", HidUsage::KEYBOARD, 0x4D, None),
+    info(ScanCode::DOWN, "ArrowDown", HidUsage::KEYBOARD, 0x51, None),
+    info(ScanCode::PAGE_DOWN, "PageDown", HidUsage::KEYBOARD, 0x4E, None),
+    info(ScanCode::INSERT, "Insert", HidUsage::KEYBOARD, 0x49, None),
+    info(ScanCode::DELETE, "Delete", HidUsage::KEYBOARD, 0x4C, None),
+    info(ScanCode::LEFT_META, "MetaLeft", HidUsage::KEYBOARD, 0xE3, Some("A00")),
+    info(ScanCode::RIGHT_META, "MetaRight", HidUsage::KEYBOARD, 0xE7, Some("A10")),
+    info(ScanCode::CONTEXT_MENU, "ContextMenu", HidUsage::KEYBOARD, 0x65, Some("A11")),
+    info(ScanCode::POWER, "Power", HidUsage::GENERIC_DESKTOP, 0x81, None),
+    info(ScanCode::SLEEP, "Sleep", HidUsage::GENERIC_DESKTOP, 0x82, None),
+    info(ScanCode::WAKE_UP, "WakeUp", HidUsage::GENERIC_DESKTOP, 0x83, None),
+    info(ScanCode::BROWSER_SEARCH, "BrowserSearch", HidUsage::CONSUMER, 0x221, None),
+    info(ScanCode::BROWSER_FAVORITES, "BrowserFavorites", HidUsage::CONSUMER, 0x22A, None),
+    info(ScanCode::BROWSER_REFRESH, "BrowserRefresh", HidUsage::CONSUMER, 0x227, None),
+    info(ScanCode::BROWSER_STOP, "BrowserStop", HidUsage::CONSUMER, 0x226, None),
+    info(ScanCode::BROWSER_FORWARD, "BrowserForward", HidUsage::CONSUMER, 0x225, None),
+    info(ScanCode::BROWSER_BACK, "BrowserBack", HidUsage::CONSUMER, 0x224, None),
+    info(ScanCode::LAUNCH_APP1, "LaunchApp1", HidUsage::CONSUMER, 0x194, None),
+    info(ScanCode::LAUNCH_MAIL, "LaunchMail", HidUsage::CONSUMER, 0x18A, None),
+    info(ScanCode::MEDIA_SELECT, "MediaSelect", HidUsage::CONSUMER, 0x183, None),
+    info(ScanCode::PAUSE, "Pause", HidUsage::KEYBOARD, 0x48, None),
+];
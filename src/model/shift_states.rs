@@ -0,0 +1,115 @@
+// Represents the MODIFIERS table, which maps combinations of modifier bits
+// to the character columns of the VK_TO_WCHARS tables.
+
+use crate::model::keyboard_layer::*;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ShiftStateTable {
+    /// The column for each combination of modifier bits up to wMaxModBits,
+    /// or None for combinations explicitly marked SHFT_INVALID.
+    columns: Vec<Option<u8>>, // ModNumber
+}
+
+impl ShiftStateTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_columns(columns: Vec<Option<u8>>) -> Self {
+        Self { columns }
+    }
+
+    /// Whether the table is unknown, as for layouts not read from a DLL.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// The columns indexed by modifier bits, as in the ModNumber array.
+    pub fn columns(&self) -> &[Option<u8>] {
+        &self.columns
+    }
+
+    /// The highest combination of modifier bits the table covers.
+    pub fn max_mod_bits(&self) -> u16 { // wMaxModBits
+        self.columns.len().saturating_sub(1) as u16
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.iter().flatten().map(|column| *column as usize + 1).max().unwrap_or(0)
+    }
+
+    /// Gets the column of characters typed with the given modifiers, if the state is valid.
+    pub fn column(&self, modifiers: &KeyModifiers) -> Option<u8> {
+        self.columns.get(modifiers.to_bits() as usize).copied().flatten()
+    }
+
+    /// Gets every combination of modifiers whose characters are in a column, in modifier bits order,
+    /// as several combinations may share a column.
+    pub fn modifiers(&self, column: u8) -> Vec<KeyModifiers> {
+        self.shift_states()
+            .filter(|(_, entry)| *entry == column)
+            .map(|(modifiers, _)| modifiers)
+            .collect()
+    }
+
+    /// Whether typing with the given modifiers produces nothing, either because the state
+    /// is marked SHFT_INVALID or because it is beyond wMaxModBits.
+    pub fn is_invalid(&self, modifiers: &KeyModifiers) -> bool {
+        self.column(modifiers).is_none()
+    }
+
+    /// Iterates the valid shift states with their column, in modifier bits order.
+    pub fn shift_states(&self) -> impl Iterator<Item = (KeyModifiers, u8)> + '_ {
+        self.columns.iter().enumerate()
+            .filter_map(|(mod_bits, column)| column.map(|column| (KeyModifiers::from_bits(mod_bits as u8), column)))
+    }
+
+    /// Sets the column of a combination of modifiers, growing the table as needed.
+    pub fn set_column(&mut self, modifiers: &KeyModifiers, column: Option<u8>) {
+        let mod_bits = modifiers.to_bits() as usize;
+        if mod_bits >= self.columns.len() {
+            self.columns.resize(mod_bits + 1, None);
+        }
+        self.columns[mod_bits] = column;
+    }
+}
+
+impl KeyboardDesc {
+    /// Computes a shift state table with one column per combination of modifiers typing something,
+    /// in modifier bits order, covering every combination the modifier keys can produce.
+    pub fn optimal_shift_state_table(&self) -> ShiftStateTable {
+        let mut max_mod_bits = 0u8;
+        let mut used_mod_bits = [false; 256];
+        for key_effect in self.virtual_keys.values() {
+            match key_effect {
+                KeyEffect::Modifier(key_modifiers) => max_mod_bits |= key_modifiers.to_bits(),
+                KeyEffect::Typing(key_typing) => {
+                    for (modifiers, _) in key_typing.all_effects() {
+                        used_mod_bits[modifiers.to_bits() as usize] = true;
+                        max_mod_bits = max_mod_bits.max(modifiers.to_bits());
+                    }
+                }
+            }
+        }
+
+        let mut column_count = 0u8;
+        let columns = used_mod_bits[..=max_mod_bits as usize].iter()
+            .map(|is_used| if *is_used {
+                column_count += 1;
+                Some(column_count - 1)
+            } else {
+                None
+            })
+            .collect();
+        ShiftStateTable::from_columns(columns)
+    }
+
+    /// Checks whether the shift state table has a column for every combination of modifiers typing something.
+    pub fn fits_shift_state_table(&self) -> bool {
+        self.virtual_keys.values().all(|key_effect| match key_effect {
+            KeyEffect::Typing(key_typing) => key_typing.all_effects()
+                .all(|(modifiers, _)| !self.shift_states.is_invalid(modifiers)),
+            KeyEffect::Modifier(_) => true
+        })
+    }
+}
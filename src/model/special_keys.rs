@@ -0,0 +1,50 @@
+// Resolves the virtual key a scan code produces, applying the flags of its scan code table
+// entry (KBDNUMPAD, KBDMULTIVK) as Windows does before the key reaches applications.
+
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use crate::model::keyboard_layer::*;
+use crate::model::scan_codes::*;
+use crate::model::virtual_keys::*;
+
+/// The navigation keys in the table entries of keypad keys (KBDNUMPAD), with the keys they become with Num Lock on.
+pub(crate) const NUMPAD_KEYS: [(VIRTUAL_KEY, VIRTUAL_KEY); 11] = [
+    (VK_INSERT, VK_NUMPAD0), (VK_END, VK_NUMPAD1), (VK_DOWN, VK_NUMPAD2), (VK_NEXT, VK_NUMPAD3),
+    (VK_LEFT, VK_NUMPAD4), (VK_CLEAR, VK_NUMPAD5), (VK_RIGHT, VK_NUMPAD6), (VK_HOME, VK_NUMPAD7),
+    (VK_UP, VK_NUMPAD8), (VK_PRIOR, VK_NUMPAD9), (VK_DELETE, VK_DECIMAL),
+];
+
+/// The keys whose virtual key changes with Ctrl held (KBDMULTIVK), with the key they become.
+const CONTROL_KEYS: [(VIRTUAL_KEY, VIRTUAL_KEY); 2] = [(VK_NUMLOCK, VK_PAUSE), (VK_SCROLL, VK_CANCEL)];
+
+impl KeyboardDesc {
+    /// Gets the virtual key a scan code produces with the given modifiers held and Num Lock on or off,
+    /// if the scan code is mapped.
+    pub fn resolve_virtual_key(&self, scan_code: ScanCode, modifiers: &KeyModifiers, num_lock: bool) -> Option<VirtualKey> {
+        // Keyboards send the SysRq scan code rather than the Print Screen one while Alt is held.
+        let scan_code = match scan_code {
+            ScanCode::PRINT_SCREEN if modifiers.alt && self.physical_keys.contains_key(&ScanCode::SYSRQ) => ScanCode::SYSRQ,
+            _ => scan_code
+        };
+        let physical_key = self.physical_keys.get(&scan_code)?;
+        if physical_key.virtual_key == VirtualKey::NONE {
+            return None
+        }
+
+        let code = physical_key.virtual_key.code as VIRTUAL_KEY;
+        let flags = &physical_key.virtual_key_flags;
+        let resolved = if flags.numpad && !flags.extended {
+            // Shift temporarily reverts keypad keys to navigation with Num Lock on.
+            match NUMPAD_KEYS.iter().find(|(navigation, _)| *navigation == code) {
+                Some((_, numpad)) if num_lock && !modifiers.shift => *numpad,
+                _ => code
+            }
+        }
+        else if flags.multi_vk && modifiers.control {
+            CONTROL_KEYS.iter().find(|(key, _)| *key == code).map_or(code, |(_, control_key)| *control_key)
+        }
+        else {
+            code
+        };
+        Some(VirtualKey { code: resolved as u8 })
+    }
+}
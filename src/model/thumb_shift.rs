@@ -0,0 +1,41 @@
+// Identifies the thumb shift (Oyayubi) keys of Japanese NICOLA layouts, whose left and right
+// thumb keys are modifiers (KBDLOYA and KBDROYA) pressed together with character keys.
+
+use crate::model::keyboard_layer::*;
+use crate::model::virtual_keys::*;
+
+/// The virtual keys bound to each thumb shift modifier in the modifier table.
+pub struct ThumbShiftKeys {
+    pub left: Vec<VirtualKey>,
+    pub right: Vec<VirtualKey>,
+}
+
+impl ThumbShiftKeys {
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+
+    pub fn contains(&self, virtual_key: &VirtualKey) -> bool {
+        self.left.contains(virtual_key) || self.right.contains(virtual_key)
+    }
+}
+
+impl KeyboardDesc {
+    pub fn thumb_shift_keys(&self) -> ThumbShiftKeys {
+        let mut keys = ThumbShiftKeys { left: Vec::new(), right: Vec::new() };
+        for (virtual_key, key_effect) in self.sorted_virtual_keys() {
+            let KeyEffect::Modifier(key_modifiers) = key_effect else { continue };
+            if key_modifiers.loya { keys.left.push(*virtual_key); }
+            if key_modifiers.roya { keys.right.push(*virtual_key); }
+        }
+        keys
+    }
+
+    /// Whether any key types something on a thumb shift layer.
+    pub fn uses_thumb_shift(&self) -> bool {
+        self.virtual_keys.values().any(|key_effect| match key_effect {
+            KeyEffect::Typing(key_typing) => key_typing.all_effects().any(|(modifiers, _)| modifiers.loya || modifiers.roya),
+            KeyEffect::Modifier(_) => false
+        })
+    }
+}
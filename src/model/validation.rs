@@ -0,0 +1,150 @@
+// Checks for inconsistencies in a keyboard layout which
+// Windows would tolerate but which are most likely mistakes.
+
+use std::collections::HashSet;
+use crate::model::keyboard_layer::*;
+use crate::model::wide_chars::*;
+use crate::model::virtual_keys::*;
+
+pub enum ValidationIssue {
+    /// A key or combo triggers a dead key which has no combos.
+    MissingDeadKey(WideChar),
+    /// Dead key combos chain back to a dead key already in the chain,
+    /// so the sequence never produces a character.
+    DeadKeyCycle(Vec<WideChar>),
+    /// Keys type on a thumb shift layer, left or right, which no modifier key enables.
+    MissingThumbShiftKey { right: bool },
+    /// A thumb shift modifier key cannot be pressed, as no scan code maps to it.
+    UnmappedThumbShiftKey(VirtualKey),
+    /// Keys type with the kana modifier, but no modifier key toggles kana lock.
+    MissingKanaToggleKey,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssue::MissingDeadKey(dead_key) =>
+                write!(f, "Dead key {:?} is triggered but has no combos.", dead_key),
+            ValidationIssue::DeadKeyCycle(chain) => {
+                let chain: Vec<String> = chain.iter().map(|dead_key| format!("{:?}", dead_key)).collect();
+                write!(f, "Dead key combos form a cycle: {}.", chain.join(" -> "))
+            },
+            ValidationIssue::MissingThumbShiftKey { right } =>
+                write!(f, "Keys type on the {} thumb shift layer but no modifier key enables it.", if *right { "right" } else { "left" }),
+            ValidationIssue::UnmappedThumbShiftKey(virtual_key) =>
+                write!(f, "Thumb shift key {:?} is not mapped to any scan code.", virtual_key),
+            ValidationIssue::MissingKanaToggleKey =>
+                write!(f, "Keys type with the kana modifier but no modifier key toggles kana lock."),
+        }
+    }
+}
+
+impl KeyboardDesc {
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        self.validate_dead_key_references(&mut issues);
+        self.validate_dead_key_chains(&mut issues);
+        self.validate_thumb_shift_keys(&mut issues);
+        self.validate_kana_lock(&mut issues);
+        issues
+    }
+
+    fn validate_dead_key_references(&self, issues: &mut Vec<ValidationIssue>) {
+        let mut referenced = Vec::new();
+        for key_effect in self.virtual_keys.values() {
+            let KeyEffect::Typing(key_typing) = key_effect else { continue };
+            for (_, typing_effect) in key_typing.all_effects() {
+                if let TypingEffect::DeadKey(dead_key) = typing_effect {
+                    referenced.push(*dead_key);
+                }
+            }
+        }
+
+        for dead_key_desc in self.dead_keys.values() {
+            for combo in dead_key_desc.combos.values() {
+                if let DeadKeyCombo::DeadKey(dead_key) = combo {
+                    referenced.push(*dead_key);
+                }
+            }
+        }
+
+        referenced.sort();
+        referenced.dedup();
+        for dead_key in referenced {
+            if !self.dead_keys.contains_key(&dead_key) {
+                issues.push(ValidationIssue::MissingDeadKey(dead_key));
+            }
+        }
+    }
+
+    fn validate_dead_key_chains(&self, issues: &mut Vec<ValidationIssue>) {
+        let mut dead_keys: Vec<WideChar> = self.dead_keys.keys().copied().collect();
+        dead_keys.sort();
+
+        // Three-colour depth-first search: dead keys in `visiting` are on the current chain,
+        // so reaching one again closes a cycle. Each back edge is reported once.
+        let mut visiting = Vec::new();
+        let mut visited = HashSet::new();
+        for start in dead_keys {
+            self.find_dead_key_cycles(start, &mut visiting, &mut visited, issues);
+        }
+    }
+
+    fn validate_thumb_shift_keys(&self, issues: &mut Vec<ValidationIssue>) {
+        let thumb_shift_keys = self.thumb_shift_keys();
+        let (mut uses_left, mut uses_right) = (false, false);
+        for key_effect in self.virtual_keys.values() {
+            let KeyEffect::Typing(key_typing) = key_effect else { continue };
+            for (modifiers, _) in key_typing.all_effects() {
+                uses_left |= modifiers.loya;
+                uses_right |= modifiers.roya;
+            }
+        }
+        if uses_left && thumb_shift_keys.left.is_empty() {
+            issues.push(ValidationIssue::MissingThumbShiftKey { right: false });
+        }
+        if uses_right && thumb_shift_keys.right.is_empty() {
+            issues.push(ValidationIssue::MissingThumbShiftKey { right: true });
+        }
+
+        // Generic modifier keys are reached through their left and right variants, but thumb shift keys are not.
+        for virtual_key in thumb_shift_keys.left.iter().chain(&thumb_shift_keys.right) {
+            if !self.physical_keys.values().any(|physical_key| physical_key.virtual_key == *virtual_key) {
+                issues.push(ValidationIssue::UnmappedThumbShiftKey(*virtual_key));
+            }
+        }
+    }
+
+    fn validate_kana_lock(&self, issues: &mut Vec<ValidationIssue>) {
+        if self.uses_kana() && self.kana_toggle_keys().is_empty() {
+            issues.push(ValidationIssue::MissingKanaToggleKey);
+        }
+    }
+
+    fn find_dead_key_cycles(&self, current: WideChar, visiting: &mut Vec<WideChar>, visited: &mut HashSet<WideChar>, issues: &mut Vec<ValidationIssue>) {
+        if !visited.insert(current) { return }
+        let Some(dead_key_desc) = self.dead_keys.get(&current) else { return };
+
+        let mut next_dead_keys: Vec<WideChar> = dead_key_desc.combos.values()
+            .filter_map(|combo| match combo {
+                DeadKeyCombo::DeadKey(next) => Some(*next),
+                DeadKeyCombo::Char(_) => None
+            })
+            .collect();
+        next_dead_keys.sort();
+        next_dead_keys.dedup();
+
+        visiting.push(current);
+        for next in next_dead_keys {
+            if let Some(position) = visiting.iter().position(|dead_key| *dead_key == next) {
+                let mut cycle = visiting[position..].to_vec();
+                cycle.push(next);
+                issues.push(ValidationIssue::DeadKeyCycle(cycle));
+            }
+            else {
+                self.find_dead_key_cycles(next, visiting, visited, issues);
+            }
+        }
+        visiting.pop();
+    }
+}
@@ -25,17 +25,25 @@ impl std::fmt::Debug for VirtualKey {
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub struct VirtualKeyFlags {
+    /// The key sends an E0-escaped scan code, or is treated as if it did.
     pub extended: bool, // KBDEXT
+    /// The virtual key depends on the modifiers held, such as Pause for Ctrl+Num Lock.
     pub multi_vk: bool, // KBDMULTIVK
+    /// The key gets special processing before translation.
     pub special: bool, // KBDSPECIAL
+    /// The key is a keypad key, typing digits instead of navigating with Num Lock on.
     pub numpad: bool, // KBDNUMPAD
     pub unicode: bool, // KBDUNICODE
     pub injected_vk: bool, // KBDINJECTEDVK
     pub mapped_vk: bool, // KBDMAPPEDVK
+    /// Marks key releases in input events, so is not expected in layout tables.
     pub r#break: bool, // KBDBREAK
 }
 
 impl VirtualKeyFlags {
+    /// The kbd.h names of the flags, by bit.
+    pub const NAMES: [&'static str; 8] = ["KBDEXT", "KBDMULTIVK", "KBDSPECIAL", "KBDNUMPAD", "KBDUNICODE", "KBDINJECTEDVK", "KBDMAPPEDVK", "KBDBREAK"];
+
     pub fn from_bits(flags: u8) -> Self {
         let flags = flags as u32;
         Self {
@@ -49,15 +57,30 @@ impl VirtualKeyFlags {
             r#break: (flags & 0x80) != 0,
         }
     }
+
+    pub fn to_bits(&self) -> u8 {
+        let mut flags = 0;
+        if self.extended { flags |= 0x01; }
+        if self.multi_vk { flags |= 0x02; }
+        if self.special { flags |= 0x04; }
+        if self.numpad { flags |= 0x08; }
+        if self.unicode { flags |= 0x10; }
+        if self.injected_vk { flags |= 0x20; }
+        if self.mapped_vk { flags |= 0x40; }
+        if self.r#break { flags |= 0x80; }
+        flags
+    }
 }
 
-#[allow(dead_code)]
 impl VirtualKey {
     // See https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
 
+    pub const BACKSPACE: Self = Self { code: 0x08 };
     pub const SHIFT: Self = Self { code: 0x10 };
     pub const CONTROL: Self = Self { code: 0x11 };
     pub const ALT: Self = Self { code: 0x12 };
+    pub const CAPS_LOCK: Self = Self { code: 0x14 };
+    pub const KANA: Self = Self { code: 0x15 };
 
     pub const LEFT_WIN: Self = Self { code: 0x5B };
     pub const RIGHT_WIN: Self = Self { code: 0x5C };
@@ -75,6 +98,12 @@ impl VirtualKey {
     pub const F11: Self = Self { code: 0x7A };
     pub const F12: Self = Self { code: 0x7B };
 
+    pub const NUM_LOCK: Self = Self { code: 0x90 };
+
+    // Thumb shift keys of Fujitsu OASYS keyboards
+    pub const OEM_FJ_LOYA: Self = Self { code: 0x95 };
+    pub const OEM_FJ_ROYA: Self = Self { code: 0x96 };
+
     pub const LEFT_SHIFT: Self = Self { code: 0xA0 };
     pub const RIGHT_SHIFT: Self = Self { code: 0xA1 };
     pub const LEFT_CONTROL: Self = Self { code: 0xA2 };
@@ -85,188 +114,306 @@ impl VirtualKey {
     pub const NONE: Self = Self { code: 0xFF };
 }
 
-use bimap::BiHashMap;
-use lazy_static::lazy_static;
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum VirtualKeyCategory {
+    Mouse,
+    /// Keys typing characters, including editing keys such as Backspace.
+    Typing,
+    /// Modifier and lock keys.
+    Modifier,
+    Navigation,
+    Function,
+    /// Input method editor keys, such as those of Japanese and Korean keyboards.
+    Ime,
+    /// Media, volume, browser and application launch keys.
+    Media,
+    Gamepad,
+    System,
+}
 
+pub struct VirtualKeyInfo {
+    pub code: u8,
+    /// The name of the VK_* constant in WinUser.h, or kbd.h for those only defined there.
+    pub name: &'static str,
+    /// Other names of the same code, such as VK_HANGUL for VK_KANA.
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    pub category: VirtualKeyCategory,
+}
 
 impl VirtualKey {
+    pub fn info(&self) -> Option<&'static VirtualKeyInfo> {
+        VIRTUAL_KEYS.iter().find(|info| info.code == self.code)
+    }
+
+    /// Gets the VK_* name of the virtual key. Digits and letters have no constant in WinUser.h,
+    /// so their names, such as VK_A, are only included if requested.
     pub fn to_vk_enum(&self, include_ascii: bool) -> Option<String> {
-        if include_ascii && Self::is_code_ascii(self.code) {
-            let mut vk_name = "VK_".to_string();
-            vk_name.push(self.code as char);
-            return Some(vk_name);
+        if !include_ascii && Self::is_code_ascii(self.code) {
+            return None
         }
-        code_to_vk_name.get_by_left(&self.code).map(|name| name.to_string())
+        self.info().map(|info| info.name.to_string())
     }
 
+    /// Parses a VK_* name or alias, ignoring case.
     pub fn from_vk_enum(name: &str, include_ascii: bool) -> Option<Self> {
-        if include_ascii && name.starts_with("VK_") && name.len() == 4 {
-            let c = name[3..].chars().next()? as u32;
-            if c < 0x80 && Self::is_code_ascii(c as u8) {
-                return Some(VirtualKey { code: c as u8 });
-            }
-        }
+        VIRTUAL_KEYS.iter()
+            .filter(|info| include_ascii || !Self::is_code_ascii(info.code))
+            .find(|info| info.name.eq_ignore_ascii_case(name)
+                || info.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name)))
+            .map(|info| VirtualKey { code: info.code })
+    }
 
-        code_to_vk_name.get_by_right(&name).copied().map(|code| VirtualKey { code })
+    /// Maps left/right modifier keys to the generic virtual key found in modifier tables.
+    pub fn generic_modifier(&self) -> VirtualKey {
+        match *self {
+            VirtualKey::LEFT_SHIFT | VirtualKey::RIGHT_SHIFT => VirtualKey::SHIFT,
+            VirtualKey::LEFT_CONTROL | VirtualKey::RIGHT_CONTROL => VirtualKey::CONTROL,
+            VirtualKey::LEFT_ALT | VirtualKey::RIGHT_ALT => VirtualKey::ALT,
+            virtual_key => virtual_key
+        }
     }
 
     pub fn is_code_ascii(code: u8) -> bool {
-        if code >= 0x30 && code <= 0x39 {
-            true // Numeric keys (0-9)
-        }
-        else if code >= 0x41 && code <= 0x5A {
-            true // Alphabetic keys (A-Z)
-        }
-        else {
-            false // Other keys
-        }
+        // Numeric keys (0-9) and alphabetic keys (A-Z)
+        (0x30..=0x39).contains(&code) || (0x41..=0x5A).contains(&code)
     }
 }
 
-lazy_static! {
-    static ref code_to_vk_name: BiHashMap<u8, &'static str> = {
-        let mut map = BiHashMap::new();
-        map.insert(0x01, "VK_LBUTTON");
-        map.insert(0x02, "VK_RBUTTON");
-        map.insert(0x03, "VK_CANCEL");
-        map.insert(0x04, "VK_MBUTTON");
-        map.insert(0x05, "VK_XBUTTON1");
-        map.insert(0x06, "VK_XBUTTON2");
-        map.insert(0x08, "VK_BACK");
-        map.insert(0x09, "VK_TAB");
-        map.insert(0x0C, "VK_CLEAR");
-        map.insert(0x0D, "VK_RETURN");
-        map.insert(0x10, "VK_SHIFT");
-        map.insert(0x11, "VK_CONTROL");
-        map.insert(0x12, "VK_MENU");
-        map.insert(0x13, "VK_PAUSE");
-        map.insert(0x14, "VK_CAPITAL");
-        map.insert(0x15, "VK_KANA");
-        map.insert(0x15, "VK_HANGUL");
-        map.insert(0x16, "VK_IME_ON");
-        map.insert(0x17, "VK_JUNJA");
-        map.insert(0x18, "VK_FINAL");
-        map.insert(0x19, "VK_HANJA");
-        map.insert(0x19, "VK_KANJI");
-        map.insert(0x1A, "VK_IME_OFF");
-        map.insert(0x1B, "VK_ESCAPE");
-        map.insert(0x1C, "VK_CONVERT");
-        map.insert(0x1D, "VK_NONCONVERT");
-        map.insert(0x1E, "VK_ACCEPT");
-        map.insert(0x1F, "VK_MODECHANGE");
-        map.insert(0x20, "VK_SPACE");
-        map.insert(0x21, "VK_PRIOR");
-        map.insert(0x22, "VK_NEXT");
-        map.insert(0x23, "VK_END");
-        map.insert(0x24, "VK_HOME");
-        map.insert(0x25, "VK_LEFT");
-        map.insert(0x26, "VK_UP");
-        map.insert(0x27, "VK_RIGHT");
-        map.insert(0x28, "VK_DOWN");
-        map.insert(0x29, "VK_SELECT");
-        map.insert(0x2A, "VK_PRINT");
-        map.insert(0x2B, "VK_EXECUTE");
-        map.insert(0x2C, "VK_SNAPSHOT");
-        map.insert(0x2D, "VK_INSERT");
-        map.insert(0x2E, "VK_DELETE");
-        map.insert(0x2F, "VK_HELP");
-        map.insert(0x5B, "VK_LWIN");
-        map.insert(0x5C, "VK_RWIN");
-        map.insert(0x5D, "VK_APPS");
-        map.insert(0x5F, "VK_SLEEP");
-        map.insert(0x60, "VK_NUMPAD0");
-        map.insert(0x61, "VK_NUMPAD1");
-        map.insert(0x62, "VK_NUMPAD2");
-        map.insert(0x63, "VK_NUMPAD3");
-        map.insert(0x64, "VK_NUMPAD4");
-        map.insert(0x65, "VK_NUMPAD5");
-        map.insert(0x66, "VK_NUMPAD6");
-        map.insert(0x67, "VK_NUMPAD7");
-        map.insert(0x68, "VK_NUMPAD8");
-        map.insert(0x69, "VK_NUMPAD9");
-        map.insert(0x6A, "VK_MULTIPLY");
-        map.insert(0x6B, "VK_ADD");
-        map.insert(0x6C, "VK_SEPARATOR");
-        map.insert(0x6D, "VK_SUBTRACT");
-        map.insert(0x6E, "VK_DECIMAL");
-        map.insert(0x6F, "VK_DIVIDE");
-        map.insert(0x70, "VK_F1");
-        map.insert(0x71, "VK_F2");
-        map.insert(0x72, "VK_F3");
-        map.insert(0x73, "VK_F4");
-        map.insert(0x74, "VK_F5");
-        map.insert(0x75, "VK_F6");
-        map.insert(0x76, "VK_F7");
-        map.insert(0x77, "VK_F8");
-        map.insert(0x78, "VK_F9");
-        map.insert(0x79, "VK_F10");
-        map.insert(0x7A, "VK_F11");
-        map.insert(0x7B, "VK_F12");
-        map.insert(0x7C, "VK_F13");
-        map.insert(0x7D, "VK_F14");
-        map.insert(0x7E, "VK_F15");
-        map.insert(0x7F, "VK_F16");
-        map.insert(0x80, "VK_F17");
-        map.insert(0x81, "VK_F18");
-        map.insert(0x82, "VK_F19");
-        map.insert(0x83, "VK_F20");
-        map.insert(0x84, "VK_F21");
-        map.insert(0x85, "VK_F22");
-        map.insert(0x86, "VK_F23");
-        map.insert(0x87, "VK_F24");
-        map.insert(0x90, "VK_NUMLOCK");
-        map.insert(0x91, "VK_SCROLL");
-        map.insert(0xA0, "VK_LSHIFT");
-        map.insert(0xA1, "VK_RSHIFT");
-        map.insert(0xA2, "VK_LCONTROL");
-        map.insert(0xA3, "VK_RCONTROL");
-        map.insert(0xA4, "VK_LMENU");
-        map.insert(0xA5, "VK_RMENU");
-        map.insert(0xA6, "VK_BROWSER_BACK");
-        map.insert(0xA7, "VK_BROWSER_FORWARD");
-        map.insert(0xA8, "VK_BROWSER_REFRESH");
-        map.insert(0xA9, "VK_BROWSER_STOP");
-        map.insert(0xAA, "VK_BROWSER_SEARCH");
-        map.insert(0xAB, "VK_BROWSER_FAVORITES");
-        map.insert(0xAC, "VK_BROWSER_HOME");
-        map.insert(0xAD, "VK_VOLUME_MUTE");
-        map.insert(0xAE, "VK_VOLUME_DOWN");
-        map.insert(0xAF, "VK_VOLUME_UP");
-        map.insert(0xB0, "VK_MEDIA_NEXT_TRACK");
-        map.insert(0xB1, "VK_MEDIA_PREV_TRACK");
-        map.insert(0xB2, "VK_MEDIA_STOP");
-        map.insert(0xB3, "VK_MEDIA_PLAY_PAUSE");
-        map.insert(0xB4, "VK_LAUNCH_MAIL");
-        map.insert(0xB5, "VK_LAUNCH_MEDIA_SELECT");
-        map.insert(0xB6, "VK_LAUNCH_APP1");
-        map.insert(0xB7, "VK_LAUNCH_APP2");
-        map.insert(0xBA, "VK_OEM_1");
-        map.insert(0xBB, "VK_OEM_PLUS");
-        map.insert(0xBC, "VK_OEM_COMMA");
-        map.insert(0xBD, "VK_OEM_MINUS");
-        map.insert(0xBE, "VK_OEM_PERIOD");
-        map.insert(0xBF, "VK_OEM_2");
-        map.insert(0xC0, "VK_OEM_3");
-        map.insert(0xC1, "VK_ABNT_C1");
-        map.insert(0xC2, "VK_ABNT_C2");
-        map.insert(0xDB, "VK_OEM_4");
-        map.insert(0xDC, "VK_OEM_5");
-        map.insert(0xDD, "VK_OEM_6");
-        map.insert(0xDE, "VK_OEM_7");
-        map.insert(0xDF, "VK_OEM_8");
-        map.insert(0xE2, "VK_OEM_102");
-        map.insert(0xE5, "VK_PROCESSKEY");
-        map.insert(0xE7, "VK_PACKET");
-        map.insert(0xF6, "VK_ATTN");
-        map.insert(0xF7, "VK_CRSEL");
-        map.insert(0xF8, "VK_EXSEL");
-        map.insert(0xF9, "VK_EREOF");
-        map.insert(0xFA, "VK_PLAY");
-        map.insert(0xFB, "VK_ZOOM");
-        map.insert(0xFC, "VK_NONAME");
-        map.insert(0xFD, "VK_PA1");
-        map.insert(0xFE, "VK_OEM_CLEAR");
-        map.insert(0xFF, "VK__none_");
-        map
-    };
-}
\ No newline at end of file
+const fn info(code: u8, name: &'static str, aliases: &'static [&'static str], description: &'static str, category: VirtualKeyCategory) -> VirtualKeyInfo {
+    VirtualKeyInfo { code, name, aliases, description, category }
+}
+
+/// Every virtual key code assigned in WinUser.h and kbd.h, ordered by code.
+/// See https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
+static VIRTUAL_KEYS: &[VirtualKeyInfo] = &[
+    info(0x01, "VK_LBUTTON", &[], "Left mouse button", VirtualKeyCategory::Mouse),
+    info(0x02, "VK_RBUTTON", &[], "Right mouse button", VirtualKeyCategory::Mouse),
+    info(0x03, "VK_CANCEL", &[], "Control-break processing", VirtualKeyCategory::System),
+    info(0x04, "VK_MBUTTON", &[], "Middle mouse button", VirtualKeyCategory::Mouse),
+    info(0x05, "VK_XBUTTON1", &[], "X1 mouse button", VirtualKeyCategory::Mouse),
+    info(0x06, "VK_XBUTTON2", &[], "X2 mouse button", VirtualKeyCategory::Mouse),
+    info(0x08, "VK_BACK", &[], "Backspace key", VirtualKeyCategory::Typing),
+    info(0x09, "VK_TAB", &[], "Tab key", VirtualKeyCategory::Typing),
+    info(0x0C, "VK_CLEAR", &[], "Clear key", VirtualKeyCategory::Typing),
+    info(0x0D, "VK_RETURN", &[], "Enter key", VirtualKeyCategory::Typing),
+    info(0x10, "VK_SHIFT", &[], "Shift key", VirtualKeyCategory::Modifier),
+    info(0x11, "VK_CONTROL", &[], "Ctrl key", VirtualKeyCategory::Modifier),
+    info(0x12, "VK_MENU", &[], "Alt key", VirtualKeyCategory::Modifier),
+    info(0x13, "VK_PAUSE", &[], "Pause key", VirtualKeyCategory::System),
+    info(0x14, "VK_CAPITAL", &[], "Caps Lock key", VirtualKeyCategory::Modifier),
+    info(0x15, "VK_KANA", &["VK_HANGUL", "VK_HANGEUL"], "IME Kana mode, or Hangul mode on Korean keyboards", VirtualKeyCategory::Ime),
+    info(0x16, "VK_IME_ON", &[], "IME On", VirtualKeyCategory::Ime),
+    info(0x17, "VK_JUNJA", &[], "IME Junja mode", VirtualKeyCategory::Ime),
+    info(0x18, "VK_FINAL", &[], "IME final mode", VirtualKeyCategory::Ime),
+    info(0x19, "VK_HANJA", &["VK_KANJI"], "IME Hanja mode, or Kanji mode on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0x1A, "VK_IME_OFF", &[], "IME Off", VirtualKeyCategory::Ime),
+    info(0x1B, "VK_ESCAPE", &[], "Esc key", VirtualKeyCategory::System),
+    info(0x1C, "VK_CONVERT", &[], "IME convert", VirtualKeyCategory::Ime),
+    info(0x1D, "VK_NONCONVERT", &[], "IME nonconvert", VirtualKeyCategory::Ime),
+    info(0x1E, "VK_ACCEPT", &[], "IME accept", VirtualKeyCategory::Ime),
+    info(0x1F, "VK_MODECHANGE", &[], "IME mode change request", VirtualKeyCategory::Ime),
+    info(0x20, "VK_SPACE", &[], "Spacebar", VirtualKeyCategory::Typing),
+    info(0x21, "VK_PRIOR", &[], "Page Up key", VirtualKeyCategory::Navigation),
+    info(0x22, "VK_NEXT", &[], "Page Down key", VirtualKeyCategory::Navigation),
+    info(0x23, "VK_END", &[], "End key", VirtualKeyCategory::Navigation),
+    info(0x24, "VK_HOME", &[], "Home key", VirtualKeyCategory::Navigation),
+    info(0x25, "VK_LEFT", &[], "Left Arrow key", VirtualKeyCategory::Navigation),
+    info(0x26, "VK_UP", &[], "Up Arrow key", VirtualKeyCategory::Navigation),
+    info(0x27, "VK_RIGHT", &[], "Right Arrow key", VirtualKeyCategory::Navigation),
+    info(0x28, "VK_DOWN", &[], "Down Arrow key", VirtualKeyCategory::Navigation),
+    info(0x29, "VK_SELECT", &[], "Select key", VirtualKeyCategory::System),
+    info(0x2A, "VK_PRINT", &[], "Print key", VirtualKeyCategory::System),
+    info(0x2B, "VK_EXECUTE", &[], "Execute key", VirtualKeyCategory::System),
+    info(0x2C, "VK_SNAPSHOT", &[], "Print Screen key", VirtualKeyCategory::System),
+    info(0x2D, "VK_INSERT", &[], "Insert key", VirtualKeyCategory::Navigation),
+    info(0x2E, "VK_DELETE", &[], "Delete key", VirtualKeyCategory::Typing),
+    info(0x2F, "VK_HELP", &[], "Help key", VirtualKeyCategory::System),
+    info(0x30, "VK_0", &[], "0 key", VirtualKeyCategory::Typing),
+    info(0x31, "VK_1", &[], "1 key", VirtualKeyCategory::Typing),
+    info(0x32, "VK_2", &[], "2 key", VirtualKeyCategory::Typing),
+    info(0x33, "VK_3", &[], "3 key", VirtualKeyCategory::Typing),
+    info(0x34, "VK_4", &[], "4 key", VirtualKeyCategory::Typing),
+    info(0x35, "VK_5", &[], "5 key", VirtualKeyCategory::Typing),
+    info(0x36, "VK_6", &[], "6 key", VirtualKeyCategory::Typing),
+    info(0x37, "VK_7", &[], "7 key", VirtualKeyCategory::Typing),
+    info(0x38, "VK_8", &[], "8 key", VirtualKeyCategory::Typing),
+    info(0x39, "VK_9", &[], "9 key", VirtualKeyCategory::Typing),
+    info(0x41, "VK_A", &[], "A key", VirtualKeyCategory::Typing),
+    info(0x42, "VK_B", &[], "B key", VirtualKeyCategory::Typing),
+    info(0x43, "VK_C", &[], "C key", VirtualKeyCategory::Typing),
+    info(0x44, "VK_D", &[], "D key", VirtualKeyCategory::Typing),
+    info(0x45, "VK_E", &[], "E key", VirtualKeyCategory::Typing),
+    info(0x46, "VK_F", &[], "F key", VirtualKeyCategory::Typing),
+    info(0x47, "VK_G", &[], "G key", VirtualKeyCategory::Typing),
+    info(0x48, "VK_H", &[], "H key", VirtualKeyCategory::Typing),
+    info(0x49, "VK_I", &[], "I key", VirtualKeyCategory::Typing),
+    info(0x4A, "VK_J", &[], "J key", VirtualKeyCategory::Typing),
+    info(0x4B, "VK_K", &[], "K key", VirtualKeyCategory::Typing),
+    info(0x4C, "VK_L", &[], "L key", VirtualKeyCategory::Typing),
+    info(0x4D, "VK_M", &[], "M key", VirtualKeyCategory::Typing),
+    info(0x4E, "VK_N", &[], "N key", VirtualKeyCategory::Typing),
+    info(0x4F, "VK_O", &[], "O key", VirtualKeyCategory::Typing),
+    info(0x50, "VK_P", &[], "P key", VirtualKeyCategory::Typing),
+    info(0x51, "VK_Q", &[], "Q key", VirtualKeyCategory::Typing),
+    info(0x52, "VK_R", &[], "R key", VirtualKeyCategory::Typing),
+    info(0x53, "VK_S", &[], "S key", VirtualKeyCategory::Typing),
+    info(0x54, "VK_T", &[], "T key", VirtualKeyCategory::Typing),
+    info(0x55, "VK_U", &[], "U key", VirtualKeyCategory::Typing),
+    info(0x56, "VK_V", &[], "V key", VirtualKeyCategory::Typing),
+    info(0x57, "VK_W", &[], "W key", VirtualKeyCategory::Typing),
+    info(0x58, "VK_X", &[], "X key", VirtualKeyCategory::Typing),
+    info(0x59, "VK_Y", &[], "Y key", VirtualKeyCategory::Typing),
+    info(0x5A, "VK_Z", &[], "Z key", VirtualKeyCategory::Typing),
+    info(0x5B, "VK_LWIN", &[], "Left Windows logo key", VirtualKeyCategory::Modifier),
+    info(0x5C, "VK_RWIN", &[], "Right Windows logo key", VirtualKeyCategory::Modifier),
+    info(0x5D, "VK_APPS", &[], "Application key", VirtualKeyCategory::System),
+    info(0x5F, "VK_SLEEP", &[], "Computer Sleep key", VirtualKeyCategory::System),
+    info(0x60, "VK_NUMPAD0", &[], "Numeric keypad 0 key", VirtualKeyCategory::Typing),
+    info(0x61, "VK_NUMPAD1", &[], "Numeric keypad 1 key", VirtualKeyCategory::Typing),
+    info(0x62, "VK_NUMPAD2", &[], "Numeric keypad 2 key", VirtualKeyCategory::Typing),
+    info(0x63, "VK_NUMPAD3", &[], "Numeric keypad 3 key", VirtualKeyCategory::Typing),
+    info(0x64, "VK_NUMPAD4", &[], "Numeric keypad 4 key", VirtualKeyCategory::Typing),
+    info(0x65, "VK_NUMPAD5", &[], "Numeric keypad 5 key", VirtualKeyCategory::Typing),
+    info(0x66, "VK_NUMPAD6", &[], "Numeric keypad 6 key", VirtualKeyCategory::Typing),
+    info(0x67, "VK_NUMPAD7", &[], "Numeric keypad 7 key", VirtualKeyCategory::Typing),
+    info(0x68, "VK_NUMPAD8", &[], "Numeric keypad 8 key", VirtualKeyCategory::Typing),
+    info(0x69, "VK_NUMPAD9", &[], "Numeric keypad 9 key", VirtualKeyCategory::Typing),
+    info(0x6A, "VK_MULTIPLY", &[], "Multiply key", VirtualKeyCategory::Typing),
+    info(0x6B, "VK_ADD", &[], "Add key", VirtualKeyCategory::Typing),
+    info(0x6C, "VK_SEPARATOR", &[], "Separator key", VirtualKeyCategory::Typing),
+    info(0x6D, "VK_SUBTRACT", &[], "Subtract key", VirtualKeyCategory::Typing),
+    info(0x6E, "VK_DECIMAL", &[], "Decimal key", VirtualKeyCategory::Typing),
+    info(0x6F, "VK_DIVIDE", &[], "Divide key", VirtualKeyCategory::Typing),
+    info(0x70, "VK_F1", &[], "F1 key", VirtualKeyCategory::Function),
+    info(0x71, "VK_F2", &[], "F2 key", VirtualKeyCategory::Function),
+    info(0x72, "VK_F3", &[], "F3 key", VirtualKeyCategory::Function),
+    info(0x73, "VK_F4", &[], "F4 key", VirtualKeyCategory::Function),
+    info(0x74, "VK_F5", &[], "F5 key", VirtualKeyCategory::Function),
+    info(0x75, "VK_F6", &[], "F6 key", VirtualKeyCategory::Function),
+    info(0x76, "VK_F7", &[], "F7 key", VirtualKeyCategory::Function),
+    info(0x77, "VK_F8", &[], "F8 key", VirtualKeyCategory::Function),
+    info(0x78, "VK_F9", &[], "F9 key", VirtualKeyCategory::Function),
+    info(0x79, "VK_F10", &[], "F10 key", VirtualKeyCategory::Function),
+    info(0x7A, "VK_F11", &[], "F11 key", VirtualKeyCategory::Function),
+    info(0x7B, "VK_F12", &[], "F12 key", VirtualKeyCategory::Function),
+    info(0x7C, "VK_F13", &[], "F13 key", VirtualKeyCategory::Function),
+    info(0x7D, "VK_F14", &[], "F14 key", VirtualKeyCategory::Function),
+    info(0x7E, "VK_F15", &[], "F15 key", VirtualKeyCategory::Function),
+    info(0x7F, "VK_F16", &[], "F16 key", VirtualKeyCategory::Function),
+    info(0x80, "VK_F17", &[], "F17 key", VirtualKeyCategory::Function),
+    info(0x81, "VK_F18", &[], "F18 key", VirtualKeyCategory::Function),
+    info(0x82, "VK_F19", &[], "F19 key", VirtualKeyCategory::Function),
+    info(0x83, "VK_F20", &[], "F20 key", VirtualKeyCategory::Function),
+    info(0x84, "VK_F21", &[], "F21 key", VirtualKeyCategory::Function),
+    info(0x85, "VK_F22", &[], "F22 key", VirtualKeyCategory::Function),
+    info(0x86, "VK_F23", &[], "F23 key", VirtualKeyCategory::Function),
+    info(0x87, "VK_F24", &[], "F24 key", VirtualKeyCategory::Function),
+    info(0x88, "VK_NAVIGATION_VIEW", &[], "UI navigation view", VirtualKeyCategory::Navigation),
+    info(0x89, "VK_NAVIGATION_MENU", &[], "UI navigation menu", VirtualKeyCategory::Navigation),
+    info(0x8A, "VK_NAVIGATION_UP", &[], "UI navigation up", VirtualKeyCategory::Navigation),
+    info(0x8B, "VK_NAVIGATION_DOWN", &[], "UI navigation down", VirtualKeyCategory::Navigation),
+    info(0x8C, "VK_NAVIGATION_LEFT", &[], "UI navigation left", VirtualKeyCategory::Navigation),
+    info(0x8D, "VK_NAVIGATION_RIGHT", &[], "UI navigation right", VirtualKeyCategory::Navigation),
+    info(0x8E, "VK_NAVIGATION_ACCEPT", &[], "UI navigation accept", VirtualKeyCategory::Navigation),
+    info(0x8F, "VK_NAVIGATION_CANCEL", &[], "UI navigation cancel", VirtualKeyCategory::Navigation),
+    info(0x90, "VK_NUMLOCK", &[], "Num Lock key", VirtualKeyCategory::Modifier),
+    info(0x91, "VK_SCROLL", &[], "Scroll Lock key", VirtualKeyCategory::Modifier),
+    info(0x92, "VK_OEM_NEC_EQUAL", &["VK_OEM_FJ_JISHO"], "Equals key on the NEC PC-9800 numeric keypad, or Dictionary key on Fujitsu/OASYS keyboards", VirtualKeyCategory::Typing),
+    info(0x93, "VK_OEM_FJ_MASSHOU", &[], "Unregister word key on Fujitsu/OASYS keyboards", VirtualKeyCategory::Ime),
+    info(0x94, "VK_OEM_FJ_TOUROKU", &[], "Register word key on Fujitsu/OASYS keyboards", VirtualKeyCategory::Ime),
+    info(0x95, "VK_OEM_FJ_LOYA", &[], "Left OYAYUBI key on Fujitsu/OASYS keyboards", VirtualKeyCategory::Modifier),
+    info(0x96, "VK_OEM_FJ_ROYA", &[], "Right OYAYUBI key on Fujitsu/OASYS keyboards", VirtualKeyCategory::Modifier),
+    info(0xA0, "VK_LSHIFT", &[], "Left Shift key", VirtualKeyCategory::Modifier),
+    info(0xA1, "VK_RSHIFT", &[], "Right Shift key", VirtualKeyCategory::Modifier),
+    info(0xA2, "VK_LCONTROL", &[], "Left Ctrl key", VirtualKeyCategory::Modifier),
+    info(0xA3, "VK_RCONTROL", &[], "Right Ctrl key", VirtualKeyCategory::Modifier),
+    info(0xA4, "VK_LMENU", &[], "Left Alt key", VirtualKeyCategory::Modifier),
+    info(0xA5, "VK_RMENU", &[], "Right Alt key", VirtualKeyCategory::Modifier),
+    info(0xA6, "VK_BROWSER_BACK", &[], "Browser Back key", VirtualKeyCategory::Media),
+    info(0xA7, "VK_BROWSER_FORWARD", &[], "Browser Forward key", VirtualKeyCategory::Media),
+    info(0xA8, "VK_BROWSER_REFRESH", &[], "Browser Refresh key", VirtualKeyCategory::Media),
+    info(0xA9, "VK_BROWSER_STOP", &[], "Browser Stop key", VirtualKeyCategory::Media),
+    info(0xAA, "VK_BROWSER_SEARCH", &[], "Browser Search key", VirtualKeyCategory::Media),
+    info(0xAB, "VK_BROWSER_FAVORITES", &[], "Browser Favorites key", VirtualKeyCategory::Media),
+    info(0xAC, "VK_BROWSER_HOME", &[], "Browser Start and Home key", VirtualKeyCategory::Media),
+    info(0xAD, "VK_VOLUME_MUTE", &[], "Volume Mute key", VirtualKeyCategory::Media),
+    info(0xAE, "VK_VOLUME_DOWN", &[], "Volume Down key", VirtualKeyCategory::Media),
+    info(0xAF, "VK_VOLUME_UP", &[], "Volume Up key", VirtualKeyCategory::Media),
+    info(0xB0, "VK_MEDIA_NEXT_TRACK", &[], "Next Track key", VirtualKeyCategory::Media),
+    info(0xB1, "VK_MEDIA_PREV_TRACK", &[], "Previous Track key", VirtualKeyCategory::Media),
+    info(0xB2, "VK_MEDIA_STOP", &[], "Stop Media key", VirtualKeyCategory::Media),
+    info(0xB3, "VK_MEDIA_PLAY_PAUSE", &[], "Play/Pause Media key", VirtualKeyCategory::Media),
+    info(0xB4, "VK_LAUNCH_MAIL", &[], "Start Mail key", VirtualKeyCategory::Media),
+    info(0xB5, "VK_LAUNCH_MEDIA_SELECT", &[], "Select Media key", VirtualKeyCategory::Media),
+    info(0xB6, "VK_LAUNCH_APP1", &[], "Start Application 1 key", VirtualKeyCategory::Media),
+    info(0xB7, "VK_LAUNCH_APP2", &[], "Start Application 2 key", VirtualKeyCategory::Media),
+    info(0xBA, "VK_OEM_1", &[], "OEM key, ;: on US keyboards", VirtualKeyCategory::Typing),
+    info(0xBB, "VK_OEM_PLUS", &[], "OEM key, + in any country/region", VirtualKeyCategory::Typing),
+    info(0xBC, "VK_OEM_COMMA", &[], "OEM key, , in any country/region", VirtualKeyCategory::Typing),
+    info(0xBD, "VK_OEM_MINUS", &[], "OEM key, - in any country/region", VirtualKeyCategory::Typing),
+    info(0xBE, "VK_OEM_PERIOD", &[], "OEM key, . in any country/region", VirtualKeyCategory::Typing),
+    info(0xBF, "VK_OEM_2", &[], "OEM key, /? on US keyboards", VirtualKeyCategory::Typing),
+    info(0xC0, "VK_OEM_3", &[], "OEM key, `~ on US keyboards", VirtualKeyCategory::Typing),
+    info(0xC1, "VK_ABNT_C1", &[], "Brazilian ABNT /? key", VirtualKeyCategory::Typing),
+    info(0xC2, "VK_ABNT_C2", &[], "Brazilian ABNT numeric keypad . key", VirtualKeyCategory::Typing),
+    info(0xC3, "VK_GAMEPAD_A", &[], "Gamepad A button", VirtualKeyCategory::Gamepad),
+    info(0xC4, "VK_GAMEPAD_B", &[], "Gamepad B button", VirtualKeyCategory::Gamepad),
+    info(0xC5, "VK_GAMEPAD_X", &[], "Gamepad X button", VirtualKeyCategory::Gamepad),
+    info(0xC6, "VK_GAMEPAD_Y", &[], "Gamepad Y button", VirtualKeyCategory::Gamepad),
+    info(0xC7, "VK_GAMEPAD_RIGHT_SHOULDER", &[], "Gamepad right shoulder", VirtualKeyCategory::Gamepad),
+    info(0xC8, "VK_GAMEPAD_LEFT_SHOULDER", &[], "Gamepad left shoulder", VirtualKeyCategory::Gamepad),
+    info(0xC9, "VK_GAMEPAD_LEFT_TRIGGER", &[], "Gamepad left trigger", VirtualKeyCategory::Gamepad),
+    info(0xCA, "VK_GAMEPAD_RIGHT_TRIGGER", &[], "Gamepad right trigger", VirtualKeyCategory::Gamepad),
+    info(0xCB, "VK_GAMEPAD_DPAD_UP", &[], "Gamepad D-pad up", VirtualKeyCategory::Gamepad),
+    info(0xCC, "VK_GAMEPAD_DPAD_DOWN", &[], "Gamepad D-pad down", VirtualKeyCategory::Gamepad),
+    info(0xCD, "VK_GAMEPAD_DPAD_LEFT", &[], "Gamepad D-pad left", VirtualKeyCategory::Gamepad),
+    info(0xCE, "VK_GAMEPAD_DPAD_RIGHT", &[], "Gamepad D-pad right", VirtualKeyCategory::Gamepad),
+    info(0xCF, "VK_GAMEPAD_MENU", &[], "Gamepad Menu button", VirtualKeyCategory::Gamepad),
+    info(0xD0, "VK_GAMEPAD_VIEW", &[], "Gamepad View button", VirtualKeyCategory::Gamepad),
+    info(0xD1, "VK_GAMEPAD_LEFT_THUMBSTICK_BUTTON", &[], "Gamepad left thumbstick button", VirtualKeyCategory::Gamepad),
+    info(0xD2, "VK_GAMEPAD_RIGHT_THUMBSTICK_BUTTON", &[], "Gamepad right thumbstick button", VirtualKeyCategory::Gamepad),
+    info(0xD3, "VK_GAMEPAD_LEFT_THUMBSTICK_UP", &[], "Gamepad left thumbstick up", VirtualKeyCategory::Gamepad),
+    info(0xD4, "VK_GAMEPAD_LEFT_THUMBSTICK_DOWN", &[], "Gamepad left thumbstick down", VirtualKeyCategory::Gamepad),
+    info(0xD5, "VK_GAMEPAD_LEFT_THUMBSTICK_RIGHT", &[], "Gamepad left thumbstick right", VirtualKeyCategory::Gamepad),
+    info(0xD6, "VK_GAMEPAD_LEFT_THUMBSTICK_LEFT", &[], "Gamepad left thumbstick left", VirtualKeyCategory::Gamepad),
+    info(0xD7, "VK_GAMEPAD_RIGHT_THUMBSTICK_UP", &[], "Gamepad right thumbstick up", VirtualKeyCategory::Gamepad),
+    info(0xD8, "VK_GAMEPAD_RIGHT_THUMBSTICK_DOWN", &[], "Gamepad right thumbstick down", VirtualKeyCategory::Gamepad),
+    info(0xD9, "VK_GAMEPAD_RIGHT_THUMBSTICK_RIGHT", &[], "Gamepad right thumbstick right", VirtualKeyCategory::Gamepad),
+    info(0xDA, "VK_GAMEPAD_RIGHT_THUMBSTICK_LEFT", &[], "Gamepad right thumbstick left", VirtualKeyCategory::Gamepad),
+    info(0xDB, "VK_OEM_4", &[], "OEM key, [{ on US keyboards", VirtualKeyCategory::Typing),
+    info(0xDC, "VK_OEM_5", &[], "OEM key, \\| on US keyboards", VirtualKeyCategory::Typing),
+    info(0xDD, "VK_OEM_6", &[], "OEM key, ]} on US keyboards", VirtualKeyCategory::Typing),
+    info(0xDE, "VK_OEM_7", &[], "OEM key, '\" on US keyboards", VirtualKeyCategory::Typing),
+    info(0xDF, "VK_OEM_8", &[], "OEM key, varies by keyboard", VirtualKeyCategory::Typing),
+    info(0xE1, "VK_OEM_AX", &[], "AX key on Japanese AX keyboards", VirtualKeyCategory::System),
+    info(0xE2, "VK_OEM_102", &[], "OEM key, <> or \\| on the 102-key keyboard", VirtualKeyCategory::Typing),
+    info(0xE3, "VK_ICO_HELP", &[], "Help key on Olivetti ICO keyboards", VirtualKeyCategory::System),
+    info(0xE4, "VK_ICO_00", &[], "00 key on Olivetti ICO keyboards", VirtualKeyCategory::Typing),
+    info(0xE5, "VK_PROCESSKEY", &[], "IME process key", VirtualKeyCategory::Ime),
+    info(0xE6, "VK_ICO_CLEAR", &[], "Clear key on Olivetti ICO keyboards", VirtualKeyCategory::Typing),
+    info(0xE7, "VK_PACKET", &[], "Unicode characters passed as keystrokes", VirtualKeyCategory::Typing),
+    info(0xE9, "VK_OEM_RESET", &[], "Nokia/Ericsson Reset key", VirtualKeyCategory::System),
+    info(0xEA, "VK_OEM_JUMP", &[], "Nokia/Ericsson Jump key", VirtualKeyCategory::System),
+    info(0xEB, "VK_OEM_PA1", &[], "Nokia/Ericsson PA1 key", VirtualKeyCategory::System),
+    info(0xEC, "VK_OEM_PA2", &[], "Nokia/Ericsson PA2 key", VirtualKeyCategory::System),
+    info(0xED, "VK_OEM_PA3", &[], "Nokia/Ericsson PA3 key", VirtualKeyCategory::System),
+    info(0xEE, "VK_OEM_WSCTRL", &[], "Nokia/Ericsson WsCtrl key", VirtualKeyCategory::System),
+    info(0xEF, "VK_OEM_CUSEL", &[], "Nokia/Ericsson CuSel key", VirtualKeyCategory::System),
+    info(0xF0, "VK_OEM_ATTN", &["VK_DBE_ALPHANUMERIC"], "Attn key on Nokia/Ericsson keyboards, or alphanumeric mode on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF1, "VK_OEM_FINISH", &["VK_DBE_KATAKANA"], "Finish key on Nokia/Ericsson keyboards, or katakana mode on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF2, "VK_OEM_COPY", &["VK_DBE_HIRAGANA"], "Copy key on Nokia/Ericsson keyboards, or hiragana mode on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF3, "VK_OEM_AUTO", &["VK_DBE_SBCSCHAR"], "Auto key on Nokia/Ericsson keyboards, or single-byte character mode on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF4, "VK_OEM_ENLW", &["VK_DBE_DBCSCHAR"], "Enlw key on Nokia/Ericsson keyboards, or double-byte character mode on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF5, "VK_OEM_BACKTAB", &["VK_DBE_ROMAN"], "BackTab key on Nokia/Ericsson keyboards, or romaji conversion on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF6, "VK_ATTN", &["VK_DBE_NOROMAN"], "Attn key, or romaji conversion off on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF7, "VK_CRSEL", &["VK_DBE_ENTERWORDREGISTERMODE"], "CrSel key, or word registration on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF8, "VK_EXSEL", &["VK_DBE_ENTERIMECONFIGMODE"], "ExSel key, or IME configuration on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xF9, "VK_EREOF", &["VK_DBE_FLUSHSTRING"], "Erase EOF key, or flush string on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xFA, "VK_PLAY", &["VK_DBE_CODEINPUT"], "Play key, or code input on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xFB, "VK_ZOOM", &["VK_DBE_NOCODEINPUT"], "Zoom key, or code input off on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xFC, "VK_NONAME", &["VK_DBE_DETERMINESTRING"], "Reserved, or determine string on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xFD, "VK_PA1", &["VK_DBE_ENTERDLGCONVERSIONMODE"], "PA1 key, or conversion dialog on Japanese keyboards", VirtualKeyCategory::Ime),
+    info(0xFE, "VK_OEM_CLEAR", &[], "Clear key", VirtualKeyCategory::Typing),
+    info(0xFF, "VK__none_", &[], "No virtual key", VirtualKeyCategory::System),
+];
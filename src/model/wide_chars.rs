@@ -0,0 +1,101 @@
+// Layout tables store UTF-16 code units, which do not always form valid Unicode:
+// characters outside the BMP need a surrogate pair, typically spread across a ligature,
+// and a table cell can hold a lone surrogate. These types decode code units into
+// Unicode scalar values where possible while preserving lone surrogates losslessly.
+
+/// A single character of a layout table.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum WideChar {
+    /// A Unicode scalar value.
+    Scalar(char),
+    /// A UTF-16 surrogate code unit which is not part of a pair.
+    Surrogate(u16),
+}
+
+impl WideChar {
+    pub fn from_u16(unit: u16) -> Self {
+        match char::from_u32(unit as u32) {
+            Some(char) => WideChar::Scalar(char),
+            None => WideChar::Surrogate(unit)
+        }
+    }
+
+    /// Gets the single UTF-16 code unit of this character, if it is in the BMP or a lone surrogate.
+    pub fn to_u16(&self) -> Option<u16> {
+        match self {
+            WideChar::Scalar(char) => u16::try_from(*char as u32).ok(),
+            WideChar::Surrogate(unit) => Some(*unit)
+        }
+    }
+
+    pub fn to_utf16(&self) -> Vec<u16> {
+        match self {
+            WideChar::Scalar(char) => char.encode_utf16(&mut [0; 2]).to_vec(),
+            WideChar::Surrogate(unit) => vec![*unit]
+        }
+    }
+
+    pub fn to_char(&self) -> Option<char> {
+        match self {
+            WideChar::Scalar(char) => Some(*char),
+            WideChar::Surrogate(_) => None
+        }
+    }
+
+    /// Whether this is a combining diacritical mark, which renders on the preceding character.
+    pub fn is_combining_mark(&self) -> bool {
+        matches!(self.to_char().map(|char| char as u32),
+            Some(0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE20..=0xFE2F))
+    }
+
+    /// Whether this is an invisible formatting character, such as a zero width joiner or a directional mark.
+    pub fn is_invisible_format(&self) -> bool {
+        matches!(self.to_char().map(|char| char as u32),
+            Some(0x200B..=0x200F | 0x202A..=0x202E | 0x2060..=0x2069 | 0xFEFF))
+    }
+}
+
+impl std::fmt::Debug for WideChar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WideChar::Scalar(char) => write!(f, "{:?}", char),
+            WideChar::Surrogate(unit) => write!(f, "\\u{:04X}", unit)
+        }
+    }
+}
+
+/// A sequence of characters of a layout table, such as a ligature.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+pub struct WideString(pub Vec<WideChar>);
+
+impl WideString {
+    /// Decodes UTF-16 code units, combining surrogate pairs and preserving lone surrogates.
+    pub fn from_utf16(units: &[u16]) -> Self {
+        Self(char::decode_utf16(units.iter().copied())
+            .map(|result| match result {
+                Ok(char) => WideChar::Scalar(char),
+                Err(error) => WideChar::Surrogate(error.unpaired_surrogate())
+            })
+            .collect())
+    }
+
+    pub fn to_utf16(&self) -> Vec<u16> {
+        self.0.iter().flat_map(|char| char.to_utf16()).collect()
+    }
+
+    /// Gets this string as Unicode text, if it contains no lone surrogates.
+    pub fn try_to_string(&self) -> Option<String> {
+        self.0.iter().map(|char| char.to_char()).collect()
+    }
+
+    /// Gets this string as Unicode text, replacing lone surrogates with U+FFFD.
+    pub fn to_string_lossy(&self) -> String {
+        self.0.iter().map(|char| char.to_char().unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+    }
+}
+
+impl From<&str> for WideString {
+    fn from(value: &str) -> Self {
+        Self(value.chars().map(WideChar::Scalar).collect())
+    }
+}
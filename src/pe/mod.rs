@@ -0,0 +1,190 @@
+// Minimal reader for Portable Executable images, enough to
+// extract headers and resources from keyboard layout DLLs.
+
+mod version_info;
+
+pub use version_info::*;
+
+pub struct PeImage<'a> {
+    bytes: &'a [u8],
+    /// The target machine (IMAGE_FILE_MACHINE_*).
+    pub machine: u16,
+    /// The link time, in seconds since the Unix epoch.
+    pub timestamp: u32,
+    /// The preferred load address, which pointers stored in the image are relative to.
+    pub image_base: u64,
+    /// Whether the image has a PE32+ optional header, as 64-bit images do.
+    pub is_pe32_plus: bool,
+    sections: Vec<Section>,
+    data_directories: Vec<DataDirectory>,
+}
+
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_data_offset: u32,
+    raw_data_size: u32,
+}
+
+#[derive(Clone, Copy)]
+struct DataDirectory {
+    rva: u32,
+    size: u32,
+}
+
+impl<'a> PeImage<'a> {
+    const DIRECTORY_ENTRY_EXPORT: usize = 0;
+    const DIRECTORY_ENTRY_RESOURCE: usize = 2;
+    const RESOURCE_TYPE_STRING: u32 = 6;
+    const RESOURCE_TYPE_VERSION: u32 = 16;
+
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        if read_u16(bytes, 0)? != 0x5A4D { return None } // MZ
+        let nt_headers_offset = read_u32(bytes, 0x3C)? as usize;
+        if read_u32(bytes, nt_headers_offset)? != 0x00004550 { return None } // PE\0\0
+
+        let file_header_offset = nt_headers_offset + 4;
+        let machine = read_u16(bytes, file_header_offset)?;
+        let section_count = read_u16(bytes, file_header_offset + 2)?;
+        let timestamp = read_u32(bytes, file_header_offset + 4)?;
+        let optional_header_size = read_u16(bytes, file_header_offset + 16)? as usize;
+
+        let optional_header_offset = file_header_offset + 20;
+        let (is_pe32_plus, image_base, data_directories_offset) = match read_u16(bytes, optional_header_offset)? {
+            0x10B => (false, read_u32(bytes, optional_header_offset + 28)? as u64, optional_header_offset + 92),
+            0x20B => (true, read_u64(bytes, optional_header_offset + 24)?, optional_header_offset + 108),
+            _ => return None
+        };
+
+        let data_directory_count = read_u32(bytes, data_directories_offset)? as usize;
+        let mut data_directories = Vec::new();
+        for index in 0..data_directory_count.min(16) {
+            let offset = data_directories_offset + 4 + index * 8;
+            data_directories.push(DataDirectory {
+                rva: read_u32(bytes, offset)?,
+                size: read_u32(bytes, offset + 4)?,
+            });
+        }
+
+        let mut sections = Vec::new();
+        let section_table_offset = optional_header_offset + optional_header_size;
+        for index in 0..section_count as usize {
+            let offset = section_table_offset + index * 40;
+            sections.push(Section {
+                virtual_size: read_u32(bytes, offset + 8)?,
+                virtual_address: read_u32(bytes, offset + 12)?,
+                raw_data_size: read_u32(bytes, offset + 16)?,
+                raw_data_offset: read_u32(bytes, offset + 20)?,
+            });
+        }
+
+        Some(Self { bytes, machine, timestamp, image_base, is_pe32_plus, sections, data_directories })
+    }
+
+    /// Gets the bytes of the image at the given relative virtual address,
+    /// up to the end of the raw data of the section containing it.
+    pub fn slice_at_rva(&self, rva: u32) -> Option<&'a [u8]> {
+        let section = self.sections.iter().find(|section| {
+            rva >= section.virtual_address
+                && rva - section.virtual_address < section.virtual_size.max(section.raw_data_size)
+        })?;
+
+        let offset_in_section = rva - section.virtual_address;
+        if offset_in_section >= section.raw_data_size { return None }
+        let start = section.raw_data_offset as usize + offset_in_section as usize;
+        let end = section.raw_data_offset as usize + section.raw_data_size as usize;
+        self.bytes.get(start..end.min(self.bytes.len()))
+    }
+
+    /// Finds the relative virtual address of an exported symbol by name.
+    pub fn find_export(&self, name: &str) -> Option<u32> {
+        let directory = *self.data_directories.get(Self::DIRECTORY_ENTRY_EXPORT)?;
+        if directory.rva == 0 || directory.size == 0 { return None }
+        let exports = self.slice_at_rva(directory.rva)?;
+
+        let name_count = read_u32(exports, 24)?;
+        let functions = self.slice_at_rva(read_u32(exports, 28)?)?;
+        let names = self.slice_at_rva(read_u32(exports, 32)?)?;
+        let name_ordinals = self.slice_at_rva(read_u32(exports, 36)?)?;
+        for index in 0..name_count as usize {
+            let name_bytes = self.slice_at_rva(read_u32(names, index * 4)?)?;
+            if name_bytes.split(|byte| *byte == 0).next() != Some(name.as_bytes()) { continue }
+
+            let ordinal = read_u16(name_ordinals, index * 2)? as usize;
+            return read_u32(functions, ordinal * 4);
+        }
+
+        None
+    }
+
+    /// Finds the data of the first resource with the given type,
+    /// regardless of its name or language.
+    pub fn find_resource(&self, resource_type: u32) -> Option<&'a [u8]> {
+        self.find_resource_by_id(resource_type, None)
+    }
+
+    /// Finds the data of the resource with the given type and, if given, numeric name, regardless of its language.
+    fn find_resource_by_id(&self, resource_type: u32, id: Option<u32>) -> Option<&'a [u8]> {
+        let directory = *self.data_directories.get(Self::DIRECTORY_ENTRY_RESOURCE)?;
+        if directory.rva == 0 || directory.size == 0 { return None }
+        let resources = self.slice_at_rva(directory.rva)?;
+
+        // The resource tree has three levels: type, name and language.
+        let names_offset = find_resource_directory_entry(resources, 0, Some(resource_type))?;
+        let languages_offset = find_resource_directory_entry(resources, names_offset & 0x7FFF_FFFF, id)?;
+        let data_entry_offset = find_resource_directory_entry(resources, languages_offset & 0x7FFF_FFFF, None)?;
+        if data_entry_offset & 0x8000_0000 != 0 { return None }
+
+        let data_rva = read_u32(resources, data_entry_offset as usize)?;
+        let data_size = read_u32(resources, data_entry_offset as usize + 4)? as usize;
+        self.slice_at_rva(data_rva)?.get(..data_size)
+    }
+
+    pub fn read_version_info(&self) -> Option<VersionInfo> {
+        VersionInfo::parse(self.find_resource(Self::RESOURCE_TYPE_VERSION)?)
+    }
+
+    /// Reads a string of the STRINGTABLE resources, which hold strings in blocks of 16,
+    /// each prefixed by its length in UTF-16 units. Empty strings count as missing, as for LoadString.
+    pub fn read_string_resource(&self, id: u32) -> Option<String> {
+        let block = self.find_resource_by_id(Self::RESOURCE_TYPE_STRING, Some(id / 16 + 1))?;
+        let mut offset = 0;
+        for _ in 0..(id % 16) {
+            offset += 2 + 2 * read_u16(block, offset)? as usize;
+        }
+
+        let length = read_u16(block, offset)? as usize;
+        if length == 0 { return None }
+        let units = (0..length).map(|index| read_u16(block, offset + 2 + 2 * index)).collect::<Option<Vec<u16>>>()?;
+        Some(String::from_utf16_lossy(&units))
+    }
+}
+
+/// Finds the entry with the given id in a resource directory, or the first entry if no id is given.
+/// Returns the raw offset to the entry's data, with the high bit set if it is a subdirectory.
+fn find_resource_directory_entry(resources: &[u8], directory_offset: u32, id: Option<u32>) -> Option<u32> {
+    let directory_offset = directory_offset as usize;
+    let named_entry_count = read_u16(resources, directory_offset + 12)? as usize;
+    let id_entry_count = read_u16(resources, directory_offset + 14)? as usize;
+    for index in 0..(named_entry_count + id_entry_count) {
+        let entry_offset = directory_offset + 16 + index * 8;
+        let name = read_u32(resources, entry_offset)?;
+        if id.is_none() || id == Some(name) {
+            return read_u32(resources, entry_offset + 4);
+        }
+    }
+
+    None
+}
+
+pub fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(offset..offset.checked_add(2)?)?.try_into().ok()?))
+}
+
+pub fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()?))
+}
+
+pub fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(offset..offset.checked_add(8)?)?.try_into().ok()?))
+}
@@ -0,0 +1,134 @@
+// Parses the VS_VERSIONINFO structure of a VERSIONINFO resource.
+// See https://learn.microsoft.com/en-us/windows/win32/menurc/vs-versioninfo
+
+use super::{read_u16, read_u32};
+
+pub struct VersionInfo {
+    /// From VS_FIXEDFILEINFO, as (major, minor, build, revision).
+    pub fixed_file_version: Option<[u16; 4]>,
+    pub fixed_product_version: Option<[u16; 4]>,
+    /// The (language, codepage) of the string table the strings were read from.
+    pub translation: Option<(u16, u16)>,
+    /// The (key, value) pairs of the string table.
+    pub strings: Vec<(String, String)>,
+}
+
+struct Block<'a> {
+    key: String,
+    value: &'a [u8],
+    is_text: bool,
+    children: &'a [u8],
+}
+
+impl VersionInfo {
+    const FIXED_FILE_INFO_SIGNATURE: u32 = 0xFEEF04BD;
+
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let (root, _) = read_block(bytes)?;
+        if root.key != "VS_VERSION_INFO" { return None }
+
+        let mut result = VersionInfo {
+            fixed_file_version: None,
+            fixed_product_version: None,
+            translation: None,
+            strings: Vec::new(),
+        };
+
+        if read_u32(root.value, 0) == Some(Self::FIXED_FILE_INFO_SIGNATURE) {
+            result.fixed_file_version = read_version(root.value, 8);
+            result.fixed_product_version = read_version(root.value, 16);
+        }
+
+        for child in blocks(root.children) {
+            match child.key.as_str() {
+                "StringFileInfo" => {
+                    // Only consider the first string table, layouts have a single one.
+                    let Some(table) = blocks(child.children).next() else { continue };
+                    if let Ok(translation) = u32::from_str_radix(&table.key, 16) {
+                        result.translation = Some(((translation >> 16) as u16, translation as u16));
+                    }
+
+                    for string in blocks(table.children) {
+                        if !string.is_text { continue }
+                        result.strings.push((string.key, utf16_bytes_to_string(string.value)));
+                    }
+                },
+                "VarFileInfo" => {
+                    if result.translation.is_some() { continue }
+                    let Some(var) = blocks(child.children).find(|var| var.key == "Translation") else { continue };
+                    if let (Some(language), Some(codepage)) = (read_u16(var.value, 0), read_u16(var.value, 2)) {
+                        result.translation = Some((language, codepage));
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        Some(result)
+    }
+
+    pub fn get_string(&self, key: &str) -> Option<&str> {
+        self.strings.iter()
+            .find(|(string_key, _)| string_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn read_version(bytes: &[u8], offset: usize) -> Option<[u16; 4]> {
+    let most_significant = read_u32(bytes, offset)?;
+    let least_significant = read_u32(bytes, offset + 4)?;
+    Some([
+        (most_significant >> 16) as u16,
+        most_significant as u16,
+        (least_significant >> 16) as u16,
+        least_significant as u16
+    ])
+}
+
+/// Reads a block and returns it along with the remaining bytes after it.
+fn read_block(bytes: &[u8]) -> Option<(Block<'_>, &[u8])> {
+    let length = read_u16(bytes, 0)? as usize;
+    let value_length = read_u16(bytes, 2)? as usize;
+    let is_text = read_u16(bytes, 4)? == 1;
+    if length < 6 { return None }
+    let block_bytes = bytes.get(..length)?;
+
+    let mut key_units = Vec::new();
+    let mut offset = 6;
+    loop {
+        let unit = read_u16(block_bytes, offset)?;
+        offset += 2;
+        if unit == 0 { break }
+        key_units.push(unit);
+    }
+
+    // Text values are measured in UTF-16 code units and include the null terminator.
+    let value_start = align4(offset);
+    let value_size = if is_text { value_length * 2 } else { value_length };
+    let value_end = (value_start + value_size).min(length);
+    let value = block_bytes.get(value_start.min(value_end)..value_end)?;
+    let children = block_bytes.get(align4(value_end).min(length)..)?;
+
+    let rest = bytes.get(align4(length).min(bytes.len())..)?;
+    Some((Block { key: String::from_utf16_lossy(&key_units), value, is_text, children }, rest))
+}
+
+fn blocks(mut bytes: &[u8]) -> impl Iterator<Item = Block<'_>> {
+    std::iter::from_fn(move || {
+        let (block, rest) = read_block(bytes)?;
+        bytes = rest;
+        Some(block)
+    })
+}
+
+fn utf16_bytes_to_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
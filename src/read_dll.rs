@@ -1,285 +1,619 @@
+// Reads keyboard layout DLLs by parsing their image rather than loading them,
+// so that layouts for any architecture can be read on any platform.
+
 use std::collections::{BTreeMap, HashMap};
-use std::mem::transmute;
-use std::ptr::null;
-use windows_sys::Win32::System::LibraryLoader::{LoadLibraryW, GetProcAddress};
+use indexmap::IndexMap;
+use std::path::Path;
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
 
 use crate::model::*;
+use crate::pe::{PeImage, read_u16, read_u32, read_u64};
+
+/// Why a layout DLL could not be read.
+#[derive(PartialEq, Eq, Debug)]
+pub enum ReadError {
+    /// The file could not be read.
+    Io(std::io::ErrorKind),
+    NotPeImage,
+    MissingDescriptor,
+    UnsupportedMachine(u16),
+    /// The code of KbdLayerDescriptor does not simply return the address of the tables.
+    UnrecognizedDescriptorCode,
+    /// A pointer or table row lies outside the sections of the image, given as a relative virtual address.
+    OutOfBounds { rva: u64 },
+    /// A table has no terminating row within the maximum number of rows.
+    TooManyRows { table: &'static str },
+    /// A string has no null terminator within the maximum length.
+    StringTooLong { rva: u64 },
+    /// A table's header contradicts its contents, such as rows too small for their characters.
+    InvalidTable { table: &'static str },
+    NoShiftState { mod_number: u8 },
+    /// A key types a dead key without being followed by the row of dead chars.
+    MissingDeadKeyRow { virtual_key: u8 },
+    /// A key with SGCAPS is not followed by the row of caps lock chars.
+    MissingCapsLockRow { virtual_key: u8 },
+    MissingLigature { virtual_key: u8 },
+}
 
-type FnKbdLayerDescriptor = extern "system" fn() -> *const KBDTABLES;
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(kind) => write!(f, "Failed to read layout DLL: {}.", kind),
+            Self::NotPeImage => write!(f, "Not a PE image."),
+            Self::MissingDescriptor => write!(f, "Missing KbdLayerDescriptor export."),
+            Self::UnsupportedMachine(machine) => write!(f, "Unsupported machine type {:04X}.", machine),
+            Self::UnrecognizedDescriptorCode => write!(f, "Unrecognized KbdLayerDescriptor code."),
+            Self::OutOfBounds { rva } => write!(f, "Address {:X} is outside the image.", rva),
+            Self::TooManyRows { table } => write!(f, "Table {} exceeds the maximum number of rows.", table),
+            Self::StringTooLong { rva } => write!(f, "String at {:X} exceeds the maximum length.", rva),
+            Self::InvalidTable { table } => write!(f, "Malformed {} table.", table),
+            Self::NoShiftState { mod_number } => write!(f, "No shift state for modification number {}.", mod_number),
+            Self::MissingDeadKeyRow { virtual_key } => write!(f, "Malformed virtual key to dead key mapping for virtual key {:02X}.", virtual_key),
+            Self::MissingCapsLockRow { virtual_key } => write!(f, "Malformed virtual key to caps lock mapping for virtual key {:02X}.", virtual_key),
+            Self::MissingLigature { virtual_key } => write!(f, "Missing ligature for virtual key {:02X}.", virtual_key),
+        }
+    }
+}
 
+impl std::error::Error for ReadError {}
 
-pub fn read_keyboard(path: String) -> KeyboardDesc {
-    unsafe {
-        let module = LoadLibraryW(string_to_rgwsz(path).as_ptr());
-        if module.is_null() {
-            panic!("Failed to load library");
-        }
+/// Bounds on how much of an image the reader walks, since layouts from untrusted sources
+/// may have tables without terminators or whose pointers loop back.
+pub struct ReadLimits {
+    /// The maximum number of rows of any table.
+    pub max_table_rows: usize,
+    /// The maximum length of key names, in UTF-16 code units.
+    pub max_string_length: usize,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        // Generous for real layouts, whose largest tables are dead key tables of a few thousand rows.
+        Self { max_table_rows: 0x10000, max_string_length: 0x400 }
+    }
+}
+
+impl ReadLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub fn read_keyboard(path: String, limits: &ReadLimits) -> Result<KeyboardDesc, ReadError> {
+    let bytes = std::fs::read(&path).map_err(|error| ReadError::Io(error.kind()))?;
+    let mut descriptor = read_keyboard_image(&bytes, limits)?;
+    if let Some(file_name) = Path::new(&path).file_name().and_then(|name| name.to_str()) {
+        descriptor.metadata.populate_from_known_layout(file_name);
+    }
+    descriptor.metadata.complete_locale();
+    Ok(descriptor)
+}
 
-        let proc: FnKbdLayerDescriptor = transmute(GetProcAddress(module, b"KbdLayerDescriptor\0".as_ptr()).unwrap());
-        let descriptor_ptr = proc();
+/// Reads a layout from the bytes of its DLL.
+pub fn read_keyboard_image(bytes: &[u8], limits: &ReadLimits) -> Result<KeyboardDesc, ReadError> {
+    let pe = PeImage::parse(bytes).ok_or(ReadError::NotPeImage)?;
+    let image = LayoutImage::new(pe, limits)?;
+    let tables = image.read_tables()?;
+
+    let mut descriptor = KeyboardDesc::new();
+    descriptor.physical_keys = read_physical_keys(&image, &tables)?;
+    descriptor.shift_states = read_shift_states(&image, &tables)?;
+    let mut char_tables = CharTableLayout::new();
+    descriptor.virtual_keys = read_virtual_keys(&image, &tables, &descriptor.shift_states, &mut char_tables)?;
+    descriptor.char_tables = Some(char_tables);
+    descriptor.dead_keys = read_dead_keys(&image, &tables)?;
+
+    let locale_flags = tables.locale_flags;
+    descriptor.version = (locale_flags >> 16) as u16;
+    descriptor.supports_altgr = (locale_flags & KLLF_ALTGR) != 0;
+    descriptor.supports_shift_lock = (locale_flags & KLLF_SHIFTLOCK) != 0;
+    descriptor.supports_directionality = (locale_flags & KLLF_LRM_RLM) != 0;
+
+    descriptor.type_value = tables.type_value;
+    descriptor.subtype_value = tables.subtype_value;
+
+    descriptor.metadata = read_metadata(&image.pe);
+    Ok(descriptor)
+}
 
-        let mut descriptor = KeyboardDesc::new();
-        descriptor.physical_keys = read_physical_keys(descriptor_ptr);
-        descriptor.virtual_keys = read_virtual_keys(descriptor_ptr);
-        descriptor.dead_keys = read_dead_keys(descriptor_ptr);
+/// The fields of KBDTABLES, with pointers as relative virtual addresses, or 0 if null.
+struct KbdTables {
+    char_modifiers: u64, // pCharModifiers
+    vk_to_wchar_table: u64, // pVkToWcharTable
+    dead_keys: u64, // pDeadKey
+    key_names: u64, // pKeyNames
+    key_names_ext: u64, // pKeyNamesExt
+    key_names_dead: u64, // pKeyNamesDead
+    vsc_to_vk: u64, // pusVSCtoVK
+    max_vsc_to_vk: u8, // bMaxVSCtoVK
+    vsc_to_vk_e0: u64, // pVSCtoVK_E0
+    vsc_to_vk_e1: u64, // pVSCtoVK_E1
+    locale_flags: u32, // fLocaleFlags
+    max_ligature: u8, // nLgMax
+    ligature_entry_size: u8, // cbLgEntry
+    ligatures: u64, // pLigature
+    type_value: u32, // dwType
+    subtype_value: u32, // dwSubType
+}
 
-        let locale_flags = descriptor_ptr.deref().fLocaleFlags;
-        descriptor.version = (locale_flags >> 16) as u16;
-        descriptor.supports_altgr = (locale_flags & KLLF_ALTGR) != 0;
-        descriptor.supports_shift_lock = (locale_flags & KLLF_SHIFTLOCK) != 0;
-        descriptor.supports_directionality = (locale_flags & KLLF_LRM_RLM) != 0;
+/// A layout DLL image, read as if loaded at its preferred address.
+/// Addresses are relative to the image base, so arithmetic on them cannot overflow,
+/// and every read is checked against the extents of the image's sections.
+struct LayoutImage<'a, 'b> {
+    pe: PeImage<'a>,
+    limits: &'b ReadLimits,
+    /// The size of the pointers in the layout tables, which is 8 for 64-bit images
+    /// but also for the 32-bit layouts of WOW64, built with KBD_LONG_POINTER.
+    pointer_size: u64,
+}
 
-        if descriptor_ptr.deref().nLgMax > 0 {
-            panic!("Ligatures are not implemented.")
+impl<'a, 'b> LayoutImage<'a, 'b> {
+    fn new(pe: PeImage<'a>, limits: &'b ReadLimits) -> Result<Self, ReadError> {
+        let pointer_size = if pe.is_pe32_plus { 8 } else { 4 };
+        let mut image = Self { pe, limits, pointer_size };
+        // With 64-bit pointers, the second dword of KBDTABLES is the upper half of pCharModifiers,
+        // which is always zero in a 32-bit image. Otherwise it is pVkToWcharTable, which is never null.
+        if !image.pe.is_pe32_plus && image.read_u32(image.find_tables()? + 4)? == 0 {
+            image.pointer_size = 8;
         }
+        Ok(image)
+    }
 
-        descriptor.type_value = descriptor_ptr.deref().dwType;
-        descriptor.subtype_value = descriptor_ptr.deref().dwSubType;
+    /// Finds the KBDTABLES structure by decoding the KbdLayerDescriptor function,
+    /// which only returns its address.
+    fn find_tables(&self) -> Result<u64, ReadError> {
+        let function_rva = self.pe.find_export("KbdLayerDescriptor").ok_or(ReadError::MissingDescriptor)?;
+
+        let machine = self.pe.machine;
+        // ARM exports have the low bit set to denote Thumb code.
+        let function_rva = if machine == LayoutMetadata::MACHINE_ARMNT { function_rva & !1 } else { function_rva };
+        let code = self.pe.slice_at_rva(function_rva).ok_or(ReadError::OutOfBounds { rva: function_rva as u64 })?;
+        let function = self.pe.image_base.wrapping_add(function_rva as u64);
+
+        let address = match machine {
+            LayoutMetadata::MACHINE_I386 => match code {
+                // mov eax, imm32, possibly after a mov edi, edi hotpatch prologue
+                [0xB8, ..] => read_u32(code, 1).map(u64::from),
+                [0x8B, 0xFF, 0xB8, ..] => read_u32(code, 3).map(u64::from),
+                _ => None
+            },
+            LayoutMetadata::MACHINE_AMD64 => match code {
+                // lea rax, [rip + disp32]
+                [0x48, 0x8D, 0x05, ..] => read_u32(code, 3).map(|disp| function.wrapping_add(7).wrapping_add_signed(disp as i32 as i64)),
+                // mov rax, imm64
+                [0x48, 0xB8, ..] => read_u64(code, 2),
+                _ => None
+            },
+            LayoutMetadata::MACHINE_ARM64 => decode_arm64_address(code, function),
+            LayoutMetadata::MACHINE_ARMNT => decode_thumb_address(code),
+            _ => return Err(ReadError::UnsupportedMachine(machine))
+        };
+
+        let address = address.ok_or(ReadError::UnrecognizedDescriptorCode)?;
+        let rva = self.to_rva(address)?;
+        if rva == 0 { return Err(ReadError::OutOfBounds { rva }) }
+        Ok(rva)
+    }
 
-        descriptor
+    fn read_tables(&self) -> Result<KbdTables, ReadError> {
+        let address = self.find_tables()?;
+        let pointer = |index: u64| self.read_ptr(address + index * self.pointer_size);
+        // The fields following the one-byte fields are aligned to the pointer size.
+        let (ligatures_offset, type_offset) = if self.pointer_size == 8 { (88, 96) } else { (48, 52) };
+        Ok(KbdTables {
+            char_modifiers: pointer(0)?,
+            vk_to_wchar_table: pointer(1)?,
+            dead_keys: pointer(2)?,
+            key_names: pointer(3)?,
+            key_names_ext: pointer(4)?,
+            key_names_dead: pointer(5)?,
+            vsc_to_vk: pointer(6)?,
+            max_vsc_to_vk: self.read_u8(address + 7 * self.pointer_size)?,
+            vsc_to_vk_e0: pointer(8)?,
+            vsc_to_vk_e1: pointer(9)?,
+            locale_flags: self.read_u32(address + 10 * self.pointer_size)?,
+            max_ligature: self.read_u8(address + 10 * self.pointer_size + 4)?,
+            ligature_entry_size: self.read_u8(address + 10 * self.pointer_size + 5)?,
+            ligatures: self.read_ptr(address + ligatures_offset)?,
+            type_value: self.read_u32(address + type_offset)?,
+            subtype_value: self.read_u32(address + type_offset + 4)?,
+        })
     }
-}
 
-unsafe fn read_physical_keys(descriptor_ptr: *const KBDTABLES) -> BTreeMap<ScanCode, PhysicalKeyDesc> {
-    let mut result: BTreeMap<ScanCode, PhysicalKeyDesc> = BTreeMap::new();
+    /// Converts a virtual address to a relative one, checking that it is in the image.
+    fn to_rva(&self, address: u64) -> Result<u64, ReadError> {
+        let rva = address.wrapping_sub(self.pe.image_base);
+        if u32::try_from(rva).is_err() { return Err(ReadError::OutOfBounds { rva }) }
+        Ok(rva)
+    }
 
-    unsafe {
-        for scan_code in 0..descriptor_ptr.deref().bMaxVSCtoVK {
-            let virtual_key_bits = descriptor_ptr.deref().pusVSCtoVK.offset(scan_code as isize).read();
-            let (virtual_key, virtual_key_flags) = VirtualKey::from_extended_bits(virtual_key_bits);
-            if virtual_key.code == 0xFF { continue }
-            result.insert(ScanCode::Unescaped(scan_code as u8), PhysicalKeyDesc {
-                virtual_key: virtual_key,
-                virtual_key_flags: virtual_key_flags,
-                name: None
-            });
-        }
+    /// Gets the bytes from an address up to the end of its section.
+    fn bytes_at(&self, rva: u64) -> Result<&'a [u8], ReadError> {
+        u32::try_from(rva).ok()
+            .and_then(|rva| self.pe.slice_at_rva(rva))
+            .ok_or(ReadError::OutOfBounds { rva })
+    }
 
-        for row_ptr in table(descriptor_ptr.deref().pVSCtoVK_E0, |row_ptr| row_ptr.deref().Vsc != 0) {
-            let (virtual_key, virtual_key_flags) = VirtualKey::from_extended_bits(row_ptr.deref().Vk);
-            result.insert(ScanCode::Extended0(row_ptr.deref().Vsc), PhysicalKeyDesc {
-                virtual_key: virtual_key,
-                virtual_key_flags: virtual_key_flags,
-                name: None
-            });
-        }
+    fn read_u8(&self, rva: u64) -> Result<u8, ReadError> {
+        self.bytes_at(rva)?.first().copied().ok_or(ReadError::OutOfBounds { rva })
+    }
 
-        for row_ptr in table(descriptor_ptr.deref().pVSCtoVK_E1, |row_ptr| row_ptr.deref().Vsc != 0) {
-            let (virtual_key, virtual_key_flags) = VirtualKey::from_extended_bits(row_ptr.deref().Vk);
-            result.insert(ScanCode::Extended1(row_ptr.deref().Vsc), PhysicalKeyDesc {
-                virtual_key: virtual_key,
-                virtual_key_flags: virtual_key_flags,
-                name: None
-            });
-        }
+    fn read_u16(&self, rva: u64) -> Result<u16, ReadError> {
+        read_u16(self.bytes_at(rva)?, 0).ok_or(ReadError::OutOfBounds { rva })
+    }
 
-        // Populate physical key names
-        for row_ptr in table(descriptor_ptr.deref().pKeyNames, |row_ptr| row_ptr.deref().vsc != 0) {
-            let Some(entry_ref) = result.get_mut(&ScanCode::Unescaped(row_ptr.deref().vsc)) else {
-                continue
-            };
-            entry_ref.name = Some(pwsz_to_string(row_ptr.deref().pwsz));
+    fn read_u32(&self, rva: u64) -> Result<u32, ReadError> {
+        read_u32(self.bytes_at(rva)?, 0).ok_or(ReadError::OutOfBounds { rva })
+    }
+
+    /// Reads a pointer as a relative virtual address, or 0 if it is null.
+    fn read_ptr(&self, rva: u64) -> Result<u64, ReadError> {
+        let bytes = self.bytes_at(rva)?;
+        let value = if self.pointer_size == 8 { read_u64(bytes, 0) } else { read_u32(bytes, 0).map(u64::from) };
+        match value.ok_or(ReadError::OutOfBounds { rva })? {
+            0 => Ok(0),
+            address => self.to_rva(address)
         }
+    }
 
-        for row_ptr in table(descriptor_ptr.deref().pKeyNamesExt, |row_ptr| row_ptr.deref().vsc != 0) {
-            let Some(entry_ref) = result.get_mut(&ScanCode::Extended0(row_ptr.deref().vsc)) else {
-                continue
-            };
-            entry_ref.name = Some(pwsz_to_string(row_ptr.deref().pwsz));
+    /// Reads a null-terminated UTF-16 string, which must end within its section and the maximum length.
+    fn read_string(&self, rva: u64) -> Result<String, ReadError> {
+        let mut chars = Vec::new();
+        for bytes in self.bytes_at(rva)?.chunks_exact(2) {
+            let char = u16::from_le_bytes([bytes[0], bytes[1]]);
+            if char == 0 { return Ok(String::from_utf16_lossy(&chars)) }
+            if chars.len() == self.limits.max_string_length { return Err(ReadError::StringTooLong { rva }) }
+            chars.push(char);
         }
+        Err(ReadError::OutOfBounds { rva: rva + chars.len() as u64 * 2 })
     }
+}
 
-    result
+/// Decodes `adrp x0, page; add x0, x0, offset; ret`.
+fn decode_arm64_address(code: &[u8], function: u64) -> Option<u64> {
+    let adrp = read_u32(code, 0)?;
+    let add = read_u32(code, 4)?;
+    if adrp & 0x9F00_001F != 0x9000_0000 || add & 0xFFC0_03FF != 0x9100_0000 { return None }
+
+    let page_bits = (((adrp >> 5) & 0x7_FFFF) << 2) | ((adrp >> 29) & 0x3);
+    let page_offset = ((page_bits as i64) << 43 >> 43) << 12; // Sign-extend the 21-bit page count
+    let offset = ((add >> 10) & 0xFFF) as u64;
+    Some((function & !0xFFF).wrapping_add_signed(page_offset) + offset)
 }
 
-unsafe fn read_virtual_keys(descriptor_ptr: *const KBDTABLES) -> HashMap<VirtualKey, KeyEffect> {
-    let mut result: HashMap<VirtualKey, KeyEffect> = HashMap::new();
+/// Decodes `movw r0, low; movt r0, high; bx lr` in Thumb-2.
+fn decode_thumb_address(code: &[u8]) -> Option<u64> {
+    let immediate = |offset: usize, opcode: u16| -> Option<u32> {
+        let first = read_u16(code, offset)?;
+        let second = read_u16(code, offset + 2)?;
+        if first & 0xFBF0 != opcode || second & 0x8F00 != 0 { return None }
+        let (i, imm4) = ((first >> 10) & 1, first & 0xF);
+        let (imm3, imm8) = ((second >> 12) & 0x7, second & 0xFF);
+        Some(((imm4 as u32) << 12) | ((i as u32) << 11) | ((imm3 as u32) << 8) | imm8 as u32)
+    };
+    let low = immediate(0, 0xF240)?;
+    let high = immediate(4, 0xF2C0)?;
+    Some(((high << 16) | low) as u64)
+}
 
-    unsafe {
-        // Populate modifier virtual keys
-        let modifiers_ptr = descriptor_ptr.deref().pCharModifiers;
-        for row_ptr in table(modifiers_ptr.deref().pVkToBit, |row_ptr| row_ptr.deref().Vk != 0) {
-            result.insert(VirtualKey { code: row_ptr.deref().Vk }, KeyEffect::Modifier(KeyModifiers::from_bits(row_ptr.deref().ModBits)));
-        }
+fn read_physical_keys(image: &LayoutImage, tables: &KbdTables) -> Result<BTreeMap<ScanCode, PhysicalKeyDesc>, ReadError> {
+    let mut result: BTreeMap<ScanCode, PhysicalKeyDesc> = BTreeMap::new();
 
-        // Build modification number -> modifiers mapping
-        let mut mod_numbers_to_mods: HashMap<u8, KeyModifiers> = HashMap::new();
-        for modifier_bits in 0..(modifiers_ptr.deref().wMaxModBits + 1) {
-            let mod_number = modifiers_ptr.deref().ModNumber.as_ptr().add(modifier_bits as usize).read();
-            if mod_number as u32 == SHFT_INVALID { continue }
-            mod_numbers_to_mods.insert(mod_number, KeyModifiers::from_bits(modifier_bits as u8));
-        }
+    for scan_code in 0..tables.max_vsc_to_vk {
+        let virtual_key_bits = image.read_u16(tables.vsc_to_vk + scan_code as u64 * 2)?;
+        let (virtual_key, virtual_key_flags) = VirtualKey::from_extended_bits(virtual_key_bits);
+        if virtual_key.code == 0xFF { continue }
+        result.insert(ScanCode::Unescaped(scan_code), PhysicalKeyDesc {
+            virtual_key,
+            virtual_key_flags,
+            name: None
+        });
+    }
+
+    // VSC_VK rows: Vsc, then Vk at offset 2.
+    for row in table(image, "pVSCtoVK_E0", tables.vsc_to_vk_e0, 4, |image, row| Ok(image.read_u8(row)? != 0)) {
+        let row = row?;
+        let (virtual_key, virtual_key_flags) = VirtualKey::from_extended_bits(image.read_u16(row + 2)?);
+        result.insert(ScanCode::Extended0(image.read_u8(row)?), PhysicalKeyDesc {
+            virtual_key,
+            virtual_key_flags,
+            name: None
+        });
+    }
+
+    for row in table(image, "pVSCtoVK_E1", tables.vsc_to_vk_e1, 4, |image, row| Ok(image.read_u8(row)? != 0)) {
+        let row = row?;
+        let (virtual_key, virtual_key_flags) = VirtualKey::from_extended_bits(image.read_u16(row + 2)?);
+        result.insert(ScanCode::Extended1(image.read_u8(row)?), PhysicalKeyDesc {
+            virtual_key,
+            virtual_key_flags,
+            name: None
+        });
+    }
+
+    // Populate physical key names, from VSC_LPWSTR rows: vsc, then pwsz at the next pointer boundary.
+    let name_row_size = 2 * image.pointer_size;
+    for row in table(image, "pKeyNames", tables.key_names, name_row_size, |image, row| Ok(image.read_u8(row)? != 0)) {
+        let row = row?;
+        let Some(entry_ref) = result.get_mut(&ScanCode::Unescaped(image.read_u8(row)?)) else {
+            continue
+        };
+        entry_ref.name = Some(image.read_string(image.read_ptr(row + image.pointer_size)?)?);
+    }
 
-        // Populate virtual keys which type stuff
-        for tables_row_ptr in table(descriptor_ptr.deref().pVkToWcharTable, |row_ptr| !row_ptr.deref().pVkToWchars.is_null()) {
-            let key_mod_count = tables_row_ptr.deref().nModifications;
-            let mut table_row_iterator = strided_table(
-                tables_row_ptr.deref().pVkToWchars,
-                tables_row_ptr.deref().cbSize as usize,
-                |row_ptr| row_ptr.deref().VirtualKey != 0);
-            loop {
-                let Some(table_row_ptr) = table_row_iterator.next() else { break };
-
-                // Read attributes
-                let attribute_bits = table_row_ptr.deref().Attributes as u32;
-                let mut key_typing = KeyTyping {
-                    by_modifiers: HashMap::new(),
-                    caps_lock_as_shift: (attribute_bits & CAPLOK) != 0,
-                    caps_lock_as_uppercase: (attribute_bits & SGCAPS) != 0,
-                    caps_lock_altgr_as_shift: (attribute_bits & CAPLOKALTGR) != 0,
-                    kana_support: (attribute_bits & KANALOK) != 0,
-                    grpseltap_support: (attribute_bits & GRPSELTAP) != 0,
-                };
-
-                let chars_ptr = table_row_ptr.deref().wch.as_ptr();
-
-                // Read chars for each modifier
-                let mut dead_row_chars_ptr: *const u16 = null();
+    for row in table(image, "pKeyNamesExt", tables.key_names_ext, name_row_size, |image, row| Ok(image.read_u8(row)? != 0)) {
+        let row = row?;
+        let Some(entry_ref) = result.get_mut(&ScanCode::Extended0(image.read_u8(row)?)) else {
+            continue
+        };
+        entry_ref.name = Some(image.read_string(image.read_ptr(row + image.pointer_size)?)?);
+    }
+
+    Ok(result)
+}
+
+fn read_shift_states(image: &LayoutImage, tables: &KbdTables) -> Result<ShiftStateTable, ReadError> {
+    // MODIFIERS: pVkToBit, then wMaxModBits and the ModNumber array.
+    let modifiers = tables.char_modifiers;
+    let max_mod_bits = image.read_u16(modifiers + image.pointer_size)?;
+    // Modifier bits are a byte wide, so a larger maximum cannot come from a real layout.
+    if max_mod_bits > 0xFF { return Err(ReadError::InvalidTable { table: "pCharModifiers" }) }
+    let columns = (0..(max_mod_bits as u64 + 1))
+        .map(|modifier_bits| image.read_u8(modifiers + image.pointer_size + 2 + modifier_bits))
+        .map(|mod_number| mod_number.map(|mod_number| if mod_number as u32 == SHFT_INVALID { None } else { Some(mod_number) }))
+        .collect::<Result<_, _>>()?;
+    Ok(ShiftStateTable::from_columns(columns))
+}
+
+fn read_virtual_keys(image: &LayoutImage, tables: &KbdTables, shift_states: &ShiftStateTable, char_tables: &mut CharTableLayout) -> Result<IndexMap<VirtualKey, KeyEffect>, ReadError> {
+    let mut result: IndexMap<VirtualKey, KeyEffect> = IndexMap::new();
+
+    // Populate modifier virtual keys, from VK_TO_BIT rows
+    let vk_to_bits = image.read_ptr(tables.char_modifiers)?;
+    for row in table(image, "pVkToBit", vk_to_bits, 2, |image, row| Ok(image.read_u8(row)? != 0)) {
+        let row = row?;
+        result.insert(VirtualKey { code: image.read_u8(row)? }, KeyEffect::Modifier(KeyModifiers::from_bits(image.read_u8(row + 1)?)));
+    }
+
+    let ligatures = read_ligatures(image, tables)?;
+
+    // Populate virtual keys which type stuff, from VK_TO_WCHAR_TABLE rows: pVkToWchars, nModifications and cbSize.
+    let pointer_size = image.pointer_size;
+    for tables_row in table(image, "pVkToWcharTable", tables.vk_to_wchar_table, 2 * pointer_size, |image, row| Ok(image.read_ptr(row)? != 0)) {
+        let tables_row = tables_row?;
+        let key_mod_count = image.read_u8(tables_row + pointer_size)?;
+        let entry_size = image.read_u8(tables_row + pointer_size + 1)?;
+        if (entry_size as usize) < 2 + 2 * key_mod_count as usize { return Err(ReadError::InvalidTable { table: "pVkToWchars" }) }
+        let group = char_tables.groups.len();
+        char_tables.groups.push(CharTableGroup {
+            modifications: key_mod_count,
+            entry_size,
+        });
+
+        // VK_TO_WCHARSn rows: VirtualKey, Attributes, then the chars.
+        let mut table_row_iterator = table(
+            image,
+            "pVkToWchars",
+            image.read_ptr(tables_row)?,
+            entry_size as u64,
+            |image, row| Ok(image.read_u8(row)? != 0));
+        while let Some(table_row) = table_row_iterator.next() {
+            let table_row = table_row?;
+            let virtual_key = image.read_u8(table_row)?;
+
+            // Read attributes
+            let attribute_bits = image.read_u8(table_row + 1)? as u32;
+            let mut key_typing = KeyTyping {
+                by_modifiers: IndexMap::new(),
+                caps_lock_as_shift: (attribute_bits & CAPLOK) != 0,
+                caps_lock_as_uppercase: (attribute_bits & SGCAPS) != 0,
+                caps_lock_layer: IndexMap::new(),
+                caps_lock_altgr_as_shift: (attribute_bits & CAPLOKALTGR) != 0,
+                kana_support: (attribute_bits & KANALOK) != 0,
+                grpseltap_support: (attribute_bits & GRPSELTAP) != 0,
+                group: Some(group),
+            };
+
+            // With SGCAPS, the key's row is followed by a row of chars typed with caps lock on.
+            let mut layers = vec![(table_row, &mut key_typing.by_modifiers)];
+            if key_typing.caps_lock_as_uppercase {
+                let caps_row = table_row_iterator.next().transpose()?
+                    .ok_or(ReadError::MissingCapsLockRow { virtual_key })?;
+                if image.read_u8(caps_row)? != 0xFF { return Err(ReadError::MissingCapsLockRow { virtual_key }) }
+                layers.push((caps_row, &mut key_typing.caps_lock_layer));
+            }
+
+            // Read chars for each modifier
+            let mut dead_row: Option<u64> = None;
+            for (row, layer) in layers {
                 for mod_number in 0..key_mod_count {
-                    let char = chars_ptr.add(mod_number as usize).read();
+                    let char = image.read_u16(row + 2 + mod_number as u64 * 2)?;
                     if char as u32 == WCH_NONE { continue }
 
-                    let modifiers = mod_numbers_to_mods[&mod_number];
+                    let all_modifiers = shift_states.modifiers(mod_number);
+                    if all_modifiers.is_empty() {
+                        return Err(ReadError::NoShiftState { mod_number })
+                    }
 
-                    if char as u32 == WCH_DEAD {
+                    let typing_effect = if char as u32 == WCH_DEAD {
                         // Read the dead row if we haven't already
-                        if dead_row_chars_ptr.is_null() {
-                            let dead_row_ptr = table_row_iterator.next();
-                            if dead_row_ptr.is_none() || dead_row_ptr.unwrap().deref().VirtualKey != 0xFF {
-                                panic!("Malformed virtual key to dead key mapping.")
+                        let dead_row = match dead_row {
+                            Some(dead_row) => dead_row,
+                            None => {
+                                let row = table_row_iterator.next().transpose()?
+                                    .ok_or(ReadError::MissingDeadKeyRow { virtual_key })?;
+                                if image.read_u8(row)? != 0xFF { return Err(ReadError::MissingDeadKeyRow { virtual_key }) }
+                                *dead_row.insert(row)
                             }
+                        };
 
-                            dead_row_chars_ptr = dead_row_ptr.unwrap().deref().wch.as_ptr()
-                        }
-
-                        let dead_char = dead_row_chars_ptr.add(mod_number as usize).read();
-                        key_typing.by_modifiers.insert(modifiers, TypingEffect::DeadKey(dead_char));
-                        continue
+                        let dead_char = image.read_u16(dead_row + 2 + mod_number as u64 * 2)?;
+                        TypingEffect::DeadKey(WideChar::from_u16(dead_char))
                     }
-
-                    if char as u32 == WCH_LGTR {
-                        panic!("Ligatures are not implemented.")
+                    else if char as u32 == WCH_LGTR {
+                        let Some(ligature) = ligatures.get(&(virtual_key, mod_number)) else {
+                            return Err(ReadError::MissingLigature { virtual_key })
+                        };
+                        ligature_to_typing_effect(ligature)
                     }
+                    else {
+                        TypingEffect::Char(WideChar::from_u16(char))
+                    };
 
-                    key_typing.by_modifiers.insert(modifiers, TypingEffect::Char(char));
+                    // Every combination of modifiers sharing the column types the same.
+                    for modifiers in all_modifiers {
+                        layer.insert(modifiers, typing_effect.clone());
+                    }
                 }
-
-                result.insert(VirtualKey { code: table_row_ptr.deref().VirtualKey }, KeyEffect::Typing(key_typing));
             }
+
+            result.insert(VirtualKey { code: virtual_key }, KeyEffect::Typing(key_typing));
         }
     }
 
-    result
+    Ok(result)
 }
 
-pub fn read_dead_keys(descriptor_ptr: *const KBDTABLES) -> HashMap<u16, DeadKeyDesc> {
-    let mut result: HashMap<u16, DeadKeyDesc> = HashMap::new();
+/// Reads the ligature table, keyed by virtual key and modification number.
+fn read_ligatures(image: &LayoutImage, tables: &KbdTables) -> Result<HashMap<(u8, u8), Vec<u16>>, ReadError> {
+    let mut result: HashMap<(u8, u8), Vec<u16>> = HashMap::new();
+
+    let max_length = tables.max_ligature as u64;
+    if max_length == 0 { return Ok(result) }
+    if (tables.ligature_entry_size as u64) < 4 + 2 * max_length { return Err(ReadError::InvalidTable { table: "pLigature" }) }
+
+    // LIGATUREn rows: VirtualKey, ModificationNumber at offset 2, then the chars.
+    for row in table(image, "pLigature", tables.ligatures, tables.ligature_entry_size as u64, |image, row| Ok(image.read_u8(row)? != 0)) {
+        let row = row?;
+        let mut chars = Vec::new();
+        for index in 0..max_length {
+            let char = image.read_u16(row + 4 + index * 2)?;
+            if char as u32 == WCH_NONE { break }
+            chars.push(char);
+        }
+        result.insert((image.read_u8(row)?, image.read_u16(row + 2)? as u8), chars);
+    }
 
-    unsafe {
-        // Populate dead key combos
-        for row_ptr in table(descriptor_ptr.deref().pDeadKey, |row_ptr| row_ptr.deref().wchComposed != 0) {
-            let accent_and_base_char = row_ptr.deref().dwBoth;
-            let base_char = (accent_and_base_char & 0xFFFF) as u16;
-            let accent_char = (accent_and_base_char >> 16) as u16;
+    Ok(result)
+}
 
-            let dead_key = result.entry(accent_char)
-                .or_insert(DeadKeyDesc { name: None, combos: HashMap::new() });
+/// Ligatures are the only way to type characters outside the BMP,
+/// so those are decoded as single characters.
+fn ligature_to_typing_effect(chars: &[u16]) -> TypingEffect {
+    let ligature = WideString::from_utf16(chars);
+    match ligature.0.as_slice() {
+        [WideChar::Scalar(char)] if chars.len() == 2 => TypingEffect::Char(WideChar::Scalar(*char)),
+        _ => TypingEffect::Ligature(ligature)
+    }
+}
 
-            dead_key.combos.insert(base_char, DeadKeyCombo {
-                composed_char: row_ptr.deref().wchComposed,
-                flags: row_ptr.deref().uFlags,
-            });
+fn read_dead_keys(image: &LayoutImage, tables: &KbdTables) -> Result<IndexMap<WideChar, DeadKeyDesc>, ReadError> {
+    let mut result: IndexMap<WideChar, DeadKeyDesc> = IndexMap::new();
+
+    // Populate dead key combos, from DEADKEY rows: dwBoth, wchComposed and uFlags.
+    for row in table(image, "pDeadKey", tables.dead_keys, 8, |image, row| Ok(image.read_u16(row + 4)? != 0)) {
+        let row = row?;
+        let accent_and_base_char = image.read_u32(row)?;
+        let base_char = WideChar::from_u16((accent_and_base_char & 0xFFFF) as u16);
+        let accent_char = WideChar::from_u16((accent_and_base_char >> 16) as u16);
+
+        let dead_key = result.entry(accent_char)
+            .or_insert(DeadKeyDesc { name: None, combos: IndexMap::new(), combo_flags: IndexMap::new() });
+
+        let composed_char = WideChar::from_u16(image.read_u16(row + 4)?);
+        let flags = image.read_u16(row + 6)?;
+        dead_key.combos.insert(base_char, if (flags as u32 & DKF_DEAD) != 0 {
+            DeadKeyCombo::DeadKey(composed_char)
+        } else {
+            DeadKeyCombo::Char(composed_char)
+        });
+        let other_flags = flags & !(DKF_DEAD as u16);
+        if other_flags != 0 {
+            dead_key.combo_flags.insert(base_char, other_flags);
         }
+    }
 
-        // Populate dead key names
-        for row_ptr in table(descriptor_ptr.deref().pKeyNamesDead, |row_ptr| !row_ptr.deref().is_null()) {
-            let pwsz = row_ptr.deref();
-            let accent_char = pwsz.read();
-            let name = pwsz_to_string(pwsz.add(1));
+    // Populate dead key names, from strings starting with the dead char.
+    for row in table(image, "pKeyNamesDead", tables.key_names_dead, image.pointer_size, |image, row| Ok(image.read_ptr(row)? != 0)) {
+        let pwsz = image.read_ptr(row?)?;
+        let accent_char = WideChar::from_u16(image.read_u16(pwsz)?);
+        let name = image.read_string(pwsz + 2)?;
 
-            let dead_key = result.entry(accent_char)
-                .or_insert(DeadKeyDesc { name: None, combos: HashMap::new() });
-            dead_key.name = Some(name);
-        }
+        let dead_key = result.entry(accent_char)
+            .or_insert(DeadKeyDesc { name: None, combos: IndexMap::new(), combo_flags: IndexMap::new() });
+        dead_key.name = Some(name);
     }
 
-    result
+    Ok(result)
 }
 
-trait PtrDeref<T>{
-    unsafe fn deref<'x>(self) -> &'x T;
+fn read_metadata(image: &PeImage) -> LayoutMetadata {
+    let mut metadata = LayoutMetadata::new();
+    metadata.timestamp = Some(image.timestamp);
+    metadata.machine = Some(image.machine);
+    metadata.display_name_resource = image.read_string_resource(LayoutMetadata::DISPLAY_NAME_RESOURCE_ID);
+
+    let Some(version_info) = image.read_version_info() else { return metadata };
+    metadata.display_name = version_info.get_string("FileDescription").map(str::to_owned);
+    metadata.company_name = version_info.get_string("CompanyName").map(str::to_owned);
+    metadata.file_version = version_info.get_string("FileVersion").map(str::to_owned)
+        .or(version_info.fixed_file_version.map(version_to_string));
+    metadata.product_version = version_info.get_string("ProductVersion").map(str::to_owned)
+        .or(version_info.fixed_product_version.map(version_to_string));
+    if let Some((language, codepage)) = version_info.translation {
+        metadata.language = Some(language);
+        metadata.codepage = Some(codepage);
+    }
+
+    metadata
 }
 
-impl<T> PtrDeref<T> for *const T{
-    unsafe fn deref<'x>(self) -> &'x T{
-        unsafe { &*self }
-    }
+fn version_to_string(version: [u16; 4]) -> String {
+    format!("{}.{}.{}.{}", version[0], version[1], version[2], version[3])
 }
 
-struct TableIterator<V> {
-    row: *const V,
-    first: bool,
-    stride: usize,
-    predicate: fn(*const V) -> bool,
+/// Iterates the addresses of the rows of a table ending with a sentinel row,
+/// for which the predicate is false. A null table has no rows.
+/// Iteration ends after the first error, including when the table has too many rows.
+struct TableIterator<'a, 'b, 'c> {
+    image: &'c LayoutImage<'a, 'b>,
+    name: &'static str,
+    row: u64,
+    stride: u64,
+    rows: usize,
+    predicate: fn(&LayoutImage, u64) -> Result<bool, ReadError>,
 }
 
-impl<V> Iterator for TableIterator<V> {
-    type Item = *const V;
+impl Iterator for TableIterator<'_, '_, '_> {
+    type Item = Result<u64, ReadError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            if self.row.is_null() {
-                return None;
-            }
-
-            if !(self.predicate)(self.row) {
-                return None;
-            }
-
-            if self.first {
-                self.first = false;
-                return Some(self.row)
-            }
+        if self.row == 0 {
+            return None;
+        }
 
-            self.row = self.row.byte_add(self.stride);
-            if !(self.predicate)(self.row) {
-                return None;
+        let row = self.row;
+        self.row = 0;
+        match (self.predicate)(self.image, row) {
+            Ok(false) => None,
+            Err(error) => Some(Err(error)),
+            Ok(true) if self.rows == self.image.limits.max_table_rows => Some(Err(ReadError::TooManyRows { table: self.name })),
+            Ok(true) => {
+                self.rows += 1;
+                self.row = row + self.stride;
+                Some(Ok(row))
             }
-
-            Some(self.row)
         }
     }
 }
 
-fn table<V>(start: *const V, predicate: fn(*const V) -> bool) -> TableIterator<V> {
+fn table<'a, 'b, 'c>(image: &'c LayoutImage<'a, 'b>, name: &'static str, start: u64, stride: u64, predicate: fn(&LayoutImage, u64) -> Result<bool, ReadError>) -> TableIterator<'a, 'b, 'c> {
     TableIterator {
+        image,
+        name,
         row: start,
-        first: true,
-        stride: size_of::<V>(),
+        stride,
+        rows: 0,
         predicate
     }
 }
-
-fn strided_table<V>(start: *const V, stride: usize, predicate: fn(*const V) -> bool) -> TableIterator<V> {
-    TableIterator {
-        row: start,
-        first: true,
-        stride: stride,
-        predicate
-    }
-}
-
-fn string_to_rgwsz(str: String) -> Vec<u16> {
-    let mut utf16: Vec<u16> = str.encode_utf16().collect();
-    utf16.push(0);
-    return utf16
-}
-
-unsafe fn pwsz_to_string(ptr: *const u16) -> String {
-    unsafe {
-        let len = (0..).take_while(|&i| *ptr.offset(i) != 0).count();
-        let slice = std::slice::from_raw_parts(ptr, len);
-        String::from_utf16_lossy(slice)
-    }
-}
@@ -0,0 +1,259 @@
+// Simulates the characters Windows produces as keys are pressed with a given layout,
+// following the logic of ToUnicodeEx over the layout tables.
+
+mod to_unicode;
+mod trace;
+
+use std::collections::BTreeSet;
+use crate::model::*;
+
+pub use to_unicode::*;
+pub use trace::*;
+
+/// How keys pressed at given times are chorded with thumb shift keys. Windows itself
+/// only applies thumb shift modifiers while their keys are held, but NICOLA input
+/// also chords a character key with a thumb key pressed shortly after it.
+#[derive(Clone, Copy)]
+pub struct ThumbShiftTiming {
+    /// How long a character key waits for a thumb key to chord with, in milliseconds.
+    pub chord_window: u32,
+}
+
+impl Default for ThumbShiftTiming {
+    fn default() -> Self {
+        Self { chord_window: 100 }
+    }
+}
+
+impl ThumbShiftTiming {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub struct TypingSimulator<'a> {
+    keyboard: &'a KeyboardDesc,
+    pressed_keys: BTreeSet<VirtualKey>,
+    caps_lock: bool,
+    kana_lock: bool,
+    kana_toggle_keys: Vec<VirtualKey>,
+    /// The pending dead key, if any, which combines with the next character typed.
+    dead_key: Option<WideChar>,
+    thumb_shift_keys: ThumbShiftKeys,
+    thumb_shift_timing: Option<ThumbShiftTiming>,
+    /// The character key waiting for a thumb key to chord with, and when it was pressed.
+    pending_key: Option<(VirtualKey, u32)>,
+}
+
+impl<'a> TypingSimulator<'a> {
+    pub fn new(keyboard: &'a KeyboardDesc) -> Self {
+        Self {
+            keyboard,
+            pressed_keys: BTreeSet::new(),
+            caps_lock: false,
+            kana_lock: false,
+            kana_toggle_keys: keyboard.kana_toggle_keys(),
+            dead_key: None,
+            thumb_shift_keys: keyboard.thumb_shift_keys(),
+            thumb_shift_timing: None,
+            pending_key: None,
+        }
+    }
+
+    /// Enables chording character keys with thumb shift keys pressed after them, for the timed key events.
+    pub fn set_thumb_shift_timing(&mut self, timing: Option<ThumbShiftTiming>) { self.thumb_shift_timing = timing; }
+
+    pub fn caps_lock(&self) -> bool { self.caps_lock }
+    pub fn set_caps_lock(&mut self, value: bool) { self.caps_lock = value; }
+
+    pub fn kana_lock(&self) -> bool { self.kana_lock }
+    pub fn set_kana_lock(&mut self, value: bool) { self.kana_lock = value; }
+
+    pub fn dead_key(&self) -> Option<WideChar> { self.dead_key }
+
+    /// Presses a key and returns the characters it produces.
+    pub fn key_down(&mut self, virtual_key: VirtualKey) -> WideString {
+        self.pressed_keys.insert(virtual_key);
+
+        if virtual_key == VirtualKey::CAPS_LOCK {
+            // With KLLF_SHIFTLOCK, Caps Lock only turns on and Shift turns it off.
+            self.caps_lock = self.keyboard.supports_shift_lock || !self.caps_lock;
+        }
+        else if self.keyboard.supports_shift_lock && self.caps_lock && virtual_key.generic_modifier() == VirtualKey::SHIFT {
+            self.caps_lock = false;
+        }
+
+        if self.kana_toggle_keys.contains(&virtual_key) {
+            self.kana_lock = !self.kana_lock;
+        }
+
+        let is_right_shift_held = self.pressed_keys.contains(&VirtualKey::RIGHT_SHIFT);
+        if let Some(mark) = directional_mark(self.keyboard, virtual_key, &self.modifiers(), is_right_shift_held) {
+            return self.apply_typing_effect(&TypingEffect::Char(mark))
+        }
+
+        let Some(KeyEffect::Typing(key_typing)) = self.keyboard.virtual_keys.get(&virtual_key) else {
+            return WideString(Vec::new())
+        };
+
+        match typing_effect(self.keyboard, key_typing, &self.modifiers(), self.kana_lock, self.caps_lock) {
+            Some(typing_effect) => self.apply_typing_effect(typing_effect),
+            None => WideString(Vec::new())
+        }
+    }
+
+    pub fn key_up(&mut self, virtual_key: VirtualKey) {
+        self.pressed_keys.remove(&virtual_key);
+    }
+
+    /// Presses and releases a key, returning the characters it produces.
+    pub fn type_key(&mut self, virtual_key: VirtualKey) -> WideString {
+        let result = self.key_down(virtual_key);
+        self.key_up(virtual_key);
+        result
+    }
+
+    /// Presses a key at the given time, in milliseconds, returning the characters produced by then.
+    /// With thumb shift timing, a character key pressed while no thumb key is held waits
+    /// for one until the chord window elapses, so its characters may come with a later event.
+    pub fn key_down_at(&mut self, virtual_key: VirtualKey, time: u32) -> WideString {
+        let mut result = self.advance_to(time);
+        if self.thumb_shift_timing.is_none() {
+            result.0.extend(self.key_down(virtual_key).0);
+            return result
+        }
+
+        if self.thumb_shift_keys.contains(&virtual_key) {
+            // The key waiting for a thumb key chords with this one.
+            result.0.extend(self.key_down(virtual_key).0);
+            if let Some((pending_key, _)) = self.pending_key.take() {
+                result.0.extend(self.key_down(pending_key).0);
+            }
+            return result
+        }
+
+        result.0.extend(self.flush_pending_key().0);
+        let is_typing_key = matches!(self.keyboard.virtual_keys.get(&virtual_key), Some(KeyEffect::Typing(_)));
+        let is_thumb_held = self.pressed_keys.iter().any(|pressed_key| self.thumb_shift_keys.contains(pressed_key));
+        if is_typing_key && !is_thumb_held {
+            self.pending_key = Some((virtual_key, time));
+        }
+        else {
+            result.0.extend(self.key_down(virtual_key).0);
+        }
+        result
+    }
+
+    /// Releases a key at the given time, in milliseconds, returning the characters produced by then.
+    pub fn key_up_at(&mut self, virtual_key: VirtualKey, time: u32) -> WideString {
+        let mut result = self.advance_to(time);
+        // A key released before any thumb key was pressed types on its own.
+        if self.pending_key.is_some_and(|(pending_key, _)| pending_key == virtual_key) {
+            result.0.extend(self.flush_pending_key().0);
+        }
+        self.key_up(virtual_key);
+        result
+    }
+
+    /// Lets time pass until the given time, in milliseconds, returning the characters
+    /// of a key whose chord window elapsed meanwhile.
+    pub fn advance_to(&mut self, time: u32) -> WideString {
+        let (Some(timing), Some((_, pressed_time))) = (self.thumb_shift_timing, self.pending_key) else {
+            return WideString(Vec::new())
+        };
+        if time.saturating_sub(pressed_time) <= timing.chord_window {
+            return WideString(Vec::new())
+        }
+        self.flush_pending_key()
+    }
+
+    /// Types the key waiting for a thumb key without one.
+    fn flush_pending_key(&mut self) -> WideString {
+        match self.pending_key.take() {
+            Some((pending_key, _)) => self.key_down(pending_key),
+            None => WideString(Vec::new())
+        }
+    }
+
+    /// Gets the modifiers currently active from the pressed modifier keys.
+    /// The kana modifier comes from kana lock instead, for the keys supporting it.
+    pub fn modifiers(&self) -> KeyModifiers {
+        let mut bits = 0u8;
+        for virtual_key in &self.pressed_keys {
+            for candidate in [*virtual_key, virtual_key.generic_modifier()] {
+                if let Some(KeyEffect::Modifier(key_modifiers)) = self.keyboard.virtual_keys.get(&candidate) {
+                    bits |= key_modifiers.to_bits();
+                    break;
+                }
+            }
+
+            // Windows synthesizes a left control press along with right alt (AltGr).
+            if self.keyboard.supports_altgr && *virtual_key == VirtualKey::RIGHT_ALT
+                && let Some(KeyEffect::Modifier(key_modifiers)) = self.keyboard.virtual_keys.get(&VirtualKey::CONTROL) {
+                bits |= key_modifiers.to_bits();
+            }
+        }
+
+        KeyModifiers { kana: false, ..KeyModifiers::from_bits(bits) }
+    }
+
+    fn apply_typing_effect(&mut self, typing_effect: &TypingEffect) -> WideString {
+        let (result, dead_key) = compose(self.keyboard, self.dead_key.take(), typing_effect);
+        self.dead_key = dead_key;
+        result
+    }
+}
+
+/// Gets the directional mark replacing what Shift+Backspace types, depending on the shift key held.
+fn directional_mark(keyboard: &KeyboardDesc, virtual_key: VirtualKey, modifiers: &KeyModifiers, is_right_shift_held: bool) -> Option<WideChar> {
+    if virtual_key != VirtualKey::BACKSPACE || *modifiers != (KeyModifiers { shift: true, ..KeyModifiers::from_bits(0) }) {
+        return None
+    }
+    let shift_key = if is_right_shift_held { VirtualKey::RIGHT_SHIFT } else { VirtualKey::LEFT_SHIFT };
+    keyboard.directional_marks().into_iter().find(|(key, _)| *key == shift_key).map(|(_, mark)| mark)
+}
+
+/// Gets what a key types with the modifiers held, kana lock and caps lock.
+fn typing_effect<'a>(keyboard: &KeyboardDesc, key_typing: &'a KeyTyping, modifiers: &KeyModifiers, kana_lock: bool, caps_lock: bool) -> Option<&'a TypingEffect> {
+    let modifiers = key_typing.kana_lock_modifiers(modifiers, kana_lock);
+
+    // Combinations marked SHFT_INVALID type nothing, even if a key maps them.
+    let shift_states = &keyboard.shift_states;
+    if !shift_states.is_empty() && shift_states.is_invalid(&modifiers) {
+        return None
+    }
+
+    key_typing.effect(&modifiers, caps_lock)
+}
+
+/// Gets what typing a key produces after a pending dead key, if any, and the dead key pending afterwards.
+fn compose(keyboard: &KeyboardDesc, dead_key: Option<WideChar>, typing_effect: &TypingEffect) -> (WideString, Option<WideChar>) {
+    let Some(dead_key) = dead_key else {
+        return match typing_effect {
+            TypingEffect::Char(char) => (WideString(vec![*char]), None),
+            TypingEffect::DeadKey(dead_key) => (WideString(Vec::new()), Some(*dead_key)),
+            TypingEffect::Ligature(ligature) => (ligature.clone(), None)
+        }
+    };
+
+    // A pending dead key combines with the character of the next key,
+    // possibly entering another dead key state (DKF_DEAD).
+    let base_char = match typing_effect {
+        TypingEffect::Char(char) | TypingEffect::DeadKey(char) => Some(*char),
+        TypingEffect::Ligature(_) => None
+    };
+    let combo = base_char.and_then(|base_char| keyboard.dead_keys.get(&dead_key)?.combos.get(&base_char));
+    match combo {
+        Some(DeadKeyCombo::Char(composed_char)) => (WideString(vec![*composed_char]), None),
+        Some(DeadKeyCombo::DeadKey(next_dead_key)) => (WideString(Vec::new()), Some(*next_dead_key)),
+        None => {
+            // Without a combo, the dead key's character is typed followed by the key's own.
+            let mut result = vec![dead_key];
+            match typing_effect {
+                TypingEffect::Char(char) | TypingEffect::DeadKey(char) => result.push(*char),
+                TypingEffect::Ligature(ligature) => result.extend(ligature.0.iter().copied())
+            }
+            (WideString(result), None)
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// Reproduces ToUnicodeEx over a layout, for interpreting key events recorded on Windows:
+// the key state array, the dead key state kept between calls and the return values.
+
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use crate::model::*;
+use super::{compose, directional_mark, typing_effect};
+
+/// The key state bit of keys held down.
+const KEY_DOWN: u8 = 0x80;
+/// The key state bit of toggle keys turned on.
+const KEY_TOGGLED: u8 = 0x01;
+/// The scan code bit of key releases.
+const SCAN_CODE_KEY_UP: u32 = 0x8000;
+/// The flag asking ToUnicodeEx not to change the keyboard state, which here is the pending dead key.
+pub const TO_UNICODE_KEEP_STATE: u32 = 0x04;
+
+/// Translates virtual keys like ToUnicodeEx, keeping the pending dead key between calls as Windows does per thread.
+pub struct UnicodeTranslator<'a> {
+    keyboard: &'a KeyboardDesc,
+    dead_key: Option<WideChar>,
+}
+
+impl<'a> UnicodeTranslator<'a> {
+    pub fn new(keyboard: &'a KeyboardDesc) -> Self {
+        Self { keyboard, dead_key: None }
+    }
+
+    pub fn dead_key(&self) -> Option<WideChar> { self.dead_key }
+
+    /// Translates a key press, returning the UTF-16 units written and the count ToUnicodeEx returns:
+    /// the number of units typed, -1 for a dead key with its spacing character written, or 0 for nothing.
+    /// A pending dead key followed by a key it does not combine with gives both characters.
+    /// Key releases, with bit 15 of the scan code set, type nothing.
+    pub fn to_unicode_ex(&mut self, virtual_key: u32, scan_code: u32, key_state: &[u8; 256], flags: u32) -> (i32, Vec<u16>) {
+        let Ok(code) = u8::try_from(virtual_key) else { return (0, Vec::new()) };
+        if (scan_code & SCAN_CODE_KEY_UP) != 0 {
+            return (0, Vec::new())
+        }
+
+        let modifiers = self.modifiers(key_state);
+        let is_right_shift_held = (key_state[VK_RSHIFT as usize] & KEY_DOWN) != 0;
+        let mark_effect;
+        let typing_effect = match directional_mark(self.keyboard, VirtualKey { code }, &modifiers, is_right_shift_held) {
+            Some(mark) => {
+                mark_effect = TypingEffect::Char(mark);
+                &mark_effect
+            },
+            None => {
+                let Some(KeyEffect::Typing(key_typing)) = self.keyboard.virtual_keys.get(&VirtualKey { code }) else {
+                    return (0, Vec::new())
+                };
+                let kana_lock = (key_state[VK_KANA as usize] & KEY_TOGGLED) != 0;
+                let caps_lock = (key_state[VK_CAPITAL as usize] & KEY_TOGGLED) != 0;
+                let Some(typing_effect) = typing_effect(self.keyboard, key_typing, &modifiers, kana_lock, caps_lock) else {
+                    return (0, Vec::new())
+                };
+                typing_effect
+            }
+        };
+
+        let (result, dead_key) = compose(self.keyboard, self.dead_key, typing_effect);
+        if (flags & TO_UNICODE_KEEP_STATE) == 0 {
+            self.dead_key = dead_key;
+        }
+        match dead_key {
+            Some(dead_key) => (-1, dead_key.to_utf16()),
+            None => {
+                let units = result.to_utf16();
+                (units.len() as i32, units)
+            }
+        }
+    }
+
+    /// Gets the modifiers of the modifier keys held down, with the kana modifier coming from kana lock instead.
+    fn modifiers(&self, key_state: &[u8; 256]) -> KeyModifiers {
+        let mut bits = 0u8;
+        for (virtual_key, key_effect) in &self.keyboard.virtual_keys {
+            let KeyEffect::Modifier(key_modifiers) = key_effect else { continue };
+            if (key_state[virtual_key.code as usize] & KEY_DOWN) != 0 {
+                bits |= key_modifiers.to_bits();
+            }
+        }
+        KeyModifiers { kana: false, ..KeyModifiers::from_bits(bits) }
+    }
+}
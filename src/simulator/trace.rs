@@ -0,0 +1,162 @@
+// Parses and runs keystroke traces, which describe layout tests as data: key events by scan code,
+// each checked against the text they should type. A trace is a text file with a step per line:
+//
+//     # Comments and blank lines are ignored.
+//     layout kbdtest           The layout the trace tests, for runners finding layouts by name.
+//     thumb-shift-timing 100   Chords thumb shift keys pressed within 100 ms after a character key.
+//     down E0 38               Presses the key with this scan code, in hex with its E0/E1 prefix, or by name.
+//     up E0 38 @120            Releases it, optionally at a time in milliseconds.
+//     type 1E                  Presses and releases a key.
+//     expect "â"               Checks the text typed since the previous check, as a JSON string.
+//
+// Text typed after the last check must be empty.
+
+use std::collections::BTreeMap;
+use crate::model::*;
+use super::{ThumbShiftTiming, TypingSimulator};
+
+pub enum TraceStep {
+    KeyDown { scan_code: ScanCode, time: Option<u32> },
+    KeyUp { scan_code: ScanCode, time: Option<u32> },
+    Expect(String),
+}
+
+pub struct Trace {
+    /// The name of the layout the trace tests, if given.
+    pub layout: Option<String>,
+    pub thumb_shift_timing: Option<ThumbShiftTiming>,
+    /// The steps with their line number.
+    pub steps: Vec<(usize, TraceStep)>,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct TraceError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+/// A check of a trace which failed, or the text typed after the last check.
+#[derive(PartialEq, Eq, Debug)]
+pub struct TraceMismatch {
+    pub line: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl std::fmt::Display for TraceMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: expected {:?}, typed {:?}", self.line, self.expected, self.actual)
+    }
+}
+
+impl Trace {
+    pub fn parse(text: &str) -> Result<Trace, TraceError> {
+        let mut trace = Trace { layout: None, thumb_shift_timing: None, steps: Vec::new() };
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |message: String| TraceError { line: line_number, message };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+
+            let (keyword, arguments) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let arguments = arguments.trim();
+            match keyword {
+                "layout" if !arguments.is_empty() => trace.layout = Some(arguments.to_string()),
+                "thumb-shift-timing" => {
+                    let chord_window = arguments.parse().map_err(|_| error(format!("Invalid chord window: {}", arguments)))?;
+                    trace.thumb_shift_timing = Some(ThumbShiftTiming { chord_window });
+                },
+                "down" | "up" | "type" => {
+                    let (scan_code, time) = parse_key_event(arguments).map_err(error)?;
+                    if keyword != "up" {
+                        trace.steps.push((line_number, TraceStep::KeyDown { scan_code, time }));
+                    }
+                    if keyword != "down" {
+                        trace.steps.push((line_number, TraceStep::KeyUp { scan_code, time }));
+                    }
+                },
+                "expect" => {
+                    let expected = serde_json::from_str(arguments).map_err(|_| error(format!("Invalid expected text: {}", arguments)))?;
+                    trace.steps.push((line_number, TraceStep::Expect(expected)));
+                },
+                _ => return Err(error(format!("Unknown step: {}", line)))
+            }
+        }
+        Ok(trace)
+    }
+
+    /// Types the trace's key events with a layout, returning the checks which failed.
+    /// Scan codes resolve to virtual keys as Windows does, following the modifiers held and Num Lock.
+    pub fn run(&self, keyboard: &KeyboardDesc) -> Vec<TraceMismatch> {
+        let mut simulator = TypingSimulator::new(keyboard);
+        simulator.set_thumb_shift_timing(self.thumb_shift_timing);
+        let mut pressed_keys = BTreeMap::new();
+        let mut num_lock = false;
+        let mut current_time = 0;
+        let mut typed = Vec::new();
+        let mut mismatches = Vec::new();
+
+        for (line, step) in &self.steps {
+            match step {
+                TraceStep::KeyDown { scan_code, time } => {
+                    current_time = time.unwrap_or(current_time);
+                    let Some(virtual_key) = keyboard.resolve_virtual_key(*scan_code, &simulator.modifiers(), num_lock) else { continue };
+                    if virtual_key == VirtualKey::NUM_LOCK {
+                        num_lock = !num_lock;
+                    }
+                    // Keys released under other modifiers release the virtual key they pressed.
+                    pressed_keys.insert(*scan_code, virtual_key);
+                    typed.extend(simulator.key_down_at(virtual_key, current_time).0);
+                },
+                TraceStep::KeyUp { scan_code, time } => {
+                    current_time = time.unwrap_or(current_time);
+                    if let Some(virtual_key) = pressed_keys.remove(scan_code) {
+                        typed.extend(simulator.key_up_at(virtual_key, current_time).0);
+                    }
+                },
+                TraceStep::Expect(expected) => {
+                    let actual = WideString(std::mem::take(&mut typed)).to_string_lossy();
+                    if actual != *expected {
+                        mismatches.push(TraceMismatch { line: *line, expected: expected.clone(), actual });
+                    }
+                }
+            }
+        }
+
+        typed.extend(simulator.advance_to(u32::MAX).0);
+        if !typed.is_empty() {
+            let line = self.steps.last().map_or(0, |(line, _)| *line);
+            mismatches.push(TraceMismatch { line, expected: String::new(), actual: WideString(typed).to_string_lossy() });
+        }
+        mismatches
+    }
+}
+
+/// Parses a scan code, as hex with an optional E0/E1 prefix word or by name, followed by an optional @time.
+fn parse_key_event(arguments: &str) -> Result<(ScanCode, Option<u32>), String> {
+    let mut words: Vec<&str> = arguments.split_whitespace().collect();
+    let time = match words.last().and_then(|word| word.strip_prefix('@')) {
+        Some(time) => {
+            words.pop();
+            Some(time.parse().map_err(|_| format!("Invalid time: {}", time))?)
+        },
+        None => None
+    };
+
+    let parse_code = |word: &str| u8::from_str_radix(word, 16).ok().filter(|code| *code < 0x80);
+    let scan_code = match words[..] {
+        ["E0", code] => parse_code(code).map(ScanCode::Extended0),
+        ["E1", code] => parse_code(code).map(ScanCode::Extended1),
+        [code] => parse_code(code).map(ScanCode::Unescaped).or_else(|| ScanCode::from_name(code)),
+        _ => None
+    };
+    scan_code.map(|scan_code| (scan_code, time)).ok_or_else(|| format!("Invalid scan code: {}", words.join(" ")))
+}
@@ -0,0 +1,295 @@
+// Renders a layout as an SVG diagram of its physical keyboard,
+// showing the characters each key types on the common modifier layers.
+
+use std::fmt::Write;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use crate::model::*;
+
+/// The size of a 1u key, in pixels.
+const KEY_UNIT: f32 = 54.0;
+/// The space between adjacent keys, in pixels.
+const KEY_SPACING: f32 = 4.0;
+const MARGIN: f32 = 16.0;
+const TITLE_HEIGHT: f32 = 28.0;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SvgLayer {
+    Base,
+    Shift,
+    AltGr,
+    ShiftAltGr,
+    /// The left thumb shift layer of NICOLA layouts (KBDLOYA).
+    LeftThumb,
+    /// The right thumb shift layer of NICOLA layouts (KBDROYA).
+    RightThumb,
+}
+
+impl SvgLayer {
+    pub const ALL: [SvgLayer; 6] = [Self::Base, Self::Shift, Self::AltGr, Self::ShiftAltGr, Self::LeftThumb, Self::RightThumb];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Base => "base",
+            Self::Shift => "shift",
+            Self::AltGr => "altgr",
+            Self::ShiftAltGr => "shift-altgr",
+            Self::LeftThumb => "left-thumb",
+            Self::RightThumb => "right-thumb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|layer| layer.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn modifiers(&self) -> KeyModifiers {
+        let bits = match self {
+            Self::Base => 0,
+            Self::Shift => KBDSHIFT,
+            Self::AltGr => KBDCTRL | KBDALT,
+            Self::ShiftAltGr => KBDSHIFT | KBDCTRL | KBDALT,
+            Self::LeftThumb => KBDLOYA,
+            Self::RightThumb => KBDROYA,
+        };
+        KeyModifiers::from_bits(bits as u8)
+    }
+
+    /// Where the layer's character sits on the key cap, as fractions of its width and height.
+    /// This follows the usual engraving: shifted characters on top, AltGr characters on the right.
+    /// NICOLA keys have no AltGr characters, and show their thumb shift characters on the right instead.
+    fn anchor(&self) -> (f32, f32) {
+        match self {
+            Self::Base => (0.25, 0.75),
+            Self::Shift => (0.25, 0.35),
+            Self::AltGr | Self::RightThumb => (0.72, 0.75),
+            Self::ShiftAltGr | Self::LeftThumb => (0.72, 0.35),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SvgTheme {
+    pub background: String,
+    pub key_fill: String,
+    pub key_stroke: String,
+    pub text: String,
+    /// The fill of keys having a dead key on any of the layers shown.
+    pub dead_key_fill: String,
+    pub dead_key_text: String,
+    /// The colour of physical key names and other secondary text.
+    pub label_text: String,
+}
+
+impl SvgTheme {
+    pub fn light() -> Self {
+        Self {
+            background: "#ffffff".to_string(),
+            key_fill: "#f4f4f4".to_string(),
+            key_stroke: "#9a9a9a".to_string(),
+            text: "#202020".to_string(),
+            dead_key_fill: "#fde9c8".to_string(),
+            dead_key_text: "#c0392b".to_string(),
+            label_text: "#707070".to_string(),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            background: "#1e1e1e".to_string(),
+            key_fill: "#2d2d2d".to_string(),
+            key_stroke: "#5a5a5a".to_string(),
+            text: "#e8e8e8".to_string(),
+            dead_key_fill: "#4a3b20".to_string(),
+            dead_key_text: "#ff8a65".to_string(),
+            label_text: "#a0a0a0".to_string(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "light" => Some(Self::light()),
+            "dark" => Some(Self::dark()),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SvgOptions {
+    /// The modifier layers to show on each key.
+    pub layers: Vec<SvgLayer>,
+    pub font_family: String,
+    pub theme: SvgTheme,
+    /// Whether to append a diagram per dead key showing the characters it composes.
+    pub dead_key_diagrams: bool,
+    /// Whether to show the characters typed with caps lock on.
+    pub caps_lock: bool,
+    /// Whether to show the characters typed with kana lock on, by keys supporting it (KANALOK).
+    pub kana_lock: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            layers: SvgLayer::ALL.to_vec(),
+            font_family: "Segoe UI, Arial, sans-serif".to_string(),
+            theme: SvgTheme::light(),
+            dead_key_diagrams: false,
+            caps_lock: false,
+            kana_lock: false,
+        }
+    }
+}
+
+impl SvgOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// What a key cap shows on one layer.
+enum KeyLabel {
+    Char(String),
+    DeadKey(String),
+}
+
+impl KeyboardDesc {
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let geometry = KeyboardGeometry::new(self.geometry_kind());
+        let diagram_height = TITLE_HEIGHT + geometry.height() * KEY_UNIT;
+
+        // The main diagram, followed by one per dead key if requested.
+        let mut diagrams: Vec<(String, Option<&DeadKeyDesc>)> = vec![(self.title(), None)];
+        if options.dead_key_diagrams {
+            for (dead_char, dead_key) in self.sorted_dead_keys() {
+                let dead_char_text = display_char(*dead_char).unwrap_or_default();
+                let title = match &dead_key.name {
+                    Some(name) => format!("Dead key {} ({})", dead_char_text, name),
+                    None => format!("Dead key {}", dead_char_text),
+                };
+                diagrams.push((title, Some(dead_key)));
+            }
+        }
+
+        let width = geometry.width() * KEY_UNIT + 2.0 * MARGIN;
+        let height = diagrams.len() as f32 * (diagram_height + MARGIN) + MARGIN;
+
+        let mut svg = String::new();
+        writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" data-geometry=\"{}\">",
+            width, height, width, height, geometry.kind.name()).unwrap();
+        writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", options.theme.background).unwrap();
+        writeln!(svg, "<g font-family=\"{}\" text-anchor=\"middle\">", escape(&options.font_family)).unwrap();
+
+        for (index, (title, dead_key)) in diagrams.iter().enumerate() {
+            let top = MARGIN + index as f32 * (diagram_height + MARGIN);
+            writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"18\" text-anchor=\"start\" fill=\"{}\">{}</text>",
+                MARGIN, top + 18.0, options.theme.text, escape(title)).unwrap();
+            for key in &geometry.keys {
+                self.write_key(&mut svg, key, MARGIN, top + TITLE_HEIGHT, *dead_key, options);
+            }
+        }
+
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+
+    /// The name to show for the layout in diagrams and documents.
+    pub(crate) fn title(&self) -> String {
+        self.metadata.layout_text.clone()
+            .or_else(|| self.metadata.display_name.clone())
+            .or_else(|| self.metadata.layout_file.clone())
+            .unwrap_or_else(|| "Keyboard layout".to_string())
+    }
+
+    fn write_key(&self, svg: &mut String, key: &KeyGeometry, left: f32, top: f32, dead_key: Option<&DeadKeyDesc>, options: &SvgOptions) {
+        let theme = &options.theme;
+        let x = left + key.x * KEY_UNIT + KEY_SPACING / 2.0;
+        let y = top + key.y * KEY_UNIT + KEY_SPACING / 2.0;
+        let width = key.width * KEY_UNIT - KEY_SPACING;
+        let height = key.height * KEY_UNIT - KEY_SPACING;
+
+        let labels: Vec<(SvgLayer, KeyLabel)> = options.layers.iter()
+            .filter_map(|layer| self.key_label(key.scan_code, *layer, dead_key, options).map(|label| (*layer, label)))
+            .collect();
+        let has_dead_key = labels.iter().any(|(_, label)| matches!(label, KeyLabel::DeadKey(_)));
+        let fill = if has_dead_key { &theme.dead_key_fill } else { &theme.key_fill };
+
+        match key.shape {
+            KeyShape::Rectangle => {
+                writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{}\" stroke=\"{}\"/>",
+                    x, y, width, height, fill, theme.key_stroke).unwrap();
+            },
+            KeyShape::IsoEnter => {
+                let inset = KeyboardGeometry::ISO_ENTER_INSET * KEY_UNIT;
+                let top_height = KEY_UNIT - KEY_SPACING;
+                writeln!(svg, "<path d=\"M{},{} H{} V{} H{} V{} H{} Z\" fill=\"{}\" stroke=\"{}\" stroke-linejoin=\"round\"/>",
+                    x, y, x + width, y + height, x + inset, y + top_height, x, fill, theme.key_stroke).unwrap();
+            },
+        }
+
+        // The label area, which for the ISO Enter is its top part.
+        let label_height = if key.shape == KeyShape::IsoEnter { KEY_UNIT - KEY_SPACING } else { height };
+        if labels.is_empty() {
+            // Keys which don't type anything on the layers shown get their name, if any.
+            if dead_key.is_some() { return; }
+            let Some(name) = self.physical_keys.get(&key.scan_code).and_then(|physical_key| physical_key.name.as_ref()) else {
+                return
+            };
+            writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"9\" fill=\"{}\">{}</text>",
+                x + width / 2.0, y + label_height / 2.0 + 3.0, theme.label_text, escape(name)).unwrap();
+            return;
+        }
+
+        for (layer, label) in labels {
+            let (anchor_x, anchor_y) = layer.anchor();
+            let (text, color) = match &label {
+                KeyLabel::Char(text) => (text, &theme.text),
+                KeyLabel::DeadKey(text) => (text, &theme.dead_key_text),
+            };
+            writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"16\" fill=\"{}\">{}</text>",
+                x + width.min(KEY_UNIT) * anchor_x, y + label_height * anchor_y + 5.0, color, escape(text)).unwrap();
+        }
+    }
+
+    /// Gets what a key shows on a layer, or the result of combining it with a dead key.
+    fn key_label(&self, scan_code: ScanCode, layer: SvgLayer, dead_key: Option<&DeadKeyDesc>, options: &SvgOptions) -> Option<KeyLabel> {
+        let physical_key = self.physical_keys.get(&scan_code)?;
+        let Some(KeyEffect::Typing(key_typing)) = self.virtual_keys.get(&physical_key.virtual_key) else {
+            return None
+        };
+
+        let modifiers = key_typing.kana_lock_modifiers(&layer.modifiers(), options.kana_lock);
+        let typing_effect = key_typing.effect(&modifiers, options.caps_lock)?;
+        let label = match typing_effect {
+            TypingEffect::Char(char) => KeyLabel::Char(display_char(*char)?),
+            TypingEffect::DeadKey(char) => KeyLabel::DeadKey(display_char(*char)?),
+            TypingEffect::Ligature(string) => KeyLabel::Char(string.to_string_lossy()),
+        };
+
+        let Some(dead_key) = dead_key else { return Some(label) };
+        let base_char = match typing_effect {
+            TypingEffect::Char(char) | TypingEffect::DeadKey(char) => *char,
+            TypingEffect::Ligature(_) => return None,
+        };
+        match dead_key.combos.get(&base_char)? {
+            DeadKeyCombo::Char(char) => Some(KeyLabel::Char(display_char(*char)?)),
+            DeadKeyCombo::DeadKey(char) => Some(KeyLabel::DeadKey(display_char(*char)?)),
+        }
+    }
+}
+
+/// Gets the text to engrave for a character, or None for control characters and spaces.
+fn display_char(wide_char: WideChar) -> Option<String> {
+    match wide_char {
+        WideChar::Scalar(char) if char.is_control() || char == ' ' => None,
+        // Combining marks are shown on a dotted circle, as is customary.
+        WideChar::Scalar(char) if wide_char.is_combining_mark() => Some(format!("\u{25CC}{}", char)),
+        WideChar::Scalar(char) if char.is_whitespace() => Some(format!("U+{:04X}", char as u32)),
+        WideChar::Scalar(char) => Some(char.to_string()),
+        WideChar::Surrogate(unit) => Some(format!("\\u{:04X}", unit)),
+    }
+}
+
+fn escape(str: &str) -> String {
+    str.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,175 @@
+// Dumps a layout as compact tables, in Markdown or aligned plain text,
+// ordered deterministically so that diffs between dumps are meaningful.
+
+use std::collections::BTreeSet;
+use crate::model::*;
+
+/// Suffix marking dead keys in table cells, as in MSKLC's .klc files.
+const DEAD_KEY_MARKER: &str = "@";
+
+struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn to_markdown(&self) -> String {
+        let escape = |cell: &String| cell.replace('\\', "\\\\").replace('|', "\\|").replace('`', "\\`");
+        let mut markdown = String::new();
+        markdown.push_str(&format!("| {} |\n", self.headers.iter().map(escape).collect::<Vec<_>>().join(" | ")));
+        markdown.push_str(&format!("|{}\n", "---|".repeat(self.headers.len())));
+        for row in &self.rows {
+            markdown.push_str(&format!("| {} |\n", row.iter().map(escape).collect::<Vec<_>>().join(" | ")));
+        }
+        markdown
+    }
+
+    fn to_text(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|header| header.chars().count()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let format_row = |cells: &Vec<String>| {
+            let padded: Vec<String> = cells.iter().zip(&widths)
+                .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+                .collect();
+            format!("{}\n", padded.join("  ").trim_end())
+        };
+
+        let mut text = format_row(&self.headers);
+        text.push_str(&format_row(&widths.iter().map(|width| "-".repeat(*width)).collect()));
+        for row in &self.rows {
+            text.push_str(&format_row(row));
+        }
+        text
+    }
+}
+
+impl KeyboardDesc {
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n\n", self.title());
+        markdown.push_str(&self.keys_table().to_markdown());
+        for (heading, table) in self.extra_tables() {
+            markdown.push_str(&format!("\n## {}\n\n", heading));
+            markdown.push_str(&table.to_markdown());
+        }
+        markdown
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n\n", self.title());
+        text.push_str(&self.keys_table().to_text());
+        for (heading, table) in self.extra_tables() {
+            text.push_str(&format!("\n{}\n\n", heading));
+            text.push_str(&table.to_text());
+        }
+        text
+    }
+
+    /// One row per typing virtual key, in scan code order, with a column per modifier combination in use,
+    /// followed by a column per combination in use on caps lock layers (SGCAPS).
+    fn keys_table(&self) -> Table {
+        let mut modifier_bits = BTreeSet::new();
+        let mut caps_lock_modifier_bits = BTreeSet::new();
+        for key_effect in self.virtual_keys.values() {
+            let KeyEffect::Typing(key_typing) = key_effect else { continue };
+            modifier_bits.extend(key_typing.by_modifiers.keys().map(|modifiers| modifiers.to_bits()));
+            caps_lock_modifier_bits.extend(key_typing.caps_lock_layer.keys().map(|modifiers| modifiers.to_bits()));
+        }
+        let modifiers: Vec<KeyModifiers> = modifier_bits.into_iter().map(KeyModifiers::from_bits).collect();
+        let caps_lock_modifiers: Vec<KeyModifiers> = caps_lock_modifier_bits.into_iter().map(KeyModifiers::from_bits).collect();
+
+        let mut headers = vec!["Scan code".to_string(), "VK".to_string()];
+        headers.extend(modifiers.iter().map(|modifiers| match modifiers.describe(self.supports_altgr) {
+            description if description.is_empty() => "Base".to_string(),
+            description => description
+        }));
+        headers.extend(caps_lock_modifiers.iter().map(|modifiers| match modifiers.describe(self.supports_altgr) {
+            description if description.is_empty() => "Caps".to_string(),
+            description => format!("Caps+{}", description)
+        }));
+
+        // Keys reachable from a scan code come first, in scan code order,
+        // followed by any other typing keys in virtual key order.
+        let mut keys: Vec<(Option<ScanCode>, VirtualKey)> = self.physical_keys.iter()
+            .map(|(scan_code, physical_key)| (Some(*scan_code), physical_key.virtual_key))
+            .collect();
+        let unmapped_keys: Vec<VirtualKey> = self.sorted_virtual_keys()
+            .map(|(virtual_key, _)| *virtual_key)
+            .filter(|virtual_key| !keys.iter().any(|(_, mapped_key)| mapped_key == virtual_key))
+            .collect();
+        keys.extend(unmapped_keys.into_iter().map(|virtual_key| (None, virtual_key)));
+
+        let mut rows = Vec::new();
+        let mut listed_keys = BTreeSet::new();
+        for (scan_code, virtual_key) in keys {
+            let Some(KeyEffect::Typing(key_typing)) = self.virtual_keys.get(&virtual_key) else { continue };
+            if !listed_keys.insert(virtual_key) { continue; }
+            let mut row = vec![
+                scan_code.map(|scan_code| format!("{:?}", scan_code)).unwrap_or_default(),
+                format!("{:?}", virtual_key),
+            ];
+            row.extend(modifiers.iter().map(|modifiers| effect_cell(key_typing.by_modifiers.get(modifiers))));
+            row.extend(caps_lock_modifiers.iter().map(|modifiers| effect_cell(key_typing.caps_lock_layer.get(modifiers))));
+            rows.push(row);
+        }
+
+        Table { headers, rows }
+    }
+
+    /// The tables following the keys table, with their heading.
+    fn extra_tables(&self) -> Vec<(String, Table)> {
+        let mut tables = Vec::new();
+        let directional_marks = self.directional_marks();
+        if !directional_marks.is_empty() {
+            // Layouts flag these keystrokes rather than listing them, so spell them out.
+            let rows = directional_marks.iter()
+                .map(|(shift_key, mark)| vec![format!("{:?}+{:?}", shift_key, VirtualKey::BACKSPACE), char_cell(*mark)])
+                .collect();
+            tables.push(("Directional marks".to_string(), Table { headers: vec!["Keys".to_string(), "Result".to_string()], rows }));
+        }
+        tables.extend(self.dead_key_tables());
+        tables
+    }
+
+    fn dead_key_tables(&self) -> Vec<(String, Table)> {
+        self.sorted_dead_keys().map(|(dead_char, dead_key)| {
+            let heading = match &dead_key.name {
+                Some(name) => format!("Dead key {} ({})", char_cell(*dead_char), name),
+                None => format!("Dead key {}", char_cell(*dead_char)),
+            };
+
+            let rows = dead_key.sorted_combos().map(|(base_char, combo)| vec![
+                char_cell(*base_char),
+                match combo {
+                    DeadKeyCombo::Char(char) => char_cell(*char),
+                    DeadKeyCombo::DeadKey(char) => format!("{}{}", char_cell(*char), DEAD_KEY_MARKER),
+                },
+            ]).collect();
+
+            (heading, Table { headers: vec!["Base".to_string(), "Result".to_string()], rows })
+        }).collect()
+    }
+}
+
+fn effect_cell(typing_effect: Option<&TypingEffect>) -> String {
+    match typing_effect {
+        Some(TypingEffect::Char(char)) => char_cell(*char),
+        Some(TypingEffect::DeadKey(char)) => format!("{}{}", char_cell(*char), DEAD_KEY_MARKER),
+        Some(TypingEffect::Ligature(string)) => string.0.iter().map(|char| char_cell(*char)).collect(),
+        None => String::new()
+    }
+}
+
+/// Formats a character for a table cell, spelling out those which would be invisible or misaligned.
+fn char_cell(wide_char: WideChar) -> String {
+    match wide_char {
+        WideChar::Scalar(char) if char.is_control() || char.is_whitespace() || wide_char.is_combining_mark() || wide_char.is_invisible_format()
+            || char.to_string() == DEAD_KEY_MARKER => format!("U+{:04X}", char as u32),
+        WideChar::Scalar(char) => char.to_string(),
+        WideChar::Surrogate(unit) => format!("U+{:04X}", unit),
+    }
+}
@@ -0,0 +1,740 @@
+// Assembles minimal keyboard layout DLL images for the reader to parse, either from a model
+// or from raw tables which tests can corrupt to reach the reader's error paths.
+// The images only contain what the reader needs: headers, an export table,
+// the code of KbdLayerDescriptor, the layout tables, and version and string resources.
+
+use std::collections::HashMap;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use kbdc::model::*;
+
+const SECTION_RVA: u32 = 0x1000;
+const HEADERS_SIZE: usize = 0x400;
+const FILE_ALIGNMENT: usize = 0x200;
+const RT_STRING: u32 = 6;
+const RT_VERSION: u32 = 16;
+
+/// The architecture of an image, which determines how it stores pointers and returns the tables.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Machine {
+    X86,
+    /// A 32-bit image with the 64-bit pointers of KBD_LONG_POINTER, as in SysWOW64.
+    Wow64,
+    X64,
+    Arm64,
+    ArmNt,
+}
+
+impl Machine {
+    pub const ALL: [Machine; 5] = [Self::X86, Self::Wow64, Self::X64, Self::Arm64, Self::ArmNt];
+
+    pub fn value(&self) -> u16 {
+        match self {
+            Self::X86 | Self::Wow64 => LayoutMetadata::MACHINE_I386,
+            Self::X64 => LayoutMetadata::MACHINE_AMD64,
+            Self::Arm64 => LayoutMetadata::MACHINE_ARM64,
+            Self::ArmNt => LayoutMetadata::MACHINE_ARMNT,
+        }
+    }
+
+    fn is_pe32_plus(&self) -> bool {
+        matches!(self, Self::X64 | Self::Arm64)
+    }
+
+    fn pointer_size(&self) -> usize {
+        match self {
+            Self::X86 | Self::ArmNt => 4,
+            Self::Wow64 | Self::X64 | Self::Arm64 => 8,
+        }
+    }
+
+    fn image_base(&self) -> u64 {
+        if self.is_pe32_plus() { 0x1_8000_0000 } else { 0x1000_0000 }
+    }
+}
+
+/// A VK_TO_WCHARS table.
+pub struct RawCharTable {
+    pub modifications: u8, // nModifications
+    pub entry_size: u8, // cbSize
+    /// The rows, without the terminating one. Chars are truncated or padded with zeroes to the entry size.
+    pub rows: Vec<RawCharRow>,
+}
+
+pub struct RawCharRow {
+    pub virtual_key: u8,
+    pub attributes: u8,
+    pub chars: Vec<u16>,
+}
+
+pub struct RawLigature {
+    pub virtual_key: u8,
+    pub modification_number: u16,
+    pub chars: Vec<u16>,
+}
+
+pub struct RawDeadKey {
+    pub accent: u16,
+    pub base: u16,
+    pub composed: u16,
+    pub flags: u16,
+}
+
+/// The tables of a layout DLL as they are stored, along with how the image exposes them.
+pub struct RawLayout {
+    pub machine: Machine,
+    pub vsc_to_vk: Vec<u16>, // pusVSCtoVK and bMaxVSCtoVK
+    pub vsc_to_vk_e0: Vec<(u8, u16)>,
+    pub vsc_to_vk_e1: Vec<(u8, u16)>,
+    pub vk_to_bits: Vec<(u8, u8)>,
+    pub mod_numbers: Vec<u8>, // ModNumber, of wMaxModBits + 1 entries
+    pub char_tables: Vec<RawCharTable>,
+    pub max_ligature: u8, // nLgMax
+    pub ligature_entry_size: u8, // cbLgEntry
+    pub ligatures: Vec<RawLigature>,
+    pub dead_keys: Vec<RawDeadKey>,
+    pub key_names: Vec<(u8, String)>,
+    pub key_names_ext: Vec<(u8, String)>,
+    /// Dead key names, each starting with its dead char.
+    pub key_names_dead: Vec<String>,
+    pub locale_flags: u32,
+    pub type_value: u32,
+    pub subtype_value: u32,
+    pub timestamp: u32,
+    /// The strings of the version resource, which is omitted if there are none.
+    pub version_strings: Vec<(String, String)>,
+    /// The (language, codepage) of the version resource strings.
+    pub translation: (u16, u16),
+    /// The STRINGTABLE resources by id.
+    pub string_resources: Vec<(u32, String)>,
+    /// The name KbdLayerDescriptor is exported under.
+    pub export_name: String,
+    /// Replaces the code of KbdLayerDescriptor, which otherwise returns the tables.
+    pub code: Option<Vec<u8>>,
+    /// Replaces the address KbdLayerDescriptor is exported at, which otherwise is that of its code.
+    pub export_rva: Option<u32>,
+}
+
+impl RawLayout {
+    pub fn new(machine: Machine) -> Self {
+        Self {
+            machine,
+            vsc_to_vk: Vec::new(),
+            vsc_to_vk_e0: Vec::new(),
+            vsc_to_vk_e1: Vec::new(),
+            vk_to_bits: Vec::new(),
+            mod_numbers: vec![0],
+            char_tables: Vec::new(),
+            max_ligature: 0,
+            ligature_entry_size: 0,
+            ligatures: Vec::new(),
+            dead_keys: Vec::new(),
+            key_names: Vec::new(),
+            key_names_ext: Vec::new(),
+            key_names_dead: Vec::new(),
+            locale_flags: 0,
+            type_value: KeyboardDesc::TYPE_GENERIC101,
+            subtype_value: 0,
+            timestamp: 0,
+            version_strings: Vec::new(),
+            translation: (0x0409, 1200),
+            string_resources: Vec::new(),
+            export_name: "KbdLayerDescriptor".to_string(),
+            code: None,
+            export_rva: None,
+        }
+    }
+
+    /// Encodes a layout the way kbd.h lays it out. The layout must fit its char table layout,
+    /// see `KeyboardDesc::regroup_char_tables`.
+    pub fn from_model(keyboard_desc: &KeyboardDesc, machine: Machine) -> Self {
+        assert!(keyboard_desc.fits_char_table_layout(), "The layout does not fit its char table layout, regroup it first.");
+        let mut raw = Self::new(machine);
+
+        for (scan_code, physical_key) in &keyboard_desc.physical_keys {
+            let bits = ((physical_key.virtual_key_flags.to_bits() as u16) << 8) | physical_key.virtual_key.code as u16;
+            match *scan_code {
+                ScanCode::Unescaped(code) => {
+                    if raw.vsc_to_vk.len() <= code as usize {
+                        raw.vsc_to_vk.resize(code as usize + 1, 0xFF);
+                    }
+                    raw.vsc_to_vk[code as usize] = bits;
+                },
+                ScanCode::Extended0(code) => raw.vsc_to_vk_e0.push((code, bits)),
+                ScanCode::Extended1(code) => raw.vsc_to_vk_e1.push((code, bits)),
+            }
+
+            let Some(name) = &physical_key.name else { continue };
+            match *scan_code {
+                ScanCode::Unescaped(code) => raw.key_names.push((code, name.clone())),
+                ScanCode::Extended0(code) => raw.key_names_ext.push((code, name.clone())),
+                ScanCode::Extended1(_) => {},
+            }
+        }
+
+        raw.mod_numbers = keyboard_desc.shift_states.columns().iter()
+            .map(|column| column.unwrap_or(SHFT_INVALID as u8))
+            .collect();
+
+        let char_tables = keyboard_desc.char_tables.as_ref().unwrap();
+        raw.char_tables = char_tables.groups.iter()
+            .map(|group| RawCharTable { modifications: group.modifications, entry_size: group.entry_size, rows: Vec::new() })
+            .collect();
+
+        for (virtual_key, key_effect) in &keyboard_desc.virtual_keys {
+            let key_typing = match key_effect {
+                KeyEffect::Modifier(modifiers) => {
+                    raw.vk_to_bits.push((virtual_key.code, modifiers.to_bits()));
+                    continue
+                },
+                KeyEffect::Typing(key_typing) => key_typing,
+            };
+
+            let group = key_typing.group.unwrap();
+            let modifications = raw.char_tables[group].modifications;
+            let mut dead_chars = vec![WCH_NONE as u16; modifications as usize];
+            let mut rows = vec![RawCharRow { virtual_key: virtual_key.code, attributes: key_attributes(key_typing), chars: Vec::new() }];
+            // With SGCAPS, the caps lock chars follow in a row of their own, before the dead chars.
+            if key_typing.caps_lock_as_uppercase {
+                rows.push(RawCharRow { virtual_key: 0xFF, attributes: 0, chars: Vec::new() });
+            }
+            for (row, layer) in rows.iter_mut().zip([&key_typing.by_modifiers, &key_typing.caps_lock_layer]) {
+                row.chars = vec![WCH_NONE as u16; modifications as usize];
+                for (modifiers, typing_effect) in layer {
+                    let column = keyboard_desc.shift_states.column(modifiers).unwrap();
+                    let ligature = match typing_effect {
+                        TypingEffect::Char(char) => match char.to_u16() {
+                            Some(unit) => {
+                                row.chars[column as usize] = unit;
+                                continue
+                            },
+                            // Characters outside the BMP can only be typed as ligatures.
+                            None => char.to_utf16(),
+                        },
+                        TypingEffect::DeadKey(char) => {
+                            row.chars[column as usize] = WCH_DEAD as u16;
+                            dead_chars[column as usize] = char.to_u16().unwrap();
+                            continue
+                        },
+                        TypingEffect::Ligature(string) => string.to_utf16(),
+                    };
+                    row.chars[column as usize] = WCH_LGTR as u16;
+                    raw.ligatures.push(RawLigature { virtual_key: virtual_key.code, modification_number: column as u16, chars: ligature });
+                }
+            }
+
+            raw.char_tables[group].rows.extend(rows);
+            if dead_chars.iter().any(|char| *char != WCH_NONE as u16) {
+                raw.char_tables[group].rows.push(RawCharRow { virtual_key: 0xFF, attributes: 0, chars: dead_chars });
+            }
+        }
+
+        raw.max_ligature = raw.ligatures.iter().map(|ligature| ligature.chars.len()).max().unwrap_or(0) as u8;
+        raw.ligature_entry_size = 4 + 2 * raw.max_ligature;
+
+        for (accent, dead_key) in &keyboard_desc.dead_keys {
+            let accent = accent.to_u16().unwrap();
+            for (base, combo) in &dead_key.combos {
+                let (composed, dead_flag) = match combo {
+                    DeadKeyCombo::Char(char) => (char, 0),
+                    DeadKeyCombo::DeadKey(char) => (char, DKF_DEAD as u16),
+                };
+                let flags = dead_flag | dead_key.combo_flags.get(base).copied().unwrap_or(0);
+                raw.dead_keys.push(RawDeadKey { accent, base: base.to_u16().unwrap(), composed: composed.to_u16().unwrap(), flags });
+            }
+
+            if let Some(name) = &dead_key.name {
+                let mut units = vec![accent];
+                units.extend(name.encode_utf16());
+                raw.key_names_dead.push(String::from_utf16(&units).unwrap());
+            }
+        }
+
+        raw.locale_flags = (keyboard_desc.version as u32) << 16;
+        if keyboard_desc.supports_altgr { raw.locale_flags |= KLLF_ALTGR; }
+        if keyboard_desc.supports_shift_lock { raw.locale_flags |= KLLF_SHIFTLOCK; }
+        if keyboard_desc.supports_directionality { raw.locale_flags |= KLLF_LRM_RLM; }
+        raw.type_value = keyboard_desc.type_value;
+        raw.subtype_value = keyboard_desc.subtype_value;
+
+        let metadata = &keyboard_desc.metadata;
+        raw.timestamp = metadata.timestamp.unwrap_or(0);
+        for (key, value) in [
+                ("CompanyName", &metadata.company_name),
+                ("FileDescription", &metadata.display_name),
+                ("FileVersion", &metadata.file_version),
+                ("ProductVersion", &metadata.product_version)] {
+            if let Some(value) = value {
+                raw.version_strings.push((key.to_string(), value.clone()));
+            }
+        }
+        if let (Some(language), Some(codepage)) = (metadata.language, metadata.codepage) {
+            raw.translation = (language, codepage);
+        }
+        if let Some(display_name) = &metadata.display_name_resource {
+            raw.string_resources.push((LayoutMetadata::DISPLAY_NAME_RESOURCE_ID, display_name.clone()));
+        }
+
+        raw
+    }
+
+    /// Assembles the DLL image.
+    pub fn build(&self) -> Vec<u8> {
+        let machine = self.machine;
+        let mut section = Section::new(machine.image_base(), machine.pointer_size());
+        self.write_tables(&mut section);
+        self.write_code(&mut section);
+        self.write_exports(&mut section);
+        self.write_resources(&mut section);
+        let labels = section.labels.clone();
+        let mut data = section.resolve();
+        data.resize(data.len().next_multiple_of(FILE_ALIGNMENT), 0);
+
+        let mut image = vec![0u8; HEADERS_SIZE];
+        image[0..2].copy_from_slice(b"MZ");
+        put_u32(&mut image, 0x3C, 0x80);
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+
+        // IMAGE_FILE_HEADER
+        let optional_header_size: u16 = if machine.is_pe32_plus() { 240 } else { 224 };
+        put_u16(&mut image, 0x84, machine.value());
+        put_u16(&mut image, 0x86, 1);
+        put_u32(&mut image, 0x88, self.timestamp);
+        put_u16(&mut image, 0x94, optional_header_size);
+        put_u16(&mut image, 0x96, if machine.is_pe32_plus() { 0x2022 } else { 0x2102 });
+
+        // IMAGE_OPTIONAL_HEADER
+        let optional = 0x98;
+        let data_directories = if machine.is_pe32_plus() {
+            put_u16(&mut image, optional, 0x20B);
+            put_u64(&mut image, optional + 24, machine.image_base());
+            optional + 108
+        } else {
+            put_u16(&mut image, optional, 0x10B);
+            put_u32(&mut image, optional + 28, machine.image_base() as u32);
+            optional + 92
+        };
+        put_u32(&mut image, optional + 32, 0x1000);
+        put_u32(&mut image, optional + 36, FILE_ALIGNMENT as u32);
+        put_u32(&mut image, optional + 56, SECTION_RVA + (data.len() as u32).next_multiple_of(0x1000));
+        put_u32(&mut image, optional + 60, HEADERS_SIZE as u32);
+        put_u16(&mut image, optional + 68, 2);
+
+        put_u32(&mut image, data_directories, 16);
+        let directory = |index: usize| data_directories + 4 + index * 8;
+        put_u32(&mut image, directory(0), labels["exports"]);
+        put_u32(&mut image, directory(0) + 4, labels["exports_end"] - labels["exports"]);
+        if let Some(resources) = labels.get("resources") {
+            put_u32(&mut image, directory(2), *resources);
+            put_u32(&mut image, directory(2) + 4, labels["resources_end"] - resources);
+        }
+
+        // IMAGE_SECTION_HEADER
+        let section_header = directory(16);
+        image[section_header..section_header + 8].copy_from_slice(b".data\0\0\0");
+        put_u32(&mut image, section_header + 8, data.len() as u32);
+        put_u32(&mut image, section_header + 12, SECTION_RVA);
+        put_u32(&mut image, section_header + 16, data.len() as u32);
+        put_u32(&mut image, section_header + 20, HEADERS_SIZE as u32);
+        put_u32(&mut image, section_header + 36, 0xE000_0040);
+
+        image.extend(data);
+        image
+    }
+
+    fn write_tables(&self, section: &mut Section) {
+        section.label("vsc_to_vk", 2);
+        for bits in &self.vsc_to_vk {
+            section.u16(*bits);
+        }
+
+        for (label, rows) in [("vsc_to_vk_e0", &self.vsc_to_vk_e0), ("vsc_to_vk_e1", &self.vsc_to_vk_e1)] {
+            section.label(label, 4);
+            for (vsc, vk) in rows.iter().chain([&(0, 0)]) {
+                section.u8(*vsc);
+                section.u8(0);
+                section.u16(*vk);
+            }
+        }
+
+        section.label("vk_to_bits", 2);
+        for (vk, bits) in self.vk_to_bits.iter().chain([&(0, 0)]) {
+            section.u8(*vk);
+            section.u8(*bits);
+        }
+
+        section.label("modifiers", 8);
+        section.pointer(Some("vk_to_bits"));
+        section.u16(self.mod_numbers.len().saturating_sub(1) as u16);
+        section.bytes(&self.mod_numbers);
+
+        for (index, char_table) in self.char_tables.iter().enumerate() {
+            section.label(&format!("chars{}", index), 8);
+            let terminator = RawCharRow { virtual_key: 0, attributes: 0, chars: Vec::new() };
+            for row in char_table.rows.iter().chain([&terminator]) {
+                let mut entry = vec![row.virtual_key, row.attributes];
+                entry.extend(row.chars.iter().flat_map(|char| char.to_le_bytes()));
+                entry.resize(char_table.entry_size as usize, 0);
+                section.bytes(&entry);
+            }
+        }
+
+        section.label("vk_to_wchar_table", 8);
+        for (index, char_table) in self.char_tables.iter().enumerate() {
+            section.pointer(Some(&format!("chars{}", index)));
+            section.u8(char_table.modifications);
+            section.u8(char_table.entry_size);
+        }
+        section.pointer(None);
+        section.u16(0);
+
+        if !self.ligatures.is_empty() {
+            section.label("ligatures", 8);
+            let terminator = RawLigature { virtual_key: 0, modification_number: 0, chars: Vec::new() };
+            for ligature in self.ligatures.iter().chain([&terminator]) {
+                let mut entry = vec![ligature.virtual_key, 0];
+                entry.extend(ligature.modification_number.to_le_bytes());
+                let mut chars = ligature.chars.clone();
+                chars.resize(self.max_ligature as usize, if ligature.virtual_key == 0 { 0 } else { WCH_NONE as u16 });
+                entry.extend(chars.iter().flat_map(|char| char.to_le_bytes()));
+                entry.resize(self.ligature_entry_size as usize, 0);
+                section.bytes(&entry);
+            }
+        }
+
+        section.label("dead_keys", 4);
+        let terminator = RawDeadKey { accent: 0, base: 0, composed: 0, flags: 0 };
+        for dead_key in self.dead_keys.iter().chain([&terminator]) {
+            section.u32(((dead_key.accent as u32) << 16) | dead_key.base as u32);
+            section.u16(dead_key.composed);
+            section.u16(dead_key.flags);
+        }
+
+        for (label, names) in [("key_names", &self.key_names), ("key_names_ext", &self.key_names_ext)] {
+            for (index, (_, name)) in names.iter().enumerate() {
+                section.label(&format!("{}{}", label, index), 2);
+                section.utf16z(name);
+            }
+            section.label(label, 8);
+            for (index, (vsc, _)) in names.iter().enumerate() {
+                section.u8(*vsc);
+                section.pointer(Some(&format!("{}{}", label, index)));
+            }
+            section.u8(0);
+            section.pointer(None);
+        }
+
+        for (index, name) in self.key_names_dead.iter().enumerate() {
+            section.label(&format!("key_names_dead{}", index), 2);
+            section.utf16z(name);
+        }
+        section.label("key_names_dead", 8);
+        for index in 0..self.key_names_dead.len() {
+            section.pointer(Some(&format!("key_names_dead{}", index)));
+        }
+        section.pointer(None);
+
+        // KBDTABLES
+        section.label("tables", 8);
+        for label in ["modifiers", "vk_to_wchar_table", "dead_keys", "key_names", "key_names_ext", "key_names_dead", "vsc_to_vk"] {
+            section.pointer(Some(label));
+        }
+        section.u8(self.vsc_to_vk.len() as u8);
+        section.pointer(Some("vsc_to_vk_e0"));
+        section.pointer(Some("vsc_to_vk_e1"));
+        section.u32(self.locale_flags);
+        section.u8(self.max_ligature);
+        section.u8(self.ligature_entry_size);
+        section.pointer(if self.ligatures.is_empty() { None } else { Some("ligatures") });
+        section.u32(self.type_value);
+        section.u32(self.subtype_value);
+    }
+
+    fn write_code(&self, section: &mut Section) {
+        section.label("code", 16);
+        if let Some(code) = &self.code {
+            section.bytes(code);
+            return;
+        }
+
+        let code_rva = section.labels["code"];
+        let tables_rva = section.labels["tables"];
+        let tables = section.image_base + tables_rva as u64;
+        match self.machine {
+            Machine::X86 | Machine::Wow64 => {
+                // mov eax, tables; ret
+                section.u8(0xB8);
+                section.u32(tables as u32);
+                section.u8(0xC3);
+            },
+            Machine::X64 => {
+                // lea rax, [rip + tables]; ret
+                section.bytes(&[0x48, 0x8D, 0x05]);
+                section.u32(tables_rva.wrapping_sub(code_rva + 7));
+                section.u8(0xC3);
+            },
+            Machine::Arm64 => {
+                // adrp x0, tables; add x0, x0, :lo12:tables; ret
+                let pages = (tables_rva >> 12).wrapping_sub(code_rva >> 12);
+                section.u32(0x9000_0000 | ((pages & 0x3) << 29) | (((pages >> 2) & 0x7_FFFF) << 5));
+                section.u32(0x9100_0000 | ((tables_rva & 0xFFF) << 10));
+                section.u32(0xD65F_03C0);
+            },
+            Machine::ArmNt => {
+                // movw r0, :lower16:tables; movt r0, :upper16:tables; bx lr
+                for (opcode, value) in [(0xF240u16, tables as u16), (0xF2C0, (tables >> 16) as u16)] {
+                    section.u16(opcode | ((value >> 1) & 0x0400) | (value >> 12));
+                    section.u16(((value << 4) & 0x7000) | (value & 0xFF));
+                }
+                section.u16(0x4770);
+            },
+        }
+    }
+
+    fn write_exports(&self, section: &mut Section) {
+        section.label("dll_name", 1);
+        section.bytes(b"kbdtest.dll\0");
+        section.label("export_name", 1);
+        section.bytes(self.export_name.as_bytes());
+        section.u8(0);
+
+        // Thumb code is exported with the low bit set.
+        section.label("functions", 4);
+        match self.export_rva {
+            Some(rva) => section.u32(rva),
+            None => section.rva("code", if self.machine == Machine::ArmNt { 1 } else { 0 }),
+        }
+        section.label("names", 4);
+        section.rva("export_name", 0);
+        section.label("ordinals", 4);
+        section.u16(0);
+
+        // IMAGE_EXPORT_DIRECTORY
+        section.label("exports", 4);
+        section.u32(0);
+        section.u32(self.timestamp);
+        section.u32(0);
+        section.rva("dll_name", 0);
+        for value in [1, 1, 1] {
+            section.u32(value);
+        }
+        section.rva("functions", 0);
+        section.rva("names", 0);
+        section.rva("ordinals", 0);
+        section.label("exports_end", 1);
+    }
+
+    fn version_resource(&self) -> Vec<u8> {
+        let strings: Vec<u8> = self.version_strings.iter()
+            .flat_map(|(key, value)| version_block(key, &utf16z(value), true, &[]))
+            .collect();
+        let (language, codepage) = self.translation;
+        let string_table = version_block(&format!("{:04X}{:04X}", language, codepage), &[], true, &strings);
+        let mut translation = language.to_le_bytes().to_vec();
+        translation.extend(codepage.to_le_bytes());
+        let var = version_block("Translation", &translation, false, &[]);
+
+        let mut children = version_block("StringFileInfo", &[], true, &string_table);
+        children.extend(version_block("VarFileInfo", &[], true, &var));
+        version_block("VS_VERSION_INFO", &[], false, &children)
+    }
+
+    /// Encodes the STRINGTABLE blocks by block id, each holding 16 length-prefixed strings.
+    fn string_table_blocks(&self) -> Vec<(u32, Vec<u8>)> {
+        let mut block_ids: Vec<u32> = self.string_resources.iter().map(|(id, _)| id / 16 + 1).collect();
+        block_ids.sort();
+        block_ids.dedup();
+        block_ids.into_iter().map(|block_id| {
+            let mut block = Vec::new();
+            for id in (block_id - 1) * 16..block_id * 16 {
+                let units: Vec<u16> = self.string_resources.iter()
+                    .find(|(string_id, _)| *string_id == id)
+                    .map_or(Vec::new(), |(_, string)| string.encode_utf16().collect());
+                block.extend((units.len() as u16).to_le_bytes());
+                block.extend(units.iter().flat_map(|unit| unit.to_le_bytes()));
+            }
+            (block_id, block)
+        }).collect()
+    }
+
+    /// Writes the resource tree, which is omitted if there are no resources.
+    fn write_resources(&self, section: &mut Section) {
+        // The (type, name, data) of the resources, in the order of their directory entries.
+        let mut resources: Vec<(u32, u32, Vec<u8>)> = Vec::new();
+        for (block_id, block) in self.string_table_blocks() {
+            resources.push((RT_STRING, block_id, block));
+        }
+        if !self.version_strings.is_empty() {
+            resources.push((RT_VERSION, 1, self.version_resource()));
+        }
+        if resources.is_empty() { return }
+
+        // A resource directory per level: type, name and language, followed by the data entries.
+        // Directory offsets are relative to the start of the tree, with the high bit set.
+        let mut types: Vec<u32> = resources.iter().map(|(resource_type, _, _)| *resource_type).collect();
+        types.dedup();
+        let name_count = |resource_type: u32| resources.iter().filter(|(other_type, _, _)| *other_type == resource_type).count();
+        let mut names_offsets = Vec::new();
+        let mut offset = 16 + 8 * types.len();
+        for resource_type in &types {
+            names_offsets.push(offset);
+            offset += 16 + 8 * name_count(*resource_type);
+        }
+        let languages_offset = offset;
+        let data_entries_offset = languages_offset + 24 * resources.len();
+
+        section.label("resources", 4);
+        resource_directory(section, types.iter().zip(&names_offsets)
+            .map(|(resource_type, names_offset)| (*resource_type, 0x8000_0000 | *names_offset as u32)));
+        for resource_type in &types {
+            resource_directory(section, resources.iter().enumerate()
+                .filter(|(_, (other_type, _, _))| other_type == resource_type)
+                .map(|(index, (_, name, _))| (*name, 0x8000_0000 | (languages_offset + 24 * index) as u32)));
+        }
+        for index in 0..resources.len() {
+            resource_directory(section, [(self.translation.0 as u32, (data_entries_offset + 16 * index) as u32)]);
+        }
+        for (index, (_, _, data)) in resources.iter().enumerate() {
+            section.rva(&format!("resource{}", index), 0);
+            section.u32(data.len() as u32);
+            section.u32(0);
+            section.u32(0);
+        }
+        for (index, (_, _, data)) in resources.iter().enumerate() {
+            section.label(&format!("resource{}", index), 4);
+            section.bytes(data);
+        }
+        section.label("resources_end", 1);
+    }
+}
+
+/// Writes a resource directory with numeric entries, given as (id, offset) pairs.
+fn resource_directory(section: &mut Section, entries: impl IntoIterator<Item = (u32, u32)>) {
+    let entries: Vec<(u32, u32)> = entries.into_iter().collect();
+    for value in [0, 0, 0] {
+        section.u32(value);
+    }
+    section.u16(0);
+    section.u16(entries.len() as u16);
+    for (id, offset) in entries {
+        section.u32(id);
+        section.u32(offset);
+    }
+}
+
+fn key_attributes(key_typing: &KeyTyping) -> u8 {
+    let mut attributes = 0;
+    if key_typing.caps_lock_as_shift { attributes |= CAPLOK; }
+    if key_typing.caps_lock_as_uppercase { attributes |= SGCAPS; }
+    if key_typing.caps_lock_altgr_as_shift { attributes |= CAPLOKALTGR; }
+    if key_typing.kana_support { attributes |= KANALOK; }
+    if key_typing.grpseltap_support { attributes |= GRPSELTAP; }
+    attributes as u8
+}
+
+/// The contents of the image's single section, with pointers to labels resolved at the end.
+struct Section {
+    data: Vec<u8>,
+    labels: HashMap<String, u32>,
+    /// Pointers (as virtual addresses) and RVAs to labels, with an addend.
+    fixups: Vec<(usize, String, bool, u32)>,
+    image_base: u64,
+    pointer_size: usize,
+}
+
+impl Section {
+    fn new(image_base: u64, pointer_size: usize) -> Self {
+        Self { data: Vec::new(), labels: HashMap::new(), fixups: Vec::new(), image_base, pointer_size }
+    }
+
+    fn label(&mut self, name: &str, alignment: usize) {
+        self.align(alignment);
+        self.labels.insert(name.to_string(), SECTION_RVA + self.data.len() as u32);
+    }
+
+    fn align(&mut self, alignment: usize) {
+        self.data.resize(self.data.len().next_multiple_of(alignment), 0);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn utf16z(&mut self, text: &str) {
+        self.bytes(&utf16z(text));
+    }
+
+    /// A pointer-aligned pointer to a label, or null.
+    fn pointer(&mut self, label: Option<&str>) {
+        self.align(self.pointer_size);
+        if let Some(label) = label {
+            self.fixups.push((self.data.len(), label.to_string(), true, 0));
+        }
+        self.data.resize(self.data.len() + self.pointer_size, 0);
+    }
+
+    fn rva(&mut self, label: &str, addend: u32) {
+        self.fixups.push((self.data.len(), label.to_string(), false, addend));
+        self.u32(0);
+    }
+
+    fn resolve(mut self) -> Vec<u8> {
+        for (offset, label, is_pointer, addend) in &self.fixups {
+            let rva = self.labels[label] + addend;
+            if !is_pointer {
+                put_u32(&mut self.data, *offset, rva);
+            } else if self.pointer_size == 8 {
+                put_u64(&mut self.data, *offset, self.image_base + rva as u64);
+            } else {
+                put_u32(&mut self.data, *offset, (self.image_base + rva as u64) as u32);
+            }
+        }
+        self.data
+    }
+}
+
+/// Encodes a VS_VERSIONINFO block: its header, key, value and children, each aligned to 32 bits.
+fn version_block(key: &str, value: &[u8], is_text: bool, children: &[u8]) -> Vec<u8> {
+    let mut block = vec![0; 6];
+    block.extend(utf16z(key));
+    block.resize(block.len().next_multiple_of(4), 0);
+    block.extend_from_slice(value);
+    if !children.is_empty() {
+        block.resize(block.len().next_multiple_of(4), 0);
+        block.extend_from_slice(children);
+    }
+    let value_length = if is_text { value.len() / 2 } else { value.len() };
+    let length = block.len() as u16;
+    put_u16(&mut block, 0, length);
+    put_u16(&mut block, 2, value_length as u16);
+    put_u16(&mut block, 4, is_text as u16);
+    block.resize(block.len().next_multiple_of(4), 0);
+    block
+}
+
+fn utf16z(text: &str) -> Vec<u8> {
+    text.encode_utf16().chain([0]).flat_map(|unit| unit.to_le_bytes()).collect()
+}
+
+fn put_u16(bytes: &mut [u8], offset: usize, value: u16) {
+    bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
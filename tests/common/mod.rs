@@ -0,0 +1,82 @@
+// Shared helpers for the integration tests, which compare the output of the writers
+// to golden files checked in under tests/golden.
+//
+// Set UPDATE_GOLDEN=1 to rewrite the golden files from the current output instead,
+// then review the differences before committing them. This also rebuilds the DLL fixtures
+// with the DLL builder, so run it twice when they change for the golden files read from them.
+
+#![allow(dead_code)]
+
+pub mod dll_builder;
+
+use std::path::PathBuf;
+use kbdc::installer_format::InstallerOptions;
+use kbdc::model::KeyboardDesc;
+use kbdc::svg_format::SvgOptions;
+
+pub const LAYOUT_FIXTURES: &[&str] = &["kbdtest", "minimal_iso", "nicola", "minimal_hebrew"];
+pub const DLL_FIXTURES: &[&str] = &["kbdtest_x86", "kbdtest_wow64", "kbdtest_x64", "kbdtest_arm64"];
+
+/// The output formats with their file extension.
+pub const FORMATS: &[&str] = &["json", "reg", "inf", "svg", "html", "md", "txt"];
+
+pub fn test_path(relative_path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join(relative_path)
+}
+
+pub fn read_layout_fixture(name: &str) -> KeyboardDesc {
+    let path = test_path(&format!("fixtures/layouts/{}.json", name));
+    let json = std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("Failed to read {}", path.display()));
+    KeyboardDesc::from_json(&json).unwrap_or_else(|error| panic!("Failed to parse {}: {}", path.display(), error))
+}
+
+pub fn read_dll_fixture(name: &str) -> KeyboardDesc {
+    let path = test_path(&format!("fixtures/dll/{}.dll", name));
+    let bytes = std::fs::read(&path).unwrap_or_else(|_| panic!("Failed to read {}", path.display()));
+    kbdc::read_dll::read_keyboard_image(&bytes, &kbdc::read_dll::ReadLimits::new())
+        .unwrap_or_else(|error| panic!("Failed to read {}: {}", path.display(), error))
+}
+
+/// Writes a layout in one of the output formats, with the default options.
+pub fn write(keyboard_desc: &KeyboardDesc, format: &str) -> String {
+    let svg_options = SvgOptions::new();
+    let installer_options = InstallerOptions::new();
+    match format {
+        "json" => keyboard_desc.to_json(),
+        "reg" => keyboard_desc.to_reg(&installer_options).expect("Failed to write .reg"),
+        "inf" => keyboard_desc.to_inf(&installer_options).expect("Failed to write .inf"),
+        "svg" => keyboard_desc.to_svg(&svg_options),
+        "html" => keyboard_desc.to_html(&svg_options),
+        "md" => keyboard_desc.to_markdown(),
+        "txt" => keyboard_desc.to_text(),
+        _ => panic!("Unknown format: {}", format)
+    }
+}
+
+/// Compares some output to a golden file, or updates the golden file if UPDATE_GOLDEN is set.
+pub fn assert_golden(relative_path: &str, actual: &str) {
+    let path = test_path(&format!("golden/{}", relative_path));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing golden file {}, run with UPDATE_GOLDEN=1 to create it", path.display()));
+    assert!(expected == actual, "Output differs from golden file {}, run with UPDATE_GOLDEN=1 to update it\n\
+        --- expected\n{}\n--- actual\n{}", path.display(), expected, actual);
+}
+
+/// Compares a layout DLL built by the DLL builder to a DLL fixture, or updates the fixture if UPDATE_GOLDEN is set.
+pub fn assert_dll_fixture(name: &str, actual: &[u8]) {
+    let path = test_path(&format!("fixtures/dll/{}.dll", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read(&path)
+        .unwrap_or_else(|_| panic!("Missing DLL fixture {}, run with UPDATE_GOLDEN=1 to create it", path.display()));
+    assert!(expected == actual, "DLL builder output differs from {}, run with UPDATE_GOLDEN=1 to update it", path.display());
+}
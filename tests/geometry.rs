@@ -0,0 +1,26 @@
+// Checks how the geometry a layout targets is inferred from the keys it types on.
+
+mod common;
+
+use common::*;
+use kbdc::model::*;
+
+#[test]
+fn infers_iso_from_a_distinct_key_right_of_left_shift() {
+    let mut keyboard_desc = read_layout_fixture("minimal_iso");
+    assert_eq!(keyboard_desc.infer_geometry_kind(), GeometryKind::Iso105);
+
+    // ANSI layouts commonly make the key a copy of Backslash, on the same virtual key or another.
+    keyboard_desc.physical_keys.get_mut(&ScanCode::INTL_BACKSLASH).unwrap().virtual_key = VirtualKey { code: 0xDC }; // VK_OEM_5
+    assert_eq!(keyboard_desc.infer_geometry_kind(), GeometryKind::Ansi104);
+}
+
+#[test]
+fn falls_back_to_the_locale_without_a_c12_key() {
+    let mut keyboard_desc = read_layout_fixture("minimal_iso");
+    keyboard_desc.physical_keys.remove(&ScanCode::BACKSLASH);
+    keyboard_desc.metadata.locale_id = Some(0x0809); // en-GB
+    assert_eq!(keyboard_desc.infer_geometry_kind(), GeometryKind::Iso105);
+    keyboard_desc.metadata.locale_id = Some(0x0409); // en-US
+    assert_eq!(keyboard_desc.infer_geometry_kind(), GeometryKind::Ansi104);
+}
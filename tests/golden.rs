@@ -0,0 +1,41 @@
+// Compares the output of every writer, and the layouts read from DLLs, to golden files.
+
+mod common;
+
+use common::*;
+
+#[test]
+fn writers_match_golden_files() {
+    for name in LAYOUT_FIXTURES {
+        let keyboard_desc = read_layout_fixture(name);
+        for format in FORMATS {
+            assert_golden(&format!("{}.{}", name, format), &write(&keyboard_desc, format));
+        }
+    }
+}
+
+#[test]
+fn dll_fixtures_match_golden_files() {
+    for name in DLL_FIXTURES {
+        assert_golden(&format!("dll/{}.json", name), &read_dll_fixture(name).to_json());
+    }
+}
+
+#[test]
+fn dll_fixtures_agree_across_architectures() {
+    // The fixtures only differ by how their tables are stored, so they should read the same but for the machine.
+    let reference = read_dll_fixture(DLL_FIXTURES[0]);
+    for name in &DLL_FIXTURES[1..] {
+        let mut keyboard_desc = read_dll_fixture(name);
+        keyboard_desc.metadata.machine = reference.metadata.machine;
+        assert_eq!(keyboard_desc.to_json(), reference.to_json(), "{} differs from {}", name, DLL_FIXTURES[0]);
+    }
+}
+
+#[test]
+fn html_quotes_font_families() {
+    let mut svg_options = kbdc::svg_format::SvgOptions::new();
+    svg_options.font_family = "'Noto Sans', Evil\"; } </style><script>, monospace".to_string();
+    let html = read_layout_fixture("minimal_iso").to_html(&svg_options);
+    assert!(html.contains(r#"body { font-family: "Noto Sans", "Evil\22 ; } \3C /style\3E \3C script\3E ", monospace;"#));
+}
@@ -0,0 +1,67 @@
+// Checks how the .reg and .inf writers pick the KLID, Layout Id and display name
+// of a layout, given the layouts already installed on the target system.
+
+mod common;
+
+use common::*;
+use kbdc::installer_format::*;
+
+const INSTALLED_LAYOUTS: &str = "\
+Windows Registry Editor Version 5.00\r
+\r
+[HKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Control\\Keyboard Layouts\\00000409]\r
+\"Layout File\"=\"KBDUS.DLL\"\r
+\"Layout Text\"=\"US\"\r
+\r
+[HKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Control\\Keyboard Layouts\\a0000409]\r
+\"Layout File\"=\"kbdother.dll\"\r
+\"Layout Id\"=\"0c00\"\r
+\r
+[HKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Control\\Keyboard Layouts\\a0010409]\r
+\"Layout File\"=\"kbdmine.dll\"\r
+\"Layout Id\"=\"0c01\"\r
+";
+
+fn registry_values(reg: &str) -> Vec<&str> {
+    reg.lines().filter(|line| line.starts_with('[') || line.starts_with("\"Layout Id\"")).collect()
+}
+
+#[test]
+fn reads_installed_layouts() {
+    let installed_layouts = read_installed_layouts(INSTALLED_LAYOUTS);
+    assert_eq!(installed_layouts, vec![
+        InstalledLayout { klid: 0x0000_0409, layout_file: Some("KBDUS.DLL".to_string()), layout_id: None },
+        InstalledLayout { klid: 0xA000_0409, layout_file: Some("kbdother.dll".to_string()), layout_id: Some(0x0C00) },
+        InstalledLayout { klid: 0xA001_0409, layout_file: Some("kbdmine.dll".to_string()), layout_id: Some(0x0C01) },
+    ]);
+}
+
+#[test]
+fn avoids_installed_klids_and_layout_ids() {
+    let mut keyboard_desc = read_layout_fixture("kbdtest");
+    let mut options = InstallerOptions::new();
+    options.installed_layouts = read_installed_layouts(INSTALLED_LAYOUTS);
+    assert_eq!(registry_values(&keyboard_desc.to_reg(&options).unwrap()), vec![
+        "[HKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Control\\Keyboard Layouts\\a0020409]",
+        "\"Layout Id\"=\"0c02\"",
+    ]);
+
+    // A layout installed before keeps its KLID and Layout Id.
+    keyboard_desc.metadata.layout_file = Some("kbdmine.dll".to_string());
+    keyboard_desc.metadata.klid = Some(0xA001_0409);
+    assert_eq!(registry_values(&keyboard_desc.to_reg(&options).unwrap()), vec![
+        "[HKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Control\\Keyboard Layouts\\a0010409]",
+        "\"Layout Id\"=\"0c01\"",
+    ]);
+}
+
+#[test]
+fn refers_to_the_display_name_resource_only_if_present() {
+    let mut keyboard_desc = read_layout_fixture("kbdtest");
+    let options = InstallerOptions::new();
+    assert!(keyboard_desc.to_inf(&options).unwrap().contains("\"Layout Display Name\", 0x00000000, %LayoutText%"));
+
+    keyboard_desc.metadata.display_name_resource = Some("Test Keyboard".to_string());
+    assert!(keyboard_desc.to_inf(&options).unwrap()
+        .contains("\"Layout Display Name\", 0x00020000, \"@%%SystemRoot%%\\system32\\kbdtest.dll,-1000\""));
+}
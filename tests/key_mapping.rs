@@ -0,0 +1,102 @@
+// Compares the MapVirtualKeyEx and GetKeyNameText emulation over every fixture layout to golden files,
+// and checks the quirks of Windows applications depend on.
+
+mod common;
+
+use std::fmt::Write;
+use common::*;
+use kbdc::model::*;
+
+/// Lists every translation which is not 0 in each mode, then the name of every scan code.
+fn dump(keyboard_desc: &KeyboardDesc) -> String {
+    let scan_codes: Vec<u32> = (0x00..0x80).chain(0xE000..0xE080).chain(0xE100..0xE180).collect();
+    let mut dump = String::new();
+    for mode in MapVirtualKeyMode::ALL {
+        writeln!(dump, "{}", mode.name()).unwrap();
+        let from_scan_codes = matches!(mode, MapVirtualKeyMode::VscToVk | MapVirtualKeyMode::VscToVkEx);
+        let codes = if from_scan_codes { scan_codes.clone() } else { (0x00..=0xFF).collect() };
+        for code in codes {
+            let result = keyboard_desc.map_virtual_key(code, mode);
+            if result == 0 { continue; }
+            match mode {
+                MapVirtualKeyMode::VkToVsc | MapVirtualKeyMode::VkToVscEx =>
+                    writeln!(dump, "  {:?} -> {:04X}", VirtualKey { code: code as u8 }, result),
+                MapVirtualKeyMode::VscToVk | MapVirtualKeyMode::VscToVkEx =>
+                    writeln!(dump, "  {:04X} -> {:?}", code, VirtualKey { code: result as u8 }),
+                MapVirtualKeyMode::VkToChar =>
+                    writeln!(dump, "  {:?} -> {:08X}", VirtualKey { code: code as u8 }, result),
+            }.unwrap();
+        }
+    }
+
+    writeln!(dump, "GetKeyNameText").unwrap();
+    for flags in [0, 1 << 24, 1 << 25, (1 << 24) | (1 << 25)] {
+        for code in 0x00..0x80 {
+            let lparam = (code << 16) | flags;
+            let name = keyboard_desc.key_name_text(lparam);
+            if !name.is_empty() {
+                writeln!(dump, "  {:08X} -> {:?}", lparam, name).unwrap();
+            }
+        }
+    }
+    dump
+}
+
+#[test]
+fn key_mapping_matches_golden_files() {
+    for name in LAYOUT_FIXTURES {
+        assert_golden(&format!("key_mapping/{}.txt", name), &dump(&read_layout_fixture(name)));
+    }
+}
+
+#[test]
+fn maps_like_windows() {
+    let keyboard_desc = read_layout_fixture("kbdtest");
+    let map = |code, mode| keyboard_desc.map_virtual_key(code, mode);
+
+    // Generic modifiers map to the left key, and scan codes map back to generic modifiers.
+    assert_eq!(map(0x10, MapVirtualKeyMode::VkToVsc), 0x2A); // VK_SHIFT
+    assert_eq!(map(0xA3, MapVirtualKeyMode::VkToVsc), 0x1D); // VK_RCONTROL
+    assert_eq!(map(0xA3, MapVirtualKeyMode::VkToVscEx), 0xE01D);
+    assert_eq!(map(0xE01D, MapVirtualKeyMode::VscToVk), 0x11); // VK_CONTROL
+    assert_eq!(map(0xE01D, MapVirtualKeyMode::VscToVkEx), 0xA3);
+
+    // Unshifted characters are uppercased, and dead keys have the top bit set.
+    assert_eq!(map(b'A' as u32, MapVirtualKeyMode::VkToChar), 'A' as u32);
+    assert_eq!(map(0xDB, MapVirtualKeyMode::VkToChar), 0x8000_0000 | '^' as u32); // VK_OEM_4
+    assert_eq!(map(0x0D, MapVirtualKeyMode::VkToChar), '\r' as u32); // VK_RETURN
+    assert_eq!(MapVirtualKeyMode::from_value(4), Some(MapVirtualKeyMode::VkToVscEx));
+    assert_eq!(MapVirtualKeyMode::from_value(5), None);
+}
+
+#[test]
+fn names_keys_like_windows() {
+    let keyboard_desc = read_layout_fixture("kbdtest");
+    let extended = 1 << 24;
+    let dont_care = 1 << 25;
+    assert_eq!(keyboard_desc.key_name_text(0x36 << 16), "Right Shift");
+    assert_eq!(keyboard_desc.key_name_text((0x36 << 16) | dont_care), "Shift");
+    assert_eq!(keyboard_desc.key_name_text((0x1D << 16) | extended), "Right Ctrl");
+    assert_eq!(keyboard_desc.key_name_text((0x1D << 16) | extended | dont_care), "Ctrl");
+
+    // Keys without a name are named after their character, or their dead key, and unmapped ones are not named.
+    assert_eq!(keyboard_desc.key_name_text(0x1E << 16), "A");
+    assert_eq!(keyboard_desc.key_name_text(0x1A << 16), "CIRCUMFLEX");
+    assert_eq!(keyboard_desc.key_name_text(0x7F << 16), "");
+}
+
+#[test]
+fn names_keys_as_windows_does_for_kbdus_and_kbdfr() {
+    // What GetKeyNameText returns with the US (kbdus.dll) and French (kbdfr.dll) layouts,
+    // whose key name tables kbdtest shares for these keys.
+    let mut keyboard_desc = read_layout_fixture("kbdtest");
+    let extended = 1 << 24;
+    assert_eq!(keyboard_desc.key_name_text(0x1E << 16), "A");
+    assert_eq!(keyboard_desc.key_name_text(0x36 << 16), "Right Shift");
+    assert_eq!(keyboard_desc.key_name_text((0x1C << 16) | extended), "Num Enter");
+    assert_eq!(keyboard_desc.key_name_text(0x45 << 16), "Num Lock");
+
+    // kbdfr has the circumflex dead key on the same key, named in French.
+    keyboard_desc.dead_keys.get_mut(&WideChar::Scalar('^')).unwrap().name = Some("ACCENT CIRCONFLEXE".to_string());
+    assert_eq!(keyboard_desc.key_name_text(0x1A << 16), "ACCENT CIRCONFLEXE");
+}
@@ -0,0 +1,298 @@
+// Reads synthetic layout DLLs assembled by the DLL builder, checking that the reader recovers
+// the layouts they were built from on every architecture and rejects malformed tables with an error.
+
+mod common;
+
+use common::*;
+use common::dll_builder::*;
+use kbdc::model::*;
+use kbdc::read_dll::*;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+
+/// Reads a layout fixture, regrouped so that it can be encoded.
+fn encodable_fixture(name: &str) -> KeyboardDesc {
+    let mut keyboard_desc = read_layout_fixture(name);
+    if !keyboard_desc.fits_char_table_layout() {
+        keyboard_desc.regroup_char_tables();
+    }
+    keyboard_desc
+}
+
+fn read(image: &[u8]) -> Result<KeyboardDesc, ReadError> {
+    read_keyboard_image(image, &ReadLimits::new())
+}
+
+/// A layout with a single key typing 'a' and 'A', to corrupt in tests.
+fn simple_layout(machine: Machine) -> RawLayout {
+    let mut raw = RawLayout::new(machine);
+    raw.vsc_to_vk = vec![0xFF, 0xFF, 0x41];
+    raw.vk_to_bits = vec![(0x10, KBDSHIFT as u8)];
+    raw.mod_numbers = vec![0, 1];
+    raw.char_tables = vec![RawCharTable {
+        modifications: 2,
+        entry_size: 6,
+        rows: vec![RawCharRow { virtual_key: 0x41, attributes: CAPLOK as u8, chars: vec!['a' as u16, 'A' as u16] }],
+    }];
+    raw
+}
+
+#[test]
+fn layouts_survive_encoding_on_every_architecture() {
+    for name in LAYOUT_FIXTURES {
+        let keyboard_desc = encodable_fixture(name);
+        for machine in Machine::ALL {
+            let mut read = read(&RawLayout::from_model(&keyboard_desc, machine).build()).unwrap();
+            assert_eq!(read.metadata.machine, Some(machine.value()));
+            assert_eq!(read.metadata.display_name, keyboard_desc.metadata.display_name);
+            // Images lack installation metadata such as the layout text used as title.
+            read.metadata.layout_text = keyboard_desc.metadata.layout_text.clone();
+
+            // Keys are read in table order, grouped by char table, so compare the sorted dump.
+            assert_eq!(read.to_text(), keyboard_desc.to_text(), "{} differs after encoding for {:?}", name, machine);
+            assert_eq!(read.shift_states, keyboard_desc.shift_states);
+            assert_eq!(read.char_tables, keyboard_desc.char_tables);
+        }
+    }
+}
+
+#[test]
+fn builder_reproduces_dll_fixtures() {
+    let keyboard_desc = encodable_fixture("kbdtest");
+    for (name, machine) in [("kbdtest_x86", Machine::X86), ("kbdtest_wow64", Machine::Wow64),
+            ("kbdtest_x64", Machine::X64), ("kbdtest_arm64", Machine::Arm64)] {
+        assert_dll_fixture(name, &RawLayout::from_model(&keyboard_desc, machine).build());
+    }
+}
+
+#[test]
+fn decodes_ligatures_chained_dead_keys_and_e1_scan_codes() {
+    let read = read(&RawLayout::from_model(&encodable_fixture("kbdtest"), Machine::X64).build()).unwrap();
+
+    let Some(KeyEffect::Typing(key_typing)) = read.virtual_keys.get(&VirtualKey { code: b'2' }) else { panic!("Missing VK_2") };
+    let altgr = KeyModifiers::from_bits((KBDCTRL | KBDALT) as u8);
+    assert!(matches!(key_typing.by_modifiers.get(&altgr), Some(TypingEffect::Ligature(string)) if string.try_to_string().as_deref() == Some("ff")));
+
+    // A surrogate pair ligature is a single character.
+    let Some(KeyEffect::Typing(key_typing)) = read.virtual_keys.get(&VirtualKey { code: b'3' }) else { panic!("Missing VK_3") };
+    assert!(matches!(key_typing.by_modifiers.get(&altgr), Some(TypingEffect::Char(WideChar::Scalar('\u{1F600}')))));
+
+    let circumflex = &read.dead_keys[&WideChar::Scalar('^')];
+    assert!(matches!(circumflex.combos.get(&WideChar::Scalar('¨')), Some(DeadKeyCombo::DeadKey(WideChar::Scalar('ˇ')))));
+    assert!(read.dead_keys.contains_key(&WideChar::Scalar('ˇ')));
+
+    assert_eq!(read.physical_keys[&ScanCode::Extended1(0x1D)].virtual_key, VirtualKey { code: 0x13 });
+}
+
+#[test]
+fn decodes_caps_lock_rows() {
+    for name in DLL_FIXTURES {
+        let read = read_dll_fixture(name);
+        assert!(!read.virtual_keys.contains_key(&VirtualKey { code: 0xFF }), "{} has a bogus VK 0xFF key", name);
+
+        let Some(KeyEffect::Typing(key_typing)) = read.virtual_keys.get(&VirtualKey { code: VK_OEM_1 as u8 }) else { panic!("Missing VK_OEM_1") };
+        assert!(key_typing.caps_lock_as_uppercase);
+        let shift = KeyModifiers::from_bits(KBDSHIFT as u8);
+        assert!(matches!(key_typing.effect(&KeyModifiers::from_bits(0), false), Some(TypingEffect::Char(WideChar::Scalar('ö')))));
+        assert!(matches!(key_typing.effect(&KeyModifiers::from_bits(0), true), Some(TypingEffect::Char(WideChar::Scalar('Ö')))));
+        assert!(matches!(key_typing.effect(&shift, true), Some(TypingEffect::Char(WideChar::Scalar('É')))));
+    }
+}
+
+#[test]
+fn reads_version_resource() {
+    let read = read(&RawLayout::from_model(&encodable_fixture("kbdtest"), Machine::X86).build()).unwrap();
+    assert_eq!(read.metadata.display_name.as_deref(), Some("Test Keyboard"));
+    assert_eq!(read.metadata.company_name.as_deref(), Some("kbdc"));
+    assert_eq!(read.metadata.language, Some(0x0409));
+    assert_eq!(read.metadata.timestamp, Some(1_600_000_000));
+}
+
+#[test]
+fn reads_display_name_resource() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.string_resources = vec![(999, "Other".to_string()), (LayoutMetadata::DISPLAY_NAME_RESOURCE_ID, "Test Keyboard".to_string())];
+    assert_eq!(read(&raw.build()).unwrap().metadata.display_name_resource.as_deref(), Some("Test Keyboard"));
+
+    raw.string_resources = vec![(999, "Other".to_string())];
+    assert!(read(&raw.build()).unwrap().metadata.display_name_resource.is_none());
+}
+
+#[test]
+fn reads_simple_layout() {
+    for machine in Machine::ALL {
+        let read = read(&simple_layout(machine).build()).unwrap();
+        assert_eq!(read.physical_keys[&ScanCode::Unescaped(2)].virtual_key, VirtualKey { code: 0x41 });
+        assert!(!read.physical_keys.contains_key(&ScanCode::Unescaped(1)));
+        assert_eq!(read.virtual_keys.len(), 2);
+        assert!(read.metadata.display_name.is_none());
+    }
+}
+
+#[test]
+fn rejects_non_pe_files() {
+    assert_eq!(read(b"Not a DLL").err(), Some(ReadError::NotPeImage));
+}
+
+#[test]
+fn rejects_missing_export() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.export_name = "DllMain".to_string();
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::MissingDescriptor));
+}
+
+#[test]
+fn rejects_unrecognized_code() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.code = Some(vec![0x33, 0xC0, 0xC3]); // xor eax, eax; ret
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::UnrecognizedDescriptorCode));
+}
+
+#[test]
+fn rejects_unsupported_machines() {
+    let mut image = simple_layout(Machine::X64).build();
+    image[0x84..0x86].copy_from_slice(&0x0200u16.to_le_bytes()); // IMAGE_FILE_MACHINE_IA64
+    assert_eq!(read(&image).err(), Some(ReadError::UnsupportedMachine(0x0200)));
+}
+
+#[test]
+fn rejects_exports_outside_the_image() {
+    let mut raw = simple_layout(Machine::X86);
+    raw.export_rva = Some(0x10_0000);
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::OutOfBounds { rva: 0x10_0000 }));
+}
+
+#[test]
+fn rejects_pointers_outside_the_image() {
+    let mut raw = simple_layout(Machine::X86);
+    // Returns a KBDTABLES address past the end of the image.
+    raw.code = Some(vec![0xB8, 0x00, 0x00, 0x00, 0x20, 0xC3]);
+    assert!(matches!(read(&raw.build()), Err(ReadError::OutOfBounds { .. })));
+
+    // Returns an address below the image base, which must not wrap around.
+    raw.code = Some(vec![0xB8, 0x00, 0x00, 0x00, 0x00, 0xC3]);
+    assert!(matches!(read(&raw.build()), Err(ReadError::OutOfBounds { .. })));
+}
+
+#[test]
+fn rejects_columns_without_shift_state() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.mod_numbers = vec![0];
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::NoShiftState { mod_number: 1 }));
+}
+
+#[test]
+fn rejects_dead_keys_without_dead_row() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.char_tables[0].rows[0].chars[0] = WCH_DEAD as u16;
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::MissingDeadKeyRow { virtual_key: 0x41 }));
+}
+
+#[test]
+fn rejects_dead_keys_followed_by_another_key() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.char_tables[0].rows[0].chars[0] = WCH_DEAD as u16;
+    raw.char_tables[0].rows.push(RawCharRow { virtual_key: 0x42, attributes: 0, chars: vec!['b' as u16, 'B' as u16] });
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::MissingDeadKeyRow { virtual_key: 0x41 }));
+}
+
+#[test]
+fn rejects_caps_lock_keys_without_caps_lock_row() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.char_tables[0].rows[0].attributes = SGCAPS as u8;
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::MissingCapsLockRow { virtual_key: 0x41 }));
+
+    raw.char_tables[0].rows.push(RawCharRow { virtual_key: 0x42, attributes: 0, chars: vec!['b' as u16, 'B' as u16] });
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::MissingCapsLockRow { virtual_key: 0x41 }));
+}
+
+#[test]
+fn rejects_missing_ligatures() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.char_tables[0].rows[0].chars[1] = WCH_LGTR as u16;
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::MissingLigature { virtual_key: 0x41 }));
+}
+
+#[test]
+fn rejects_rows_smaller_than_their_chars() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.char_tables[0].entry_size = 4;
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::InvalidTable { table: "pVkToWchars" }));
+
+    let mut raw = simple_layout(Machine::X64);
+    raw.max_ligature = 2;
+    raw.ligature_entry_size = 6;
+    assert_eq!(read(&raw.build()).err(), Some(ReadError::InvalidTable { table: "pLigature" }));
+}
+
+#[test]
+fn limits_table_rows() {
+    let mut raw = simple_layout(Machine::X64);
+    raw.char_tables[0].rows.push(RawCharRow { virtual_key: 0x42, attributes: 0, chars: vec!['b' as u16, 'B' as u16] });
+    let image = raw.build();
+
+    let mut limits = ReadLimits::new();
+    limits.max_table_rows = 2;
+    assert!(read_keyboard_image(&image, &limits).is_ok());
+    limits.max_table_rows = 1;
+    assert_eq!(read_keyboard_image(&image, &limits).err(), Some(ReadError::TooManyRows { table: "pVkToWchars" }));
+}
+
+#[test]
+fn limits_string_lengths() {
+    let mut raw = simple_layout(Machine::X86);
+    raw.key_names = vec![(2, "Letter A".to_string())];
+    let image = raw.build();
+
+    let mut limits = ReadLimits::new();
+    limits.max_string_length = 8;
+    assert_eq!(read_keyboard_image(&image, &limits).ok().unwrap().physical_keys[&ScanCode::Unescaped(2)].name.as_deref(), Some("Letter A"));
+    limits.max_string_length = 7;
+    assert!(matches!(read_keyboard_image(&image, &limits), Err(ReadError::StringTooLong { .. })));
+}
+
+/// Truncated and corrupted images must be rejected or read, but never crash the reader.
+#[test]
+fn survives_truncated_and_corrupted_images() {
+    let mut limits = ReadLimits::new();
+    limits.max_table_rows = 0x100;
+    for name in DLL_FIXTURES {
+        let image = std::fs::read(test_path(&format!("fixtures/dll/{}.dll", name))).unwrap();
+        for length in (0..image.len()).step_by(7) {
+            let _ = read_keyboard_image(&image[..length], &limits);
+        }
+        for offset in 0..image.len() {
+            let mut corrupted = image.clone();
+            corrupted[offset] ^= 0xFF;
+            let _ = read_keyboard_image(&corrupted, &limits);
+        }
+    }
+}
+
+#[test]
+fn preserves_dead_key_combo_flags() {
+    let mut raw = RawLayout::from_model(&encodable_fixture("kbdtest"), Machine::X64);
+    raw.dead_keys[0].flags |= 0x8000;
+    raw.dead_keys[1].flags |= 0x0002;
+    let read = read(&raw.build()).unwrap();
+    let round_tripped = KeyboardDesc::from_json(&read.to_json()).unwrap();
+    let rebuilt = RawLayout::from_model(&round_tripped, Machine::X64);
+    let flags = |raw: &RawLayout| raw.dead_keys.iter().map(|dead_key| dead_key.flags).collect::<Vec<u16>>();
+    assert_eq!(flags(&rebuilt), flags(&raw));
+}
+
+#[test]
+fn shared_columns_type_with_every_combination_of_modifiers() {
+    // Shift and Ctrl share the second column, as kbd.h layouts sometimes do.
+    let mut raw = simple_layout(Machine::X64);
+    raw.vk_to_bits.push((0x11, KBDCTRL as u8));
+    raw.mod_numbers = vec![0, 1, 1];
+    let read = read(&raw.build()).unwrap();
+    let bits = |all_modifiers: Vec<KeyModifiers>| all_modifiers.iter().map(|modifiers| modifiers.to_bits()).collect::<Vec<u8>>();
+    assert_eq!(bits(read.shift_states.modifiers(1)), vec![KBDSHIFT as u8, KBDCTRL as u8]);
+
+    let Some(KeyEffect::Typing(key_typing)) = read.virtual_keys.get(&VirtualKey { code: b'A' }) else { panic!("Missing VK_A") };
+    for mod_bits in [KBDSHIFT, KBDCTRL] {
+        let typed = key_typing.by_modifiers.get(&KeyModifiers::from_bits(mod_bits as u8));
+        assert!(matches!(typed, Some(TypingEffect::Char(WideChar::Scalar('A')))), "{:#X} types nothing", mod_bits);
+    }
+}
@@ -0,0 +1,64 @@
+// Checks that writing a layout to JSON and reading it back loses nothing,
+// by comparing the output of every writer before and after the round trip.
+
+mod common;
+
+use common::*;
+use kbdc::model::KeyboardDesc;
+
+fn assert_round_trip(name: &str, keyboard_desc: &KeyboardDesc) {
+    let json = keyboard_desc.to_json();
+    let round_tripped = KeyboardDesc::from_json(&json)
+        .unwrap_or_else(|error| panic!("Failed to read back {}: {}", name, error));
+    for format in FORMATS {
+        assert_eq!(write(keyboard_desc, format), write(&round_tripped, format),
+            "{} differs in {} after a JSON round trip", name, format);
+    }
+}
+
+#[test]
+fn json_fixtures_round_trip() {
+    for name in LAYOUT_FIXTURES {
+        assert_round_trip(name, &read_layout_fixture(name));
+    }
+}
+
+#[test]
+fn json_fixtures_are_canonical() {
+    for name in LAYOUT_FIXTURES {
+        let path = test_path(&format!("fixtures/layouts/{}.json", name));
+        let json = std::fs::read_to_string(path).unwrap();
+        assert_eq!(json.trim_end(), read_layout_fixture(name).to_json(), "{} is not in the form the writer produces", name);
+    }
+}
+
+#[test]
+fn dll_fixtures_round_trip() {
+    for name in DLL_FIXTURES {
+        let mut keyboard_desc = read_dll_fixture(name);
+        // The installer formats need a file name and a KLID, which images read from bytes lack.
+        keyboard_desc.metadata.layout_file = Some(format!("{}.dll", name));
+        keyboard_desc.metadata.klid = Some(0x0000_0409);
+        keyboard_desc.metadata.complete_locale();
+        assert_round_trip(name, &keyboard_desc);
+    }
+}
+
+#[test]
+fn json_round_trips_shift_lock() {
+    let keyboard_desc = read_layout_fixture("minimal_hebrew");
+    assert!(keyboard_desc.supports_shift_lock);
+    assert!(KeyboardDesc::from_json(&keyboard_desc.to_json()).unwrap().supports_shift_lock);
+}
+
+#[test]
+fn json_rejects_malformed_metadata_numbers() {
+    let json = std::fs::read_to_string(test_path("fixtures/layouts/minimal_iso.json")).unwrap();
+    for (valid, malformed, message) in [
+        ("\"klid\": \"0000040C\"", "\"klid\": \"0000O40C\"", "Invalid value for klid: 0000O40C"),
+        ("\"localeId\": \"040C\"", "\"localeId\": \"1040C\"", "Invalid value for localeId: 1040C"),
+    ] {
+        let error = KeyboardDesc::from_json(&json.replace(valid, malformed)).err().expect(malformed);
+        assert_eq!(error.to_string(), message);
+    }
+}
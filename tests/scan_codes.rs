@@ -0,0 +1,43 @@
+// Checks the scan code catalogue: names, USB HID usages and ISO/IEC 9995-1 positions.
+
+use kbdc::model::*;
+
+#[test]
+fn catalogue_positions_are_unique() {
+    let mut positions = std::collections::HashSet::new();
+    for code in 0..0x80 {
+        for scan_code in [ScanCode::Unescaped(code), ScanCode::Extended0(code), ScanCode::Extended1(code)] {
+            if let Some(iso_position) = scan_code.iso_position() {
+                assert!(positions.insert(iso_position), "{} is used twice", iso_position);
+                assert_eq!(ScanCode::from_iso_position(iso_position), Some(scan_code));
+            }
+        }
+    }
+}
+
+#[test]
+fn positions_cover_the_whole_alphanumeric_section() {
+    for (scan_code, iso_position) in [
+        (ScanCode::BACKQUOTE, "E00"), (ScanCode::BACKSPACE, "E14"),
+        (ScanCode::TAB, "D00"), (ScanCode::BRACKET_RIGHT, "D12"),
+        (ScanCode::CAPS_LOCK, "C00"), (ScanCode::BACKSLASH, "C12"), (ScanCode::ENTER, "C13"),
+        (ScanCode::LEFT_SHIFT, "B99"), (ScanCode::INTL_BACKSLASH, "B00"), (ScanCode::RIGHT_SHIFT, "B12"),
+        (ScanCode::LEFT_CONTROL, "A99"), (ScanCode::LEFT_META, "A00"), (ScanCode::LEFT_ALT, "A01"),
+        (ScanCode::SPACE, "A03"), (ScanCode::RIGHT_ALT, "A09"), (ScanCode::RIGHT_CONTROL, "A12"),
+    ] {
+        assert_eq!(scan_code.iso_position(), Some(iso_position), "{:?}", scan_code);
+    }
+
+    // Keys outside the alphanumeric section have no position.
+    assert_eq!(ScanCode::ESCAPE.iso_position(), None);
+    assert_eq!(ScanCode::NUMPAD_ENTER.iso_position(), None);
+}
+
+#[test]
+fn names_and_hid_usages_resolve_both_ways() {
+    assert_eq!(ScanCode::from_name("ShiftLeft"), Some(ScanCode::LEFT_SHIFT));
+    assert_eq!(ScanCode::LEFT_SHIFT.name(), Some("ShiftLeft"));
+    let usage = HidUsage { page: HidUsage::KEYBOARD, id: 0x2C };
+    assert_eq!(ScanCode::SPACE.hid_usage(), Some(usage));
+    assert_eq!(ScanCode::from_hid_usage(usage), Some(ScanCode::SPACE));
+}
@@ -0,0 +1,145 @@
+// Runs the typing simulator over fixture layouts, including thumb shift chords
+// on the NICOLA fixture.
+
+mod common;
+
+use common::*;
+use kbdc::model::*;
+use kbdc::simulator::{ThumbShiftTiming, TypingSimulator};
+
+const VK_A: VirtualKey = VirtualKey { code: b'A' };
+const VK_S: VirtualKey = VirtualKey { code: b'S' };
+
+fn text(typed: WideString) -> String {
+    typed.to_string_lossy()
+}
+
+#[test]
+fn finds_thumb_shift_keys() {
+    let keyboard_desc = read_layout_fixture("nicola");
+    let thumb_shift_keys = keyboard_desc.thumb_shift_keys();
+    assert_eq!(thumb_shift_keys.left, vec![VirtualKey::OEM_FJ_LOYA]);
+    assert_eq!(thumb_shift_keys.right, vec![VirtualKey::OEM_FJ_ROYA]);
+    assert!(keyboard_desc.validate().is_empty());
+    assert!(!read_layout_fixture("kbdtest").uses_thumb_shift());
+}
+
+#[test]
+fn types_with_thumb_keys_held() {
+    let keyboard_desc = read_layout_fixture("nicola");
+    let mut simulator = TypingSimulator::new(&keyboard_desc);
+    assert_eq!(text(simulator.type_key(VK_A)), "う");
+
+    simulator.key_down(VirtualKey::OEM_FJ_LOYA);
+    assert_eq!(text(simulator.type_key(VK_A)), "を");
+    simulator.key_up(VirtualKey::OEM_FJ_LOYA);
+
+    simulator.key_down(VirtualKey::OEM_FJ_ROYA);
+    assert_eq!(text(simulator.type_key(VK_S)), "じ");
+    simulator.key_up(VirtualKey::OEM_FJ_ROYA);
+}
+
+#[test]
+fn chords_thumb_keys_pressed_within_the_window() {
+    let keyboard_desc = read_layout_fixture("nicola");
+    let mut simulator = TypingSimulator::new(&keyboard_desc);
+    simulator.set_thumb_shift_timing(Some(ThumbShiftTiming { chord_window: 50 }));
+
+    // The character key waits for the thumb key pressed just after it.
+    assert_eq!(text(simulator.key_down_at(VK_S, 0)), "");
+    assert_eq!(text(simulator.key_down_at(VirtualKey::OEM_FJ_LOYA, 30)), "あ");
+    assert_eq!(text(simulator.key_up_at(VK_S, 60)), "");
+    assert_eq!(text(simulator.key_up_at(VirtualKey::OEM_FJ_LOYA, 70)), "");
+
+    // Once the window elapses, the key types on its own.
+    assert_eq!(text(simulator.key_down_at(VK_A, 100)), "");
+    assert_eq!(text(simulator.advance_to(150)), "");
+    assert_eq!(text(simulator.advance_to(151)), "う");
+    assert_eq!(text(simulator.key_down_at(VirtualKey::OEM_FJ_ROYA, 160)), "");
+    assert_eq!(text(simulator.key_up_at(VK_A, 170)), "");
+    assert_eq!(text(simulator.key_up_at(VirtualKey::OEM_FJ_ROYA, 180)), "");
+
+    // Releasing the key, or pressing another, ends the wait too.
+    assert_eq!(text(simulator.key_down_at(VK_A, 200)), "");
+    assert_eq!(text(simulator.key_up_at(VK_A, 220)), "う");
+    assert_eq!(text(simulator.key_down_at(VK_S, 230)), "");
+    assert_eq!(text(simulator.key_down_at(VK_A, 240)), "し");
+    assert_eq!(text(simulator.key_up_at(VK_S, 250)), "");
+    assert_eq!(text(simulator.key_up_at(VK_A, 260)), "う");
+}
+
+#[test]
+fn reports_thumb_layers_without_keys() {
+    let mut keyboard_desc = read_layout_fixture("nicola");
+    keyboard_desc.virtual_keys.shift_remove(&VirtualKey::OEM_FJ_ROYA);
+    keyboard_desc.physical_keys.retain(|_, physical_key| physical_key.virtual_key != VirtualKey::OEM_FJ_LOYA);
+    let issues: Vec<String> = keyboard_desc.validate().iter().map(|issue| issue.to_string()).collect();
+    assert_eq!(issues, vec![
+        "Keys type on the right thumb shift layer but no modifier key enables it.",
+        "Thumb shift key VK_OEM_FJ_LOYA is not mapped to any scan code.",
+    ]);
+}
+
+#[test]
+fn reports_dead_key_cycles_once() {
+    let mut keyboard_desc = read_layout_fixture("kbdtest");
+    assert!(keyboard_desc.validate().is_empty());
+    // '^' + '¨' enters 'ˇ', which now chains back to '^', and to itself.
+    let caron = keyboard_desc.dead_keys.get_mut(&WideChar::Scalar('ˇ')).unwrap();
+    caron.combos.insert(WideChar::Scalar('^'), DeadKeyCombo::DeadKey(WideChar::Scalar('^')));
+    caron.combos.insert(WideChar::Scalar('ˇ'), DeadKeyCombo::DeadKey(WideChar::Scalar('ˇ')));
+    let issues: Vec<String> = keyboard_desc.validate().iter().map(|issue| issue.to_string()).collect();
+    assert_eq!(issues, vec![
+        "Dead key combos form a cycle: '^' -> 'ˇ' -> '^'.",
+        "Dead key combos form a cycle: 'ˇ' -> 'ˇ'.",
+    ]);
+}
+
+#[test]
+fn kana_key_toggles_kana_lock() {
+    let mut keyboard_desc = read_layout_fixture("nicola");
+    let KeyEffect::Typing(key_typing) = keyboard_desc.virtual_keys.get_mut(&VK_S).unwrap() else { panic!() };
+    key_typing.kana_support = false;
+
+    let mut simulator = TypingSimulator::new(&keyboard_desc);
+    assert_eq!(keyboard_desc.kana_toggle_keys(), vec![VirtualKey::KANA]);
+    simulator.type_key(VirtualKey::KANA);
+    assert!(simulator.kana_lock());
+
+    // Only the keys supporting kana lock (KANALOK) type on their kana column.
+    assert_eq!(text(simulator.type_key(VK_A)), "ち");
+    assert_eq!(text(simulator.type_key(VK_S)), "し");
+
+    simulator.type_key(VirtualKey::KANA);
+    assert!(!simulator.kana_lock());
+    assert_eq!(text(simulator.type_key(VK_A)), "う");
+}
+
+#[test]
+fn reports_kana_columns_without_kana_key() {
+    let mut keyboard_desc = read_layout_fixture("nicola");
+    keyboard_desc.virtual_keys.shift_remove(&VirtualKey::KANA);
+    let issues: Vec<String> = keyboard_desc.validate().iter().map(|issue| issue.to_string()).collect();
+    assert_eq!(issues, vec!["Keys type with the kana modifier but no modifier key toggles kana lock."]);
+}
+
+#[test]
+fn shift_backspace_types_directional_marks() {
+    let keyboard_desc = read_layout_fixture("minimal_hebrew");
+    let mut simulator = TypingSimulator::new(&keyboard_desc);
+    simulator.key_down(VirtualKey::LEFT_SHIFT);
+    assert_eq!(text(simulator.type_key(VirtualKey::BACKSPACE)), "\u{200E}");
+    simulator.key_up(VirtualKey::LEFT_SHIFT);
+
+    simulator.key_down(VirtualKey::RIGHT_SHIFT);
+    assert_eq!(text(simulator.type_key(VirtualKey::BACKSPACE)), "\u{200F}");
+    simulator.key_up(VirtualKey::RIGHT_SHIFT);
+    assert_eq!(text(simulator.type_key(VirtualKey::BACKSPACE)), "\u{8}");
+
+    // Without the flag, Shift+Backspace types what the tables say.
+    let mut keyboard_desc = read_layout_fixture("minimal_hebrew");
+    keyboard_desc.supports_directionality = false;
+    let mut simulator = TypingSimulator::new(&keyboard_desc);
+    simulator.key_down(VirtualKey::LEFT_SHIFT);
+    assert_eq!(text(simulator.type_key(VirtualKey::BACKSPACE)), "\u{8}");
+}
@@ -0,0 +1,84 @@
+// Checks that the ToUnicodeEx emulation returns what Windows does for key events
+// on the kbdtest fixture, including dead key states kept between calls.
+
+mod common;
+
+use common::*;
+use kbdc::simulator::{UnicodeTranslator, TO_UNICODE_KEEP_STATE};
+
+const VK_SHIFT: usize = 0x10;
+const VK_CONTROL: usize = 0x11;
+const VK_MENU: usize = 0x12;
+const VK_CAPITAL: usize = 0x14;
+const VK_OEM_4: u32 = 0xDB;
+
+fn key_state(held: &[usize]) -> [u8; 256] {
+    let mut key_state = [0; 256];
+    for virtual_key in held {
+        key_state[*virtual_key] = 0x80;
+    }
+    key_state
+}
+
+fn units(text: &str) -> Vec<u16> {
+    text.encode_utf16().collect()
+}
+
+#[test]
+fn translates_characters_and_control_characters() {
+    let keyboard_desc = read_layout_fixture("kbdtest");
+    let mut translator = UnicodeTranslator::new(&keyboard_desc);
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E, &key_state(&[]), 0), (1, units("a")));
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E, &key_state(&[VK_SHIFT]), 0), (1, units("A")));
+    assert_eq!(translator.to_unicode_ex('Q' as u32, 0x10, &key_state(&[VK_CONTROL]), 0), (1, vec![0x11]));
+
+    // Characters beyond the BMP count as their two surrogates.
+    assert_eq!(translator.to_unicode_ex('3' as u32, 0x04, &key_state(&[VK_CONTROL, VK_MENU]), 0), (2, units("😀")));
+
+    let mut caps_lock = key_state(&[]);
+    caps_lock[VK_CAPITAL] = 0x01;
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E, &caps_lock, 0), (1, units("A")));
+
+    // Releases, modifier keys and invalid shift states type nothing.
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E | 0x8000, &key_state(&[]), 0), (0, vec![]));
+    assert_eq!(translator.to_unicode_ex(VK_SHIFT as u32, 0x2A, &key_state(&[VK_SHIFT]), 0), (0, vec![]));
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E, &key_state(&[VK_SHIFT, VK_CONTROL]), 0), (0, vec![]));
+}
+
+#[test]
+fn translates_dead_keys() {
+    let keyboard_desc = read_layout_fixture("kbdtest");
+    let mut translator = UnicodeTranslator::new(&keyboard_desc);
+    let none = key_state(&[]);
+
+    // A dead key writes its spacing character and returns -1, then combines with the next key.
+    assert_eq!(translator.to_unicode_ex(VK_OEM_4, 0x1A, &none, 0), (-1, units("^")));
+    assert_eq!(translator.to_unicode_ex(VK_SHIFT as u32, 0x2A, &key_state(&[VK_SHIFT]), 0), (0, vec![]));
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E, &none, 0), (1, units("â")));
+
+    // Keys it does not combine with follow its character.
+    assert_eq!(translator.to_unicode_ex(VK_OEM_4, 0x1A, &none, 0), (-1, units("^")));
+    assert_eq!(translator.to_unicode_ex('Q' as u32, 0x10, &none, 0), (2, units("^q")));
+
+    // Chained dead keys return -1 again.
+    assert_eq!(translator.to_unicode_ex(VK_OEM_4, 0x1A, &none, 0), (-1, units("^")));
+    assert_eq!(translator.to_unicode_ex(VK_OEM_4, 0x1A, &key_state(&[VK_SHIFT]), 0), (-1, units("ˇ")));
+    assert_eq!(translator.to_unicode_ex('E' as u32, 0x12, &none, 0), (1, units("ě")));
+    assert_eq!(translator.dead_key(), None);
+}
+
+#[test]
+fn keeps_dead_key_state_when_asked() {
+    let keyboard_desc = read_layout_fixture("kbdtest");
+    let mut translator = UnicodeTranslator::new(&keyboard_desc);
+    let none = key_state(&[]);
+
+    assert_eq!(translator.to_unicode_ex(VK_OEM_4, 0x1A, &none, TO_UNICODE_KEEP_STATE), (-1, units("^")));
+    assert_eq!(translator.dead_key(), None);
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E, &none, 0), (1, units("a")));
+
+    // Peeking at a composition leaves the dead key pending.
+    translator.to_unicode_ex(VK_OEM_4, 0x1A, &none, 0);
+    assert_eq!(translator.to_unicode_ex('A' as u32, 0x1E, &none, TO_UNICODE_KEEP_STATE), (1, units("â")));
+    assert_eq!(translator.to_unicode_ex('E' as u32, 0x12, &none, 0), (1, units("ê")));
+}
@@ -0,0 +1,60 @@
+// Runs the keystroke traces under tests/fixtures/traces over the fixture layouts they name,
+// checking the text typed by each step.
+
+mod common;
+
+use common::*;
+use kbdc::model::*;
+use kbdc::simulator::*;
+
+#[test]
+fn traces_type_the_expected_text() {
+    let mut paths: Vec<_> = std::fs::read_dir(test_path("fixtures/traces")).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "trace"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    let mut failures = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let trace = Trace::parse(&text).unwrap_or_else(|error| panic!("Failed to parse {}: {}", path.display(), error));
+        let layout = trace.layout.as_deref().unwrap_or_else(|| panic!("{} names no layout", path.display()));
+        for mismatch in trace.run(&read_layout_fixture(layout)) {
+            failures.push(format!("{}: {}", path.display(), mismatch));
+        }
+    }
+    assert!(failures.is_empty(), "Traces failed:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn reports_mismatches() {
+    let trace = Trace::parse("type 1E\nexpect \"b\"\n# Left over\ntype 1E\n").unwrap();
+    let mismatches = trace.run(&read_layout_fixture("kbdtest"));
+    assert_eq!(mismatches, vec![
+        TraceMismatch { line: 2, expected: "b".to_string(), actual: "a".to_string() },
+        TraceMismatch { line: 4, expected: String::new(), actual: "a".to_string() },
+    ]);
+    assert_eq!(mismatches[0].to_string(), "line 2: expected \"b\", typed \"a\"");
+}
+
+#[test]
+fn parses_scan_codes_and_times() {
+    let trace = Trace::parse("down E0 38 @5\nup E1 1D\ntype 2A\n").unwrap();
+    let steps: Vec<_> = trace.steps.iter().map(|(line, step)| match step {
+        TraceStep::KeyDown { scan_code, time } => (*line, "down", *scan_code, *time),
+        TraceStep::KeyUp { scan_code, time } => (*line, "up", *scan_code, *time),
+        TraceStep::Expect(_) => unreachable!(),
+    }).collect();
+    assert_eq!(steps, vec![
+        (1, "down", ScanCode::Extended0(0x38), Some(5)),
+        (2, "up", ScanCode::Extended1(0x1D), None),
+        (3, "down", ScanCode::Unescaped(0x2A), None),
+        (3, "up", ScanCode::Unescaped(0x2A), None),
+    ]);
+
+    let error = Trace::parse("type 1E\ndown E2 38\n").err().unwrap();
+    assert_eq!(error.to_string(), "line 2: Invalid scan code: E2 38");
+    assert_eq!(Trace::parse("press 1E").err().unwrap().to_string(), "line 1: Unknown step: press 1E");
+}
@@ -0,0 +1,119 @@
+// Checks the virtual key catalogue: names and aliases parse back to their codes,
+// and layouts using any code, named or not, survive a JSON round trip.
+// Also checks how scan code flags change the virtual key a scan code resolves to.
+
+mod common;
+
+use common::*;
+use indexmap::IndexMap;
+use kbdc::model::*;
+
+#[test]
+fn names_and_aliases_parse_ignoring_case() {
+    assert_eq!(VirtualKey::from_vk_enum("VK_KANA", true), Some(VirtualKey { code: 0x15 }));
+    assert_eq!(VirtualKey::from_vk_enum("VK_HANGUL", true), Some(VirtualKey { code: 0x15 }));
+    assert_eq!(VirtualKey::from_vk_enum("vk_kanji", true), Some(VirtualKey { code: 0x19 }));
+    assert_eq!(VirtualKey::from_vk_enum("VK_DBE_HIRAGANA", true), Some(VirtualKey { code: 0xF2 }));
+    assert_eq!(VirtualKey::from_vk_enum("vk_q", true), Some(VirtualKey { code: b'Q' }));
+    assert_eq!(VirtualKey::from_vk_enum("VK_Q", false), None);
+    assert_eq!(VirtualKey::from_vk_enum("VK_NOT_A_KEY", true), None);
+
+    // Aliases never take over the primary name.
+    assert_eq!(VirtualKey { code: 0x15 }.to_vk_enum(true).as_deref(), Some("VK_KANA"));
+    assert_eq!(VirtualKey { code: 0x19 }.to_vk_enum(true).as_deref(), Some("VK_HANJA"));
+}
+
+#[test]
+fn catalogue_names_are_unique() {
+    let mut names = Vec::new();
+    for code in 0..=0xFF {
+        let Some(info) = (VirtualKey { code }).info() else { continue };
+        assert_eq!(info.code, code);
+        names.push(info.name.to_ascii_uppercase());
+        names.extend(info.aliases.iter().map(|alias| alias.to_ascii_uppercase()));
+        for name in std::iter::once(&info.name).chain(info.aliases) {
+            assert_eq!(VirtualKey::from_vk_enum(name, true), Some(VirtualKey { code }), "{} does not parse back", name);
+        }
+    }
+
+    let count = names.len();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), count);
+}
+
+#[test]
+fn catalogue_covers_oem_ime_and_gamepad_ranges() {
+    let category = |code| (VirtualKey { code }).info().map(|info| info.category);
+    assert_eq!(category(0xE1), Some(VirtualKeyCategory::System)); // VK_OEM_AX
+    assert_eq!(category(0xE9), Some(VirtualKeyCategory::System)); // VK_OEM_RESET
+    assert_eq!(category(0xF5), Some(VirtualKeyCategory::Ime)); // VK_OEM_BACKTAB, VK_DBE_ROMAN
+    assert_eq!(category(0xC3), Some(VirtualKeyCategory::Gamepad)); // VK_GAMEPAD_A
+    assert_eq!(category(0x8A), Some(VirtualKeyCategory::Navigation)); // VK_NAVIGATION_UP
+    assert_eq!(category(0xB3), Some(VirtualKeyCategory::Media)); // VK_MEDIA_PLAY_PAUSE
+    assert_eq!(category(0x07), None);
+}
+
+#[test]
+fn every_virtual_key_survives_json() {
+    let mut keyboard_desc = KeyboardDesc::new();
+    for code in 0x01..=0xFE {
+        let mut by_modifiers = IndexMap::new();
+        by_modifiers.insert(KeyModifiers::from_bits(0), TypingEffect::Char(WideChar::Scalar('x')));
+        keyboard_desc.virtual_keys.insert(VirtualKey { code }, KeyEffect::Typing(KeyTyping {
+            by_modifiers,
+            caps_lock_as_shift: false,
+            caps_lock_as_uppercase: false,
+            caps_lock_layer: IndexMap::new(),
+            caps_lock_altgr_as_shift: false,
+            kana_support: false,
+            grpseltap_support: false,
+            group: None,
+        }));
+    }
+
+    let read = KeyboardDesc::from_json(&keyboard_desc.to_json()).unwrap();
+    let codes: Vec<u8> = read.virtual_keys.keys().map(|virtual_key| virtual_key.code).collect();
+    assert_eq!(codes, (0x01..=0xFE).collect::<Vec<u8>>());
+}
+
+#[test]
+fn virtual_key_flags_survive_json() {
+    let mut keyboard_desc = read_layout_fixture("minimal_iso");
+    for (bits, physical_key) in (0..=0xFF).step_by(0x11).zip(keyboard_desc.physical_keys.values_mut()) {
+        physical_key.virtual_key_flags = VirtualKeyFlags::from_bits(bits);
+    }
+
+    let read = KeyboardDesc::from_json(&keyboard_desc.to_json()).unwrap();
+    for (scan_code, physical_key) in &keyboard_desc.physical_keys {
+        assert_eq!(read.physical_keys[scan_code].virtual_key_flags.to_bits(), physical_key.virtual_key_flags.to_bits());
+    }
+}
+
+#[test]
+fn resolves_special_keys() {
+    let mut keyboard_desc = read_layout_fixture("kbdtest");
+    keyboard_desc.physical_keys.insert(ScanCode::NUMPAD_7, PhysicalKeyDesc {
+        virtual_key: VirtualKey { code: 0x24 }, // VK_HOME
+        virtual_key_flags: VirtualKeyFlags::from_bits(0x0C), // KBDSPECIAL | KBDNUMPAD
+        name: None,
+    });
+    let none = KeyModifiers::from_bits(0);
+    let shift = KeyModifiers { shift: true, ..none };
+    let control = KeyModifiers { control: true, ..none };
+    let resolve = |scan_code, modifiers: &KeyModifiers, num_lock| keyboard_desc.resolve_virtual_key(scan_code, modifiers, num_lock)
+        .map(|virtual_key| virtual_key.code);
+
+    // Keypad keys type digits with Num Lock on, unless Shift is held.
+    assert_eq!(resolve(ScanCode::NUMPAD_7, &none, false), Some(0x24));
+    assert_eq!(resolve(ScanCode::NUMPAD_7, &none, true), Some(0x67)); // VK_NUMPAD7
+    assert_eq!(resolve(ScanCode::NUMPAD_7, &shift, true), Some(0x24));
+
+    // Ctrl+Num Lock is Pause (KBDMULTIVK).
+    assert_eq!(resolve(ScanCode::NUM_LOCK, &none, false), Some(0x90));
+    assert_eq!(resolve(ScanCode::NUM_LOCK, &control, true), Some(0x13));
+
+    // Other keys ignore the modifiers and unmapped scan codes resolve to nothing.
+    assert_eq!(resolve(ScanCode::A, &control, true), Some(b'A'));
+    assert_eq!(resolve(ScanCode::F24, &none, false), None);
+}